@@ -0,0 +1,212 @@
+use erg_common::traits::Locational;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::hir::Def;
+use erg_compiler::varinfo::AbsLocation;
+
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams, Range,
+    SymbolKind,
+};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::symbol::{container_name, symbol_kind};
+use crate::util::{self, NormalizedUrl};
+
+fn call_hierarchy_item(def: &Def, uri: &NormalizedUrl) -> Option<CallHierarchyItem> {
+    let ident = def.sig.ident();
+    let selection_range = util::loc_to_range(ident.loc())?;
+    let range = util::loc_to_range(def.loc()).unwrap_or(selection_range);
+    let in_type = container_name(&ident.vi).is_some();
+    Some(CallHierarchyItem {
+        name: ident.inspect().to_string(),
+        kind: symbol_kind(&ident.vi, Some(def.def_kind()), in_type),
+        tags: None,
+        detail: Some(ident.vi.t.to_string()),
+        uri: uri.clone().raw(),
+        range,
+        selection_range,
+        data: None,
+    })
+}
+
+/// The item representing the top level of a module (calls outside of any subroutine)
+fn module_item(uri: &NormalizedUrl) -> CallHierarchyItem {
+    let path = util::uri_to_path(uri);
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    CallHierarchyItem {
+        name,
+        kind: SymbolKind::FILE,
+        tags: None,
+        detail: None,
+        uri: uri.clone().raw(),
+        range: Range::default(),
+        selection_range: Range::default(),
+        data: None,
+    }
+}
+
+/// Adds `range` to the call from/to `item` (calls to the same subroutine are grouped).
+fn push_call(
+    calls: &mut Vec<(CallHierarchyItem, Vec<Range>)>,
+    item: CallHierarchyItem,
+    range: Range,
+) {
+    if let Some((_, ranges)) = calls
+        .iter_mut()
+        .find(|(other, _)| other.uri == item.uri && other.selection_range == item.selection_range)
+    {
+        ranges.push(range);
+    } else {
+        calls.push((item, vec![range]));
+    }
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    /// Returns the item of the subroutine defined at `def_loc`.
+    fn def_loc_to_item(&self, def_loc: &AbsLocation) -> Option<CallHierarchyItem> {
+        let uri = NormalizedUrl::from_file_path(def_loc.module.as_ref()?).ok()?;
+        let pos = util::loc_to_range(def_loc.loc)?.start;
+        let visitor = self.get_cached_visitor(&uri)?;
+        let def = visitor.get_enclosing_def(pos)?;
+        if def.sig.ident().loc() != def_loc.loc {
+            return None;
+        }
+        call_hierarchy_item(def, &uri)
+    }
+
+    /// Returns the definition location of the subroutine `item` represents.
+    fn item_to_def_loc(&self, item: &CallHierarchyItem) -> Option<AbsLocation> {
+        let uri = NormalizedUrl::new(item.uri.clone());
+        let visitor = self.get_cached_visitor(&uri)?;
+        let def = visitor.get_enclosing_def(item.selection_range.start)?;
+        Some(def.sig.ident().vi.def_loc.clone())
+    }
+
+    pub(crate) fn handle_call_hierarchy_prepare(
+        &mut self,
+        params: CallHierarchyPrepareParams,
+    ) -> ELSResult<Option<Vec<CallHierarchyItem>>> {
+        send_log(format!("call hierarchy preparation requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let Some(token) = self.file_cache.get_token(&uri, pos) else {
+            return Ok(None);
+        };
+        let Some(vi) = self
+            .get_visitor(&uri)
+            .and_then(|visitor| visitor.get_info(&token))
+        else {
+            return Ok(None);
+        };
+        if !vi.t.is_subr() {
+            return Ok(None);
+        }
+        Ok(self.def_loc_to_item(&vi.def_loc).map(|item| vec![item]))
+    }
+
+    /// Finds the references to the subroutine that are called, and groups them by the enclosing definitions.
+    pub(crate) fn handle_call_hierarchy_incoming(
+        &mut self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> ELSResult<Option<Vec<CallHierarchyIncomingCall>>> {
+        send_log(format!("incoming calls requested: {params:?}"))?;
+        if params.item.kind == SymbolKind::FILE {
+            return Ok(Some(vec![]));
+        }
+        let Some(def_loc) = self.item_to_def_loc(&params.item) else {
+            return Ok(None);
+        };
+        let Some(referrers) = self
+            .get_index()
+            .and_then(|index| index.get_refs(&def_loc))
+            .map(|value| value.referrers.iter().cloned().collect::<Vec<_>>())
+        else {
+            return Ok(None);
+        };
+        let mut calls = vec![];
+        for referrer in referrers {
+            if self.is_cancelled() {
+                return Ok(None);
+            }
+            let Some(path) = referrer.module.as_ref() else {
+                continue;
+            };
+            let (Ok(uri), Some(range)) = (
+                NormalizedUrl::from_file_path(path),
+                util::loc_to_range(referrer.loc),
+            ) else {
+                continue;
+            };
+            let Some(visitor) = self.get_cached_visitor(&uri) else {
+                continue;
+            };
+            let caller = visitor.get_enclosing_def(range.start);
+            // exclude the references that are not calls (e.g. `g = f`)
+            let is_called = visitor
+                .get_callees(caller)
+                .iter()
+                .any(|callee| callee.loc() == referrer.loc);
+            if !is_called {
+                continue;
+            }
+            let item = match caller {
+                Some(def) => call_hierarchy_item(def, &uri),
+                None => Some(module_item(&uri)),
+            };
+            if let Some(item) = item {
+                push_call(&mut calls, item, range);
+            }
+        }
+        let calls = calls
+            .into_iter()
+            .map(|(from, from_ranges)| CallHierarchyIncomingCall { from, from_ranges })
+            .collect();
+        Ok(Some(calls))
+    }
+
+    pub(crate) fn handle_call_hierarchy_outgoing(
+        &mut self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> ELSResult<Option<Vec<CallHierarchyOutgoingCall>>> {
+        send_log(format!("outgoing calls requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.item.uri.clone());
+        let Some(visitor) = self.get_cached_visitor(&uri) else {
+            return Ok(None);
+        };
+        let caller = if params.item.kind == SymbolKind::FILE {
+            None
+        } else {
+            let Some(def) = visitor.get_enclosing_def(params.item.selection_range.start) else {
+                return Ok(None);
+            };
+            Some(def)
+        };
+        let callees = visitor
+            .get_callees(caller)
+            .into_iter()
+            .filter(|callee| callee.vi.def_loc != AbsLocation::unknown())
+            .filter_map(|callee| {
+                let range = util::loc_to_range(callee.loc())?;
+                Some((callee.vi.def_loc.clone(), range))
+            })
+            .collect::<Vec<_>>();
+        drop(visitor);
+        let mut calls = vec![];
+        for (def_loc, range) in callees {
+            if let Some(item) = self.def_loc_to_item(&def_loc) {
+                push_call(&mut calls, item, range);
+            }
+        }
+        let calls = calls
+            .into_iter()
+            .map(|(to, from_ranges)| CallHierarchyOutgoingCall { to, from_ranges })
+            .collect();
+        Ok(Some(calls))
+    }
+}
@@ -0,0 +1,118 @@
+use erg_common::dict::Dict;
+use erg_common::shared::{CancellationToken, Shared};
+
+use serde_json::Value;
+
+use crate::util::NormalizedUrl;
+
+/// The error code of the responses to cancelled requests (LSP `RequestCancelled`)
+pub const REQUEST_CANCELLED: i64 = -32800;
+
+#[derive(Debug, Clone)]
+struct PendingRequest {
+    method: &'static str,
+    /// the target document (`params.textDocument.uri`)
+    uri: Option<NormalizedUrl>,
+    /// the version of the target document when the request was received
+    version: Option<i32>,
+    /// the params except for the progress tokens, which differ for each request
+    params: Value,
+    token: CancellationToken,
+}
+
+impl PendingRequest {
+    /// The result of this request is stale (the document has been changed since) or the same as that of the new request.
+    fn is_superseded_by(&self, new: &PendingRequest) -> bool {
+        self.method == new.method
+            && self.uri.is_some()
+            && self.uri == new.uri
+            && (self.version != new.version || self.params == new.params)
+    }
+}
+
+/// The requests which are queued or being processed by the workers.
+#[derive(Debug, Clone, Default)]
+pub struct PendingRequests(Shared<Dict<i64, PendingRequest>>);
+
+impl PendingRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The pending requests of the same method for the same document are cancelled
+    /// if they target an older version of the document or have the same params as the new one.
+    pub fn register(
+        &self,
+        id: i64,
+        method: &'static str,
+        uri: Option<NormalizedUrl>,
+        version: Option<i32>,
+        params: &Value,
+    ) {
+        let mut params = params.clone();
+        if let Some(params) = params.as_object_mut() {
+            params.remove("workDoneToken");
+            params.remove("partialResultToken");
+        }
+        let new = PendingRequest {
+            method,
+            uri,
+            version,
+            params,
+            token: CancellationToken::new(),
+        };
+        let mut requests = self.0.borrow_mut();
+        for req in requests.values() {
+            if req.is_superseded_by(&new) {
+                req.token.cancel();
+            }
+        }
+        requests.insert(id, new);
+    }
+
+    /// `$/cancelRequest`
+    pub fn cancel(&self, id: i64) {
+        if let Some(req) = self.0.borrow().get(&id) {
+            req.token.cancel();
+        }
+    }
+
+    pub fn get_token(&self, id: i64) -> Option<CancellationToken> {
+        self.0.borrow().get(&id).map(|req| req.token.clone())
+    }
+
+    pub fn remove(&self, id: i64) {
+        self.0.borrow_mut().remove(&id);
+    }
+}
+
+/// The files being checked.
+/// A check is cancelled only when another check of the same file is started, since the result will be stale.
+/// Edits alone do not cancel it, because they may not schedule a new check.
+#[derive(Debug, Clone, Default)]
+pub struct PendingChecks(Shared<Dict<NormalizedUrl, CancellationToken>>);
+
+impl PendingChecks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&self, uri: &NormalizedUrl) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(old) = self.0.borrow_mut().insert(uri.clone(), token.clone()) {
+            old.cancel();
+        }
+        token
+    }
+
+    /// Removes the check if it has not been superseded by another one.
+    pub fn finish(&self, uri: &NormalizedUrl, token: &CancellationToken) {
+        let mut checks = self.0.borrow_mut();
+        if checks
+            .get(uri)
+            .is_some_and(|current| current.same_as(token))
+        {
+            checks.remove(uri);
+        }
+    }
+}
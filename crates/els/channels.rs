@@ -4,17 +4,33 @@ use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::erg_parser::parse::Parsable;
 
 use lsp_types::request::{
-    CodeActionRequest, CodeActionResolveRequest, CodeLensRequest, Completion, ExecuteCommand,
-    GotoDefinition, HoverRequest, InlayHintRequest, InlayHintResolveRequest, References,
-    ResolveCompletionItem, SemanticTokensFullRequest, SignatureHelpRequest, WillRenameFiles,
+    CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+    CodeActionRequest, CodeActionResolveRequest, CodeLensRequest, Completion,
+    DocumentHighlightRequest, DocumentSymbolRequest, ExecuteCommand, FoldingRangeRequest,
+    Formatting, GotoDefinition, GotoImplementation, GotoTypeDefinition, HoverRequest,
+    InlayHintRequest, InlayHintResolveRequest, OnTypeFormatting, RangeFormatting, References,
+    ResolveCompletionItem, SelectionRangeRequest, SemanticTokensFullRequest, SignatureHelpRequest,
+    WillRenameFiles, WorkspaceSymbol,
 };
 use lsp_types::{
+    CallHierarchyIncomingCallsParams, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
     CodeAction, CodeActionParams, CodeLensParams, CompletionItem, CompletionParams,
-    ExecuteCommandParams, GotoDefinitionParams, HoverParams, InlayHint, InlayHintParams,
-    ReferenceParams, RenameFilesParams, SemanticTokensParams, SignatureHelpParams,
+    DocumentFormattingParams, DocumentHighlightParams, DocumentOnTypeFormattingParams,
+    DocumentRangeFormattingParams, DocumentSymbolParams, ExecuteCommandParams, FoldingRangeParams,
+    GotoDefinitionParams, HoverParams, InlayHint, InlayHintParams, ReferenceParams,
+    RenameFilesParams, SelectionRangeParams, SemanticTokensParams, SignatureHelpParams,
+    WorkspaceSymbolParams,
 };
 
+use crate::diagnostics::{
+    DocumentDiagnosticParams, DocumentDiagnosticRequest, WorkspaceDiagnosticParams,
+    WorkspaceDiagnosticRequest,
+};
 use crate::server::Server;
+use crate::type_hierarchy::{
+    TypeHierarchyPrepare, TypeHierarchyPrepareParams, TypeHierarchySubtypes,
+    TypeHierarchySubtypesParams, TypeHierarchySupertypes, TypeHierarchySupertypesParams,
+};
 
 #[derive(Debug, Clone)]
 pub enum WorkerMessage<P> {
@@ -44,6 +60,24 @@ pub struct SendChannels {
     signature_help: mpsc::Sender<WorkerMessage<SignatureHelpParams>>,
     will_rename_files: mpsc::Sender<WorkerMessage<RenameFilesParams>>,
     execute_command: mpsc::Sender<WorkerMessage<ExecuteCommandParams>>,
+    document_symbol: mpsc::Sender<WorkerMessage<DocumentSymbolParams>>,
+    workspace_symbol: mpsc::Sender<WorkerMessage<WorkspaceSymbolParams>>,
+    formatting: mpsc::Sender<WorkerMessage<DocumentFormattingParams>>,
+    range_formatting: mpsc::Sender<WorkerMessage<DocumentRangeFormattingParams>>,
+    on_type_formatting: mpsc::Sender<WorkerMessage<DocumentOnTypeFormattingParams>>,
+    call_hierarchy_prepare: mpsc::Sender<WorkerMessage<CallHierarchyPrepareParams>>,
+    call_hierarchy_incoming: mpsc::Sender<WorkerMessage<CallHierarchyIncomingCallsParams>>,
+    call_hierarchy_outgoing: mpsc::Sender<WorkerMessage<CallHierarchyOutgoingCallsParams>>,
+    type_hierarchy_prepare: mpsc::Sender<WorkerMessage<TypeHierarchyPrepareParams>>,
+    type_hierarchy_supertypes: mpsc::Sender<WorkerMessage<TypeHierarchySupertypesParams>>,
+    type_hierarchy_subtypes: mpsc::Sender<WorkerMessage<TypeHierarchySubtypesParams>>,
+    goto_type_definition: mpsc::Sender<WorkerMessage<GotoDefinitionParams>>,
+    goto_implementation: mpsc::Sender<WorkerMessage<GotoDefinitionParams>>,
+    document_highlight: mpsc::Sender<WorkerMessage<DocumentHighlightParams>>,
+    folding_range: mpsc::Sender<WorkerMessage<FoldingRangeParams>>,
+    selection_range: mpsc::Sender<WorkerMessage<SelectionRangeParams>>,
+    document_diagnostic: mpsc::Sender<WorkerMessage<DocumentDiagnosticParams>>,
+    workspace_diagnostic: mpsc::Sender<WorkerMessage<WorkspaceDiagnosticParams>>,
     pub(crate) health_check: mpsc::Sender<WorkerMessage<()>>,
 }
 
@@ -63,6 +97,24 @@ impl SendChannels {
         let (tx_sig_help, rx_sig_help) = mpsc::channel();
         let (tx_will_rename_files, rx_will_rename_files) = mpsc::channel();
         let (tx_execute_command, rx_execute_command) = mpsc::channel();
+        let (tx_document_symbol, rx_document_symbol) = mpsc::channel();
+        let (tx_workspace_symbol, rx_workspace_symbol) = mpsc::channel();
+        let (tx_formatting, rx_formatting) = mpsc::channel();
+        let (tx_range_formatting, rx_range_formatting) = mpsc::channel();
+        let (tx_on_type_formatting, rx_on_type_formatting) = mpsc::channel();
+        let (tx_call_hierarchy_prepare, rx_call_hierarchy_prepare) = mpsc::channel();
+        let (tx_call_hierarchy_incoming, rx_call_hierarchy_incoming) = mpsc::channel();
+        let (tx_call_hierarchy_outgoing, rx_call_hierarchy_outgoing) = mpsc::channel();
+        let (tx_type_hierarchy_prepare, rx_type_hierarchy_prepare) = mpsc::channel();
+        let (tx_type_hierarchy_supertypes, rx_type_hierarchy_supertypes) = mpsc::channel();
+        let (tx_type_hierarchy_subtypes, rx_type_hierarchy_subtypes) = mpsc::channel();
+        let (tx_goto_type_definition, rx_goto_type_definition) = mpsc::channel();
+        let (tx_goto_implementation, rx_goto_implementation) = mpsc::channel();
+        let (tx_document_highlight, rx_document_highlight) = mpsc::channel();
+        let (tx_folding_range, rx_folding_range) = mpsc::channel();
+        let (tx_selection_range, rx_selection_range) = mpsc::channel();
+        let (tx_document_diagnostic, rx_document_diagnostic) = mpsc::channel();
+        let (tx_workspace_diagnostic, rx_workspace_diagnostic) = mpsc::channel();
         let (tx_health_check, rx_health_check) = mpsc::channel();
         (
             Self {
@@ -80,6 +132,24 @@ impl SendChannels {
                 signature_help: tx_sig_help,
                 will_rename_files: tx_will_rename_files,
                 execute_command: tx_execute_command,
+                document_symbol: tx_document_symbol,
+                workspace_symbol: tx_workspace_symbol,
+                formatting: tx_formatting,
+                range_formatting: tx_range_formatting,
+                on_type_formatting: tx_on_type_formatting,
+                call_hierarchy_prepare: tx_call_hierarchy_prepare,
+                call_hierarchy_incoming: tx_call_hierarchy_incoming,
+                call_hierarchy_outgoing: tx_call_hierarchy_outgoing,
+                type_hierarchy_prepare: tx_type_hierarchy_prepare,
+                type_hierarchy_supertypes: tx_type_hierarchy_supertypes,
+                type_hierarchy_subtypes: tx_type_hierarchy_subtypes,
+                goto_type_definition: tx_goto_type_definition,
+                goto_implementation: tx_goto_implementation,
+                document_highlight: tx_document_highlight,
+                folding_range: tx_folding_range,
+                selection_range: tx_selection_range,
+                document_diagnostic: tx_document_diagnostic,
+                workspace_diagnostic: tx_workspace_diagnostic,
                 health_check: tx_health_check,
             },
             ReceiveChannels {
@@ -97,6 +167,24 @@ impl SendChannels {
                 signature_help: rx_sig_help,
                 will_rename_files: rx_will_rename_files,
                 execute_command: rx_execute_command,
+                document_symbol: rx_document_symbol,
+                workspace_symbol: rx_workspace_symbol,
+                formatting: rx_formatting,
+                range_formatting: rx_range_formatting,
+                on_type_formatting: rx_on_type_formatting,
+                call_hierarchy_prepare: rx_call_hierarchy_prepare,
+                call_hierarchy_incoming: rx_call_hierarchy_incoming,
+                call_hierarchy_outgoing: rx_call_hierarchy_outgoing,
+                type_hierarchy_prepare: rx_type_hierarchy_prepare,
+                type_hierarchy_supertypes: rx_type_hierarchy_supertypes,
+                type_hierarchy_subtypes: rx_type_hierarchy_subtypes,
+                goto_type_definition: rx_goto_type_definition,
+                goto_implementation: rx_goto_implementation,
+                document_highlight: rx_document_highlight,
+                folding_range: rx_folding_range,
+                selection_range: rx_selection_range,
+                document_diagnostic: rx_document_diagnostic,
+                workspace_diagnostic: rx_workspace_diagnostic,
                 health_check: rx_health_check,
             },
         )
@@ -117,6 +205,36 @@ impl SendChannels {
         self.signature_help.send(WorkerMessage::Kill).unwrap();
         self.will_rename_files.send(WorkerMessage::Kill).unwrap();
         self.execute_command.send(WorkerMessage::Kill).unwrap();
+        self.document_symbol.send(WorkerMessage::Kill).unwrap();
+        self.workspace_symbol.send(WorkerMessage::Kill).unwrap();
+        self.formatting.send(WorkerMessage::Kill).unwrap();
+        self.range_formatting.send(WorkerMessage::Kill).unwrap();
+        self.on_type_formatting.send(WorkerMessage::Kill).unwrap();
+        self.goto_type_definition.send(WorkerMessage::Kill).unwrap();
+        self.goto_implementation.send(WorkerMessage::Kill).unwrap();
+        self.document_highlight.send(WorkerMessage::Kill).unwrap();
+        self.folding_range.send(WorkerMessage::Kill).unwrap();
+        self.selection_range.send(WorkerMessage::Kill).unwrap();
+        self.document_diagnostic.send(WorkerMessage::Kill).unwrap();
+        self.workspace_diagnostic.send(WorkerMessage::Kill).unwrap();
+        self.type_hierarchy_prepare
+            .send(WorkerMessage::Kill)
+            .unwrap();
+        self.type_hierarchy_supertypes
+            .send(WorkerMessage::Kill)
+            .unwrap();
+        self.type_hierarchy_subtypes
+            .send(WorkerMessage::Kill)
+            .unwrap();
+        self.call_hierarchy_prepare
+            .send(WorkerMessage::Kill)
+            .unwrap();
+        self.call_hierarchy_incoming
+            .send(WorkerMessage::Kill)
+            .unwrap();
+        self.call_hierarchy_outgoing
+            .send(WorkerMessage::Kill)
+            .unwrap();
         self.health_check.send(WorkerMessage::Kill).unwrap();
     }
 }
@@ -137,6 +255,27 @@ pub struct ReceiveChannels {
     pub(crate) signature_help: mpsc::Receiver<WorkerMessage<SignatureHelpParams>>,
     pub(crate) will_rename_files: mpsc::Receiver<WorkerMessage<RenameFilesParams>>,
     pub(crate) execute_command: mpsc::Receiver<WorkerMessage<ExecuteCommandParams>>,
+    pub(crate) document_symbol: mpsc::Receiver<WorkerMessage<DocumentSymbolParams>>,
+    pub(crate) workspace_symbol: mpsc::Receiver<WorkerMessage<WorkspaceSymbolParams>>,
+    pub(crate) formatting: mpsc::Receiver<WorkerMessage<DocumentFormattingParams>>,
+    pub(crate) range_formatting: mpsc::Receiver<WorkerMessage<DocumentRangeFormattingParams>>,
+    pub(crate) on_type_formatting: mpsc::Receiver<WorkerMessage<DocumentOnTypeFormattingParams>>,
+    pub(crate) call_hierarchy_prepare: mpsc::Receiver<WorkerMessage<CallHierarchyPrepareParams>>,
+    pub(crate) type_hierarchy_prepare: mpsc::Receiver<WorkerMessage<TypeHierarchyPrepareParams>>,
+    pub(crate) type_hierarchy_supertypes:
+        mpsc::Receiver<WorkerMessage<TypeHierarchySupertypesParams>>,
+    pub(crate) type_hierarchy_subtypes: mpsc::Receiver<WorkerMessage<TypeHierarchySubtypesParams>>,
+    pub(crate) goto_type_definition: mpsc::Receiver<WorkerMessage<GotoDefinitionParams>>,
+    pub(crate) goto_implementation: mpsc::Receiver<WorkerMessage<GotoDefinitionParams>>,
+    pub(crate) document_highlight: mpsc::Receiver<WorkerMessage<DocumentHighlightParams>>,
+    pub(crate) folding_range: mpsc::Receiver<WorkerMessage<FoldingRangeParams>>,
+    pub(crate) selection_range: mpsc::Receiver<WorkerMessage<SelectionRangeParams>>,
+    pub(crate) document_diagnostic: mpsc::Receiver<WorkerMessage<DocumentDiagnosticParams>>,
+    pub(crate) workspace_diagnostic: mpsc::Receiver<WorkerMessage<WorkspaceDiagnosticParams>>,
+    pub(crate) call_hierarchy_incoming:
+        mpsc::Receiver<WorkerMessage<CallHierarchyIncomingCallsParams>>,
+    pub(crate) call_hierarchy_outgoing:
+        mpsc::Receiver<WorkerMessage<CallHierarchyOutgoingCallsParams>>,
     pub(crate) health_check: mpsc::Receiver<WorkerMessage<()>>,
 }
 
@@ -179,3 +318,73 @@ impl_sendable!(CodeActionResolveRequest, CodeAction, code_action_resolve);
 impl_sendable!(SignatureHelpRequest, SignatureHelpParams, signature_help);
 impl_sendable!(WillRenameFiles, RenameFilesParams, will_rename_files);
 impl_sendable!(ExecuteCommand, ExecuteCommandParams, execute_command);
+impl_sendable!(DocumentSymbolRequest, DocumentSymbolParams, document_symbol);
+impl_sendable!(WorkspaceSymbol, WorkspaceSymbolParams, workspace_symbol);
+impl_sendable!(Formatting, DocumentFormattingParams, formatting);
+impl_sendable!(
+    RangeFormatting,
+    DocumentRangeFormattingParams,
+    range_formatting
+);
+impl_sendable!(
+    OnTypeFormatting,
+    DocumentOnTypeFormattingParams,
+    on_type_formatting
+);
+impl_sendable!(
+    CallHierarchyPrepare,
+    CallHierarchyPrepareParams,
+    call_hierarchy_prepare
+);
+impl_sendable!(
+    CallHierarchyIncomingCalls,
+    CallHierarchyIncomingCallsParams,
+    call_hierarchy_incoming
+);
+impl_sendable!(
+    CallHierarchyOutgoingCalls,
+    CallHierarchyOutgoingCallsParams,
+    call_hierarchy_outgoing
+);
+impl_sendable!(
+    TypeHierarchyPrepare,
+    TypeHierarchyPrepareParams,
+    type_hierarchy_prepare
+);
+impl_sendable!(
+    TypeHierarchySupertypes,
+    TypeHierarchySupertypesParams,
+    type_hierarchy_supertypes
+);
+impl_sendable!(
+    TypeHierarchySubtypes,
+    TypeHierarchySubtypesParams,
+    type_hierarchy_subtypes
+);
+impl_sendable!(
+    GotoTypeDefinition,
+    GotoDefinitionParams,
+    goto_type_definition
+);
+impl_sendable!(
+    GotoImplementation,
+    GotoDefinitionParams,
+    goto_implementation
+);
+impl_sendable!(
+    DocumentHighlightRequest,
+    DocumentHighlightParams,
+    document_highlight
+);
+impl_sendable!(FoldingRangeRequest, FoldingRangeParams, folding_range);
+impl_sendable!(SelectionRangeRequest, SelectionRangeParams, selection_range);
+impl_sendable!(
+    DocumentDiagnosticRequest,
+    DocumentDiagnosticParams,
+    document_diagnostic
+);
+impl_sendable!(
+    WorkspaceDiagnosticRequest,
+    WorkspaceDiagnosticParams,
+    workspace_diagnostic
+);
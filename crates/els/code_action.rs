@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use erg_common::consts::{ERG_MODE, PYTHON_MODE};
 use erg_common::deepen_indent;
+use erg_common::error::ErrorKind;
+use erg_common::set::Set;
 use erg_common::traits::{Locational, Stream};
 use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::erg_parser::parse::Parsable;
@@ -56,27 +58,8 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                         send_log("range not found")?;
                         continue;
                     };
-                    let next = lsp_types::Range {
-                        start: lsp_types::Position {
-                            line: range.end.line,
-                            character: range.end.character,
-                        },
-                        end: lsp_types::Position {
-                            line: range.end.line,
-                            character: range.end.character + 1,
-                        },
-                    };
-                    let code = self.file_cache.get_ranged(&uri, next)?;
-                    match code.as_ref().map(|s| &s[..]) {
-                        None => {
-                            // \n
-                            range.end.line += 1;
-                            range.end.character = 0;
-                        }
-                        Some(";") => range.end.character += 1,
-                        Some(other) => {
-                            send_log(format!("? {other}"))?;
-                        }
+                    if let Some(end) = self.removal_end(&uri, range.end) {
+                        range.end = end;
                     }
                     let edit = TextEdit::new(range, "".to_string());
                     map.entry(uri.clone().raw()).or_insert(vec![]).push(edit);
@@ -107,6 +90,71 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         Ok(Some(action))
     }
 
+    /// Returns the end of the range to be removed along with the definition ending at `def_end`:
+    /// the trailing whitespaces and comment, and the following `;` or line break.
+    fn removal_end(&self, uri: &NormalizedUrl, def_end: Position) -> Option<Position> {
+        let cst = self.file_cache.get_cst(uri)?;
+        let tokens = cst.tokens().collect::<Vec<_>>();
+        // the last token of the definition
+        let idx = tokens.iter().rposition(|token| {
+            util::loc_to_range(token.loc())
+                .is_some_and(|range| !token.is_layout() && range.start < def_end)
+        })?;
+        let end = match tokens.get(idx + 1) {
+            Some(next) if next.is(TokenKind::Semi) || next.is(TokenKind::Newline) => next,
+            _ => tokens[idx],
+        };
+        let last = end
+            .trailing_trivia
+            .last()
+            .map_or(end.loc(), |trivia| trivia.loc());
+        util::loc_to_range(last).map(|range| range.end)
+    }
+
+    /// Generates the actions which import the undefined names (`NameError`s) from the modules defining them.
+    fn gen_add_import_actions(&self, params: &CodeActionParams) -> Vec<CodeAction> {
+        let mut actions = vec![];
+        let uri = NormalizedUrl::new(params.text_document.uri.clone());
+        let Some(result) = self.analysis_result.get(&uri) else {
+            return actions;
+        };
+        for diag in params.context.diagnostics.iter() {
+            let is_name_error = result.artifact.errors.iter().any(|err| {
+                err.core.kind == ErrorKind::NameError
+                    && util::loc_to_range(err.core.loc).map(|range| range.start)
+                        == Some(diag.range.start)
+            });
+            if !is_name_error {
+                continue;
+            }
+            let Some(token) = self.file_cache.get_token(&uri, diag.range.start) else {
+                continue;
+            };
+            for item in self.import_completion(&uri, None, &Set::new()) {
+                if item.insert_text.as_deref() != Some(&token.content[..]) {
+                    continue;
+                }
+                let Some(edits) = item.additional_text_edits else {
+                    continue;
+                };
+                let Some(import) = edits.first() else {
+                    continue;
+                };
+                let title = format!("Add import: {}", import.new_text.trim_end());
+                let mut map = HashMap::new();
+                map.insert(uri.clone().raw(), edits);
+                actions.push(CodeAction {
+                    title,
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diag.clone()]),
+                    edit: Some(WorkspaceEdit::new(map)),
+                    ..Default::default()
+                });
+            }
+        }
+        actions
+    }
+
     fn gen_change_case_action(
         &self,
         token: Token,
@@ -225,6 +273,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             let actions = self.gen_eliminate_unused_vars_action(params)?;
             result.extend(actions);
         }
+        result.extend(self.gen_add_import_actions(params));
         Ok(result)
     }
 
@@ -48,7 +48,34 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         Ok(result)
     }
 
-    fn send_class_inherits_lens(&mut self, _uri: &NormalizedUrl) -> ELSResult<Vec<CodeLens>> {
-        Ok(vec![])
+    fn send_class_inherits_lens(&mut self, uri: &NormalizedUrl) -> ELSResult<Vec<CodeLens>> {
+        let class_locs = if let Some(hir) = self.analysis_result.get_hir(uri) {
+            hir.module
+                .iter()
+                .filter_map(|chunk| match chunk {
+                    Expr::ClassDef(class_def) => Some(class_def.sig.ident().vi.def_loc.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+        let mut result = vec![];
+        for class_loc in class_locs {
+            let Some(range) = util::loc_to_range(class_loc.loc) else {
+                continue;
+            };
+            // only the classes that have subclasses are shown
+            let Some(command) = self.gen_show_subclasses_command(class_loc)? else {
+                continue;
+            };
+            let lens = CodeLens {
+                range,
+                command: Some(command),
+                data: None,
+            };
+            result.push(lens);
+        }
+        Ok(result)
     }
 }
@@ -3,13 +3,12 @@ use erg_compiler::varinfo::AbsLocation;
 use serde_json::Value;
 
 use erg_compiler::artifact::BuildRunnable;
-use erg_compiler::hir::Expr;
 
 use lsp_types::{Command, ExecuteCommandParams, Location, Url};
 
 use crate::_log;
 use crate::server::{ELSResult, Server};
-use crate::util::{self, NormalizedUrl};
+use crate::util;
 
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
     pub(crate) fn handle_execute_command(
@@ -30,29 +29,43 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         &self,
         trait_loc: AbsLocation,
     ) -> ELSResult<Option<Command>> {
-        let refs = self.get_refs_from_abs_loc(&trait_loc);
-        let filter = |loc: Location| {
-            let uri = NormalizedUrl::new(loc.uri.clone());
-            let token = self.file_cache.get_token(&uri, loc.range.start)?;
-            let opt_visitor = self.get_visitor(&uri);
-            let min_expr = opt_visitor
-                .as_ref()
-                .and_then(|visitor| visitor.get_min_expr(&token))?;
-            matches!(min_expr, Expr::ClassDef(_)).then_some(loc)
-        };
-        let impls = refs.into_iter().filter_map(filter).collect::<Vec<_>>();
-        let impl_len = impls.len();
-        let locations = serde_json::to_value(impls)?;
-        let Ok(uri) = trait_loc.module.ok_or(()).and_then(Url::from_file_path) else {
+        let impls = self.get_trait_impls(&trait_loc);
+        let title = format!("{} implementations", impls.len());
+        Self::gen_show_references_command(title, trait_loc, impls)
+    }
+
+    pub(crate) fn gen_show_subclasses_command(
+        &self,
+        class_loc: AbsLocation,
+    ) -> ELSResult<Option<Command>> {
+        let subclasses = self
+            .get_subtype_items(&class_loc)
+            .into_iter()
+            .map(|item| Location::new(item.uri, item.selection_range))
+            .collect::<Vec<_>>();
+        if subclasses.is_empty() {
+            return Ok(None);
+        }
+        let title = format!("{} subclasses", subclasses.len());
+        Self::gen_show_references_command(title, class_loc, subclasses)
+    }
+
+    fn gen_show_references_command(
+        title: String,
+        loc: AbsLocation,
+        locations: Vec<Location>,
+    ) -> ELSResult<Option<Command>> {
+        let locations = serde_json::to_value(locations)?;
+        let Ok(uri) = loc.module.ok_or(()).and_then(Url::from_file_path) else {
             return Ok(None);
         };
         let uri = serde_json::to_value(uri)?;
-        let Some(position) = util::loc_to_pos(trait_loc.loc) else {
+        let Some(position) = util::loc_to_pos(loc.loc) else {
             return Ok(None);
         };
         let position = serde_json::to_value(position)?;
         Ok(Some(Command {
-            title: format!("{impl_len} implementations"),
+            title,
             // the command is defined in: https://github.com/erg-lang/vscode-erg/blob/20e6e2154b045ab56fedbc8769d03633acfd12e0/src/extension.ts#L92-L94
             command: "erg.showReferences".to_string(),
             arguments: Some(vec![uri, position, locations]),
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 use erg_common::consts::PYTHON_MODE;
 use erg_common::spawn::spawn_new_thread;
@@ -8,7 +8,7 @@ use serde_json::Value;
 
 use erg_common::config::ErgConfig;
 use erg_common::dict::Dict;
-use erg_common::env::{erg_py_external_lib_path, erg_pystd_path};
+use erg_common::env::{erg_py_external_lib_path, erg_pystd_path, python_site_packages};
 use erg_common::impl_u8_enum;
 use erg_common::io::Input;
 use erg_common::python_util::{BUILTIN_PYTHON_MODS, EXT_COMMON_ALIAS, EXT_PYTHON_MODS};
@@ -186,6 +186,9 @@ type Cache = Shared<Dict<String, Vec<CompletionItem>>>;
 #[derive(Debug, Clone)]
 pub struct CompletionCache {
     cache: Cache,
+    cfg: ErgConfig,
+    /// Has loading the declarations of the Python modules been started?
+    decls_loading: Shared<bool>,
 }
 
 fn external_item(name: &str, vi: &VarInfo, mod_name: &str) -> CompletionItem {
@@ -311,6 +314,51 @@ fn module_completions() -> Vec<CompletionItem> {
     comps
 }
 
+/// The top-level modules declared in `root` (e.g. `math.d.er`, `os.d/__init__.d.er`)
+fn decl_modules(root: &Path) -> Vec<String> {
+    let Ok(dir) = root.read_dir() else {
+        return vec![];
+    };
+    let mut mods = dir
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let name = name
+                .strip_suffix(".d.er")
+                .or_else(|| name.strip_suffix(".d"))?;
+            (!name.starts_with('_') && name != "builtins").then(|| name.to_string())
+        })
+        .collect::<Vec<_>>();
+    mods.sort();
+    mods
+}
+
+/// The installed packages whose declaration files have been generated (e.g. by pylyzer)
+///
+/// 1. `site-packages/{pkg}/__pycache__/__init__.d.er`
+/// 2. `site-packages/__pycache__/{mod}.d.er`
+fn site_packages_decl_modules(site_packages: &Path) -> Vec<String> {
+    let mut mods = vec![];
+    if let Ok(dir) = site_packages.read_dir() {
+        for entry in dir.flatten() {
+            if entry.path().join("__pycache__/__init__.d.er").exists() {
+                mods.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+    if let Ok(dir) = site_packages.join("__pycache__").read_dir() {
+        for entry in dir.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(name) = name.strip_suffix(".d.er") {
+                mods.push(name.to_string());
+            }
+        }
+    }
+    mods.retain(|name| !name.starts_with('_'));
+    mods.sort();
+    mods
+}
+
 fn load_modules<'a>(
     cfg: ErgConfig,
     cache: Cache,
@@ -331,84 +379,133 @@ fn load_modules<'a>(
     if cache.get("<module>").is_none() {
         cache.insert("<module>".into(), module_completions());
     }
-    let std_path = root.display().to_string().replace('\\', "/");
     for (path, entry) in shared.py_mod_cache.ref_inner().iter() {
+        // the modules loaded as dependencies (e.g. `builtins`) are not under `root`
+        let Ok(rel_path) = path.strip_prefix(root) else {
+            continue;
+        };
         let dir = entry.module.context.local_dir();
-        let mod_name = path.display().to_string().replace('\\', "/");
+        let mod_name = rel_path.display().to_string().replace('\\', "/");
         let mod_name = mod_name
-            .trim_start_matches(&std_path)
-            .trim_start_matches('/')
             .trim_end_matches("/__init__.d.er")
             .trim_end_matches(".d.er")
             .replace(".d", "");
+        // `{pkg}/__pycache__`, `__pycache__/{mod}` (site-packages)
+        let mod_name = mod_name
+            .trim_end_matches("/__pycache__")
+            .trim_start_matches("__pycache__/");
         let items = dir
             .into_iter()
-            .filter(|(name, _)| !name.inspect().starts_with('%'))
-            .map(|(name, vi)| external_item(name.inspect(), vi, &mod_name));
+            .filter(|(name, vi)| !name.inspect().starts_with('%') && !vi.vis.is_private())
+            .map(|(name, vi)| external_item(name.inspect(), vi, mod_name));
         cache.get_mut("<module>").unwrap().extend(items)
     }
 }
 
+/// The path to import `module` from a file in `dir` (e.g. `foo/bar`, `../baz`).
+/// Returns `None` if `module` is not a user script (`.er`, or `.py` in Python mode).
+fn import_path(dir: &Path, module: &Path) -> Option<String> {
+    let file_name = module.file_name()?.to_str()?;
+    let (ext, init) = if PYTHON_MODE {
+        (".py", "__init__.py")
+    } else {
+        (".er", "__init__.er")
+    };
+    if !file_name.ends_with(ext) || file_name.ends_with(".d.er") {
+        return None;
+    }
+    let module = if file_name == init {
+        module.parent()?.to_path_buf()
+    } else {
+        module.with_extension("")
+    };
+    let dir = dir.components().collect::<Vec<_>>();
+    let module = module.components().collect::<Vec<_>>();
+    let common = dir
+        .iter()
+        .zip(module.iter())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let mut path = PathBuf::new();
+    for _ in common..dir.len() {
+        path.push(Component::ParentDir);
+    }
+    path.extend(&module[common..]);
+    Some(path.display().to_string().replace('\\', "/"))
+}
+
+/// Converts an `import_path` into a relative module path of Python
+/// (one leading dot plus one per `..`, e.g. `foo/bar` -> `.foo.bar`, `../baz` -> `..baz`).
+fn py_relative_module(import_path: &str) -> String {
+    let mut dots = String::from(".");
+    let mut names = vec![];
+    for comp in import_path.split('/') {
+        if comp == ".." {
+            dots.push('.');
+        } else {
+            names.push(comp);
+        }
+    }
+    dots + &names.join(".")
+}
+
 impl CompletionCache {
     pub fn new(cfg: ErgConfig) -> Self {
-        let cache = Shared::new(Dict::default());
-        let clone = cache.clone();
+        Self {
+            cache: Shared::new(Dict::default()),
+            cfg,
+            decls_loading: Shared::new(false),
+        }
+    }
+
+    /// Loads the declarations of the Python standard library and the installed packages in another thread.
+    /// This is deferred until the first import completion, since there are many of them.
+    pub fn load_decls(&self) {
+        {
+            let mut loading = self.decls_loading.borrow_mut();
+            if *loading {
+                return;
+            }
+            *loading = true;
+        }
+        let cfg = self.cfg.clone();
+        let clone = self.cache.clone();
         spawn_new_thread(
             move || {
                 crate::_log!("load_modules");
-                let major_mods = [
-                    "argparse",
-                    "array",
-                    "asyncio",
-                    "base64",
-                    "datetime",
-                    "decimal",
-                    "fraction",
-                    "glob",
-                    "html",
-                    "http",
-                    "http/client",
-                    "http/server",
-                    "io",
-                    "json",
-                    "logging",
-                    "math",
-                    "os",
-                    "os/path",
-                    "pathlib",
-                    "platform",
-                    "random",
-                    "re",
-                    "shutil",
-                    "socket",
-                    "sqlite3",
-                    "ssl",
-                    "string",
-                    "subprocess",
-                    "sys",
-                    "tempfile",
-                    "time",
-                    "timeit",
-                    "unittest",
-                    "urllib",
-                    "zipfile",
-                ];
-                #[cfg(feature = "py_compat")]
-                let py_specific_mods = ["dataclasses", "typing", "collections/abc"];
-                #[cfg(not(feature = "py_compat"))]
-                let py_specific_mods = [];
-                let ext_mods = ["numpy", "pandas", "matplotlib", "matplotlib/pyplot"];
+                // the submodules are not found by `decl_modules`
+                let pystd_submods = ["collections/abc", "http/client", "http/server", "os/path"];
+                // modules that are only useful in Python
+                let py_specific_mods = ["collections/abc", "dataclasses", "typing"];
+                let pystd_mods = decl_modules(erg_pystd_path());
+                let pystd_mods = pystd_mods
+                    .iter()
+                    .map(|name| &name[..])
+                    .chain(pystd_submods)
+                    .filter(|name| PYTHON_MODE || !py_specific_mods.contains(name));
+                load_modules(cfg.clone(), clone.clone(), erg_pystd_path(), pystd_mods);
+                let ext_mods = decl_modules(erg_py_external_lib_path());
+                let ext_mods = ext_mods
+                    .iter()
+                    .map(|name| &name[..])
+                    .chain(["matplotlib/pyplot"]);
                 load_modules(
                     cfg.clone(),
                     clone.clone(),
-                    erg_pystd_path(),
-                    major_mods.into_iter().chain(py_specific_mods),
+                    erg_py_external_lib_path(),
+                    ext_mods,
                 );
-                load_modules(cfg, clone, erg_py_external_lib_path(), ext_mods.into_iter());
+                for site_packages in python_site_packages() {
+                    let mods = site_packages_decl_modules(site_packages);
+                    if mods.is_empty() {
+                        continue;
+                    }
+                    let mods = mods.iter().map(|name| &name[..]);
+                    load_modules(cfg.clone(), clone.clone(), site_packages, mods);
+                }
             },
             "load_modules",
         );
-        Self { cache }
     }
 
     pub fn get(&self, namespace: &str) -> Option<MappedRwLockReadGuard<Vec<CompletionItem>>> {
@@ -427,6 +524,7 @@ impl CompletionCache {
 
     pub fn clear(&self) {
         self.cache.borrow_mut().clear();
+        *self.decls_loading.borrow_mut() = false;
     }
 
     pub fn _append(&self, cache: Dict<String, Vec<CompletionItem>>) {
@@ -441,38 +539,51 @@ impl CompletionCache {
 }
 
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
-    /// Returns completion candidates from modules in the same directory
-    fn neighbor_completion(
+    /// Returns completion candidates of the public names in the user modules (not yet imported in `uri`)
+    fn user_module_completion(
         &self,
         uri: &NormalizedUrl,
-        arg_pt: Option<ParamTy>,
-        already_appeared: &mut Set<String>,
+        arg_pt: Option<&ParamTy>,
+        already_appeared: &Set<String>,
     ) -> Vec<CompletionItem> {
         let mut comps = vec![];
-        for mod_ctx in self.get_neighbor_ctxs(uri) {
+        let Some(shared) = self.get_shared() else {
+            return comps;
+        };
+        let path = util::uri_to_path(uri);
+        let Some(dir) = path.parent() else {
+            return comps;
+        };
+        for (mod_path, entry) in shared.mod_cache.ref_inner().iter() {
+            let mod_path: &Path = mod_path;
+            if mod_path == path.as_path() || mod_path.starts_with(&self.erg_path) {
+                continue;
+            }
+            let Some(import_path) = import_path(dir, mod_path) else {
+                continue;
+            };
+            let mod_ctx = &entry.module.context;
             for (name, vi) in mod_ctx.local_dir() {
-                if vi.vis.is_private() {
+                if vi.vis.is_private() || name.inspect().starts_with('%') {
                     continue;
                 }
                 if already_appeared.contains(&name.inspect()[..]) {
                     continue;
                 }
-                let Some(path) = vi.def_loc.module.as_ref() else {
+                // re-exported names
+                if vi.def_loc.module.as_deref() != Some(mod_path) {
                     continue;
-                };
-                let path = path.file_stem().unwrap().to_string_lossy();
+                }
                 let mut item = CompletionItem::new_simple(
-                    format!("{name} (import from {path})"),
+                    format!("{name} (import from {import_path})"),
                     vi.t.to_string(),
                 );
-                CompletionOrderSetter::new(vi, arg_pt.as_ref(), mod_ctx, item.label.clone())
-                    .set(&mut item);
-                // item.sort_text = Some(format!("{}_{}", CompletionOrder::OtherNamespace, item.label));
+                CompletionOrderSetter::new(vi, arg_pt, mod_ctx, item.label.clone()).set(&mut item);
                 item.kind = Some(comp_item_kind(vi));
                 let import = if PYTHON_MODE {
-                    format!("from {path} import {name}\n")
+                    format!("from {} import {name}\n", py_relative_module(&import_path))
                 } else {
-                    format!("{{{name};}} = import \"{path}\"\n")
+                    format!("{{{name};}} = import \"{import_path}\"\n")
                 };
                 item.additional_text_edits = Some(vec![TextEdit {
                     range: Range::new(Position::new(0, 0), Position::new(0, 0)),
@@ -480,13 +591,36 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 }]);
                 item.insert_text = Some(name.inspect().trim_end_matches('\0').to_string());
                 item.filter_text = Some(name.inspect().to_string());
-                already_appeared.insert(name.inspect().to_string());
                 comps.push(item);
             }
         }
         comps
     }
 
+    /// Returns the items which import the names (and modules) not yet imported in `uri`.
+    /// Used by the auto-import completion and the "add import" quick fix.
+    pub(crate) fn import_completion(
+        &self,
+        uri: &NormalizedUrl,
+        arg_pt: Option<&ParamTy>,
+        already_appeared: &Set<String>,
+    ) -> Vec<CompletionItem> {
+        self.comp_cache.load_decls();
+        let mut comps = self.user_module_completion(uri, arg_pt, already_appeared);
+        let not_imported = |item: &&CompletionItem| match &item.filter_text {
+            Some(name) => !already_appeared.contains(name),
+            None => true,
+        };
+        if let Some(std_comps) = self.comp_cache.get("<module>") {
+            comps.extend(std_comps.iter().filter(not_imported).cloned());
+        } else {
+            let std_comps = module_completions();
+            self.comp_cache.insert("<module>".into(), std_comps.clone());
+            comps.extend(std_comps.iter().filter(not_imported).cloned());
+        }
+        comps
+    }
+
     pub(crate) fn handle_completion(
         &mut self,
         params: CompletionParams,
@@ -621,14 +755,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             result.push(item);
         }
         if comp_kind.should_be_local() {
-            if let Some(comps) = self.comp_cache.get("<module>") {
-                result.extend(comps.clone());
-            } else {
-                let comps = module_completions();
-                self.comp_cache.insert("<module>".into(), comps.clone());
-                result.extend(comps);
-            }
-            self.neighbor_completion(&uri, arg_pt, &mut already_appeared);
+            result.extend(self.import_completion(&uri, arg_pt.as_ref(), &already_appeared));
         }
         send_log(format!("completion items: {}", result.len()))?;
         Ok(Some(CompletionResponse::Array(result)))
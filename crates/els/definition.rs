@@ -2,13 +2,14 @@ use erg_common::consts::PYTHON_MODE;
 use erg_common::traits::{Locational, Stream};
 use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::context::register::PylyzerStatus;
+use erg_compiler::context::Context;
 use erg_compiler::erg_parser::parse::Parsable;
 use erg_compiler::erg_parser::token::{Token, TokenCategory};
-use erg_compiler::hir::Expr;
-use erg_compiler::ty::HasType;
-use erg_compiler::varinfo::VarInfo;
+use erg_compiler::hir::{ClassDef, Def, Expr};
+use erg_compiler::ty::{HasType, Type};
+use erg_compiler::varinfo::{AbsLocation, VarInfo};
 
-use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Url};
+use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Location, Url};
 
 use crate::server::{send_log, ELSResult, Server};
 use crate::util::{self, NormalizedUrl};
@@ -145,4 +146,179 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         let result = self.get_definition_response(params)?;
         Ok(Some(result))
     }
+
+    /// Returns the definition locations of the classes/traits of `t`.
+    /// `Ref`/`RefMut`/refinement types are unwrapped, and the type arguments are followed if the definition of a polymorphic type is not found (e.g. `Array(C, 2)` => `C`).
+    fn get_type_def_locs(ctx: &Context, t: &Type, locs: &mut Vec<AbsLocation>) {
+        match t {
+            Type::FreeVar(fv) if fv.is_linked() => Self::get_type_def_locs(ctx, &fv.crack(), locs),
+            Type::Ref(t) | Type::RefMut { before: t, .. } => Self::get_type_def_locs(ctx, t, locs),
+            Type::Refinement(refine) => Self::get_type_def_locs(ctx, &refine.t, locs),
+            Type::And(lhs, rhs) | Type::Or(lhs, rhs) => {
+                Self::get_type_def_locs(ctx, lhs, locs);
+                Self::get_type_def_locs(ctx, rhs, locs);
+            }
+            Type::Mono(_) | Type::Poly { .. } => {
+                // the types defined in the module (`<module>::C`) are not found by `get_type_info`
+                let def_loc = ctx
+                    .get_type_info(t)
+                    .or_else(|| ctx.get_var_info(&t.local_name()))
+                    .map(|(_, vi)| &vi.def_loc)
+                    .filter(|def_loc| def_loc.module.is_some());
+                match def_loc {
+                    Some(def_loc) if !locs.contains(def_loc) => locs.push(def_loc.clone()),
+                    Some(_) => {}
+                    None => {
+                        for inner in t.inner_ts() {
+                            Self::get_type_def_locs(ctx, &inner, locs);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn handle_goto_type_definition(
+        &mut self,
+        params: GotoDefinitionParams,
+    ) -> ELSResult<Option<GotoDefinitionResponse>> {
+        send_log(format!("type definition requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let Some(token) = self.file_cache.get_token(&uri, pos) else {
+            return Ok(None);
+        };
+        let Some(vi) = self.get_definition(&uri, &token)? else {
+            return Ok(None);
+        };
+        let Some(ctx) = self.get_local_ctx(&uri, pos).first().copied() else {
+            return Ok(None);
+        };
+        let mut def_locs = vec![];
+        Self::get_type_def_locs(ctx, &vi.t, &mut def_locs);
+        let locations = def_locs
+            .iter()
+            .filter_map(util::abs_loc_to_lsp_loc)
+            .collect();
+        Ok(Some(GotoDefinitionResponse::Array(locations)))
+    }
+
+    /// Returns the locations of the class definitions implementing the trait defined at `trait_loc`.
+    pub(crate) fn get_trait_impls(&self, trait_loc: &AbsLocation) -> Vec<Location> {
+        let refs = self.get_refs_from_abs_loc(trait_loc);
+        let filter = |loc: Location| {
+            let uri = NormalizedUrl::new(loc.uri.clone());
+            let token = self.file_cache.get_token(&uri, loc.range.start)?;
+            let opt_visitor = self.get_visitor(&uri);
+            let min_expr = opt_visitor
+                .as_ref()
+                .and_then(|visitor| visitor.get_min_expr(&token))?;
+            matches!(min_expr, Expr::ClassDef(_)).then_some(loc)
+        };
+        refs.into_iter().filter_map(filter).collect()
+    }
+
+    fn get_method_def<'e>(exprs: impl Iterator<Item = &'e Expr>, name: &str) -> Option<&'e Def> {
+        for expr in exprs {
+            match expr {
+                Expr::Def(def) if def.sig.ident().inspect() == name => return Some(def),
+                Expr::Dummy(dummy) => {
+                    if let Some(def) = Self::get_method_def(dummy.iter(), name) {
+                        return Some(def);
+                    }
+                }
+                Expr::Compound(block) => {
+                    if let Some(def) = Self::get_method_def(block.iter(), name) {
+                        return Some(def);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Returns the locations of the methods `name` of the classes implementing the trait defined at `trait_loc`.
+    fn get_trait_method_impls(&self, trait_loc: &AbsLocation, name: &str) -> Vec<Location> {
+        let mut locations = vec![];
+        for class_loc in self.get_trait_impls(trait_loc) {
+            if self.is_cancelled() {
+                break;
+            }
+            let uri = NormalizedUrl::new(class_loc.uri.clone());
+            let Some(visitor) = self.get_cached_visitor(&uri) else {
+                continue;
+            };
+            let Some(Expr::ClassDef(ClassDef { methods, .. })) =
+                visitor.get_type_def(class_loc.range.start)
+            else {
+                continue;
+            };
+            let Some(def) = Self::get_method_def(methods.iter(), name) else {
+                continue;
+            };
+            let Some(range) = util::loc_to_range(def.sig.ident().loc()) else {
+                continue;
+            };
+            let location = Location::new(class_loc.uri, range);
+            if !locations.contains(&location) {
+                locations.push(location);
+            }
+        }
+        locations
+    }
+
+    /// Returns the definition location of the trait whose definition contains `def_loc`.
+    fn get_enclosing_trait_loc(&self, def_loc: &AbsLocation) -> Option<AbsLocation> {
+        let uri = NormalizedUrl::try_from(def_loc.module.as_ref()?.as_path()).ok()?;
+        let pos = util::loc_to_range(def_loc.loc)?.start;
+        let visitor = self.get_cached_visitor(&uri)?;
+        match visitor.get_type_def(pos)? {
+            Expr::Def(def) => Some(def.sig.ident().vi.def_loc.clone()),
+            _ => None,
+        }
+    }
+
+    /// Trait => the classes implementing it, trait method => the methods implementing it
+    pub(crate) fn handle_goto_implementation(
+        &mut self,
+        params: GotoDefinitionParams,
+    ) -> ELSResult<Option<GotoDefinitionResponse>> {
+        send_log(format!("implementation requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let Some(token) = self.file_cache.get_token(&uri, pos) else {
+            return Ok(None);
+        };
+        let Some(vi) = self.get_definition(&uri, &token)? else {
+            return Ok(None);
+        };
+        let locations = if vi.def_loc.module.is_some() {
+            match self.get_enclosing_trait_loc(&vi.def_loc) {
+                Some(trait_loc) if trait_loc == vi.def_loc => self.get_trait_impls(&trait_loc),
+                Some(trait_loc) if vi.t.is_subr() => {
+                    self.get_trait_method_impls(&trait_loc, token.inspect())
+                }
+                _ => vec![],
+            }
+        } else if let Some(self_t) = vi.t.self_t() {
+            // the methods called via a trait type (`x: T; x.f()`) have no definition location
+            let mut def_locs = vec![];
+            if let Some(ctx) = self.get_local_ctx(&uri, pos).first() {
+                Self::get_type_def_locs(ctx, self_t, &mut def_locs);
+            }
+            match def_locs.first() {
+                Some(def_loc)
+                    if self.get_enclosing_trait_loc(def_loc).as_ref() == Some(def_loc) =>
+                {
+                    self.get_trait_method_impls(def_loc, token.inspect())
+                }
+                _ => vec![],
+            }
+        } else {
+            vec![]
+        };
+        Ok(Some(GotoDefinitionResponse::Array(locations)))
+    }
 }
@@ -4,6 +4,7 @@ use std::time::Duration;
 
 use erg_common::consts::PYTHON_MODE;
 use erg_common::dict::Dict;
+use erg_common::shared::Shared;
 use erg_common::spawn::spawn_new_thread;
 use erg_common::style::*;
 use erg_common::traits::Stream;
@@ -13,10 +14,14 @@ use erg_compiler::erg_parser::ast::Module;
 use erg_compiler::erg_parser::parse::Parsable;
 use erg_compiler::error::CompileErrors;
 
+use lsp_types::request::Request;
 use lsp_types::{
-    ConfigurationParams, Diagnostic, DiagnosticSeverity, NumberOrString, Position,
-    PublishDiagnosticsParams, Range, Url,
+    ConfigurationParams, Diagnostic, DiagnosticSeverity, DidChangeWatchedFilesParams,
+    DidChangeWatchedFilesRegistrationOptions, FileChangeType, FileSystemWatcher, NumberOrString,
+    PartialResultParams, Position, PublishDiagnosticsParams, Range, Registration,
+    RegistrationParams, TextDocumentIdentifier, Url, WorkDoneProgressParams,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use crate::_log;
@@ -24,10 +29,117 @@ use crate::channels::WorkerMessage;
 use crate::diff::{ASTDiff, HIRDiff};
 use crate::server::{
     send, send_log, AnalysisResult, DefaultFeatures, ELSResult, Server, ASK_AUTO_SAVE_ID,
-    HEALTH_CHECKER_ID,
+    HEALTH_CHECKER_ID, REFRESH_DIAGNOSTICS_ID, REGISTER_FILE_WATCHER_ID,
 };
 use crate::util::{self, NormalizedUrl};
 
+// lsp-types 0.93 does not have the pull diagnostics API (LSP 3.17), so it is defined here.
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiagnosticParams {
+    pub text_document: TextDocumentIdentifier,
+    pub identifier: Option<String>,
+    pub previous_result_id: Option<String>,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousResultId {
+    pub uri: Url,
+    pub value: String,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDiagnosticParams {
+    pub identifier: Option<String>,
+    pub previous_result_ids: Vec<PreviousResultId>,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+/// ELS always returns full reports (`unchanged` reports are not used)
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DocumentDiagnosticReport {
+    Full { items: Vec<Diagnostic> },
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDocumentDiagnosticReport {
+    pub uri: Url,
+    pub version: Option<i32>,
+    #[serde(flatten)]
+    pub report: DocumentDiagnosticReport,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDiagnosticReport {
+    pub items: Vec<WorkspaceDocumentDiagnosticReport>,
+}
+
+#[derive(Debug)]
+pub enum DocumentDiagnosticRequest {}
+
+impl Request for DocumentDiagnosticRequest {
+    type Params = DocumentDiagnosticParams;
+    type Result = DocumentDiagnosticReport;
+    const METHOD: &'static str = "textDocument/diagnostic";
+}
+
+#[derive(Debug)]
+pub enum WorkspaceDiagnosticRequest {}
+
+impl Request for WorkspaceDiagnosticRequest {
+    type Params = WorkspaceDiagnosticParams;
+    type Result = WorkspaceDiagnosticReport;
+    const METHOD: &'static str = "workspace/diagnostic";
+}
+
+/// The latest diagnostics of each file, which are returned to the pull diagnostics requests
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticsCache(Shared<Dict<NormalizedUrl, Vec<Diagnostic>>>);
+
+impl DiagnosticsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, uri: NormalizedUrl, diags: Vec<Diagnostic>) {
+        self.0.borrow_mut().insert(uri, diags);
+    }
+
+    pub fn get(&self, uri: &NormalizedUrl) -> Vec<Diagnostic> {
+        self.0.borrow().get(uri).cloned().unwrap_or_default()
+    }
+
+    pub fn entries(&self) -> Vec<(NormalizedUrl, Vec<Diagnostic>)> {
+        self.0
+            .borrow()
+            .iter()
+            .map(|(uri, diags)| (uri.clone(), diags.clone()))
+            .collect()
+    }
+}
+
+/// Declaration files (`.d.er`) are checked in the `declare` mode.
+pub(crate) fn module_mode(uri: &NormalizedUrl) -> &'static str {
+    if util::uri_to_path(uri).to_string_lossy().ends_with(".d.er") {
+        "declare"
+    } else {
+        "exec"
+    }
+}
+
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
     pub(crate) fn get_ast(&self, uri: &NormalizedUrl) -> Option<Module> {
         let code = self.file_cache.get_entire_code(uri).ok()?;
@@ -39,21 +151,38 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         uri: NormalizedUrl,
         code: S,
     ) -> ELSResult<()> {
-        send_log(format!("checking {uri}"))?;
-        let path = util::uri_to_path(&uri);
-        let mode = if path.to_string_lossy().ends_with(".d.er") {
-            "declare"
-        } else {
-            "exec"
-        };
+        let mode = module_mode(&uri);
         if let Some((old, new)) = self.analysis_result.get_ast(&uri).zip(self.get_ast(&uri)) {
             if ASTDiff::diff(old, &new).is_nop() {
                 crate::_log!("no changes: {uri}");
                 return Ok(());
             }
         }
-        let mut checker = self.get_checker(path.clone());
-        let artifact = match checker.build(code.into(), mode) {
+        // collect them before the module (and its dependents) are cleared from the graph
+        let dependents = self.transitive_dependents_of(&uri);
+        self.check_module(uri.clone(), code.into(), mode)?;
+        self.check_dependents(&uri, dependents)?;
+        self.refresh_diagnostics()
+    }
+
+    /// Checks the module and publishes the diagnostics (the modules depending on it are not checked).
+    pub(crate) fn check_module(
+        &mut self,
+        uri: NormalizedUrl,
+        code: String,
+        mode: &str,
+    ) -> ELSResult<()> {
+        send_log(format!("checking {uri}"))?;
+        let path = util::uri_to_path(&uri);
+        let token = self.pending_checks.start(&uri);
+        let mut checker = self.get_checker(path.clone(), token.clone());
+        let result = checker.build(code, mode);
+        // the file is being checked again, so the result is stale
+        if token.is_cancelled() {
+            send_log(format!("checking {uri} cancelled"))?;
+            return Ok(());
+        }
+        let artifact = match result {
             Ok(artifact) => {
                 send_log(format!(
                     "checking {uri} passed, found warns: {}",
@@ -97,10 +226,12 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 );
             } else {
                 shared.mod_cache.register(
-                    path,
+                    path.clone(),
                     artifact.object.clone(),
                     checker.get_context().unwrap().clone(),
                 );
+                // the dependents should not build the module again from the (unsaved) file
+                shared.promises.mark_as_finished(path);
             }
         }
         if let Some(module) = self.get_ast(&uri) {
@@ -111,11 +242,37 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             send_log(format!("{uri}: {}", module.context.name))?;
             self.modules.insert(uri.clone(), module);
         }
-        let dependents = self.dependents_of(&uri);
-        for dep in dependents {
+        self.pending_checks.finish(&uri, &token);
+        Ok(())
+    }
+
+    /// Re-checks the modules depending on `uri` directly or indirectly and publishes their diagnostics
+    /// (collect them with `transitive_dependents_of` before the module is cleared).
+    pub(crate) fn check_dependents(
+        &mut self,
+        uri: &NormalizedUrl,
+        dependents: Vec<NormalizedUrl>,
+    ) -> ELSResult<()> {
+        if dependents.is_empty() {
+            return Ok(());
+        }
+        let progress = self.start_progress(format!("Checking the dependents of {uri}"))?;
+        let total = dependents.len();
+        for (i, dep) in dependents.into_iter().enumerate() {
             // _log!("dep: {dep}");
-            let code = self.file_cache.get_entire_code(&dep)?.to_string();
-            self.check_file(dep, code)?;
+            if let Some(progress) = progress.as_ref() {
+                progress.report(
+                    format!("{}/{total}: {dep}", i + 1),
+                    (i * 100 / total) as u32,
+                )?;
+            }
+            // the dependents may not be opened in the editor, in which case the code is read from the file
+            let code = self.file_cache.get_entire_code(&dep)?;
+            let mode = module_mode(&dep);
+            self.check_module(dep, code, mode)?;
+        }
+        if let Some(progress) = progress {
+            progress.end()?;
         }
         Ok(())
     }
@@ -198,13 +355,23 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         uri_and_diags
     }
 
-    fn send_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>) -> ELSResult<()> {
+    fn send_diagnostics(&self, uri: Url, mut diagnostics: Vec<Diagnostic>) -> ELSResult<()> {
         if self
             .disabled_features
             .contains(&DefaultFeatures::Diagnostics)
         {
             return Ok(());
         }
+        // disabled by the settings, so clear those already sent
+        if self.is_disabled(DefaultFeatures::Diagnostics, Some(&uri)) {
+            diagnostics.clear();
+        }
+        self.diagnostics
+            .insert(NormalizedUrl::new(uri.clone()), diagnostics.clone());
+        // the client will request them
+        if self.pull_diagnostics {
+            return Ok(());
+        }
         let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
         if self
             .init_params
@@ -225,6 +392,109 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         Ok(())
     }
 
+    /// Asks the client to pull the diagnostics again, since those of other files may have been changed.
+    pub(crate) fn refresh_diagnostics(&self) -> ELSResult<()> {
+        if !self.pull_diagnostics || !self.diagnostics_refresh {
+            return Ok(());
+        }
+        send(&json!({
+            "jsonrpc": "2.0",
+            "id": REFRESH_DIAGNOSTICS_ID,
+            "method": "workspace/diagnostic/refresh",
+        }))
+    }
+
+    pub(crate) fn handle_document_diagnostic(
+        &mut self,
+        params: DocumentDiagnosticParams,
+    ) -> ELSResult<DocumentDiagnosticReport> {
+        send_log(format!("document diagnostics requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        Ok(DocumentDiagnosticReport::Full {
+            items: self.diagnostics.get(&uri),
+        })
+    }
+
+    pub(crate) fn handle_workspace_diagnostic(
+        &mut self,
+        params: WorkspaceDiagnosticParams,
+    ) -> ELSResult<WorkspaceDiagnosticReport> {
+        send_log(format!("workspace diagnostics requested: {params:?}"))?;
+        let items = self
+            .diagnostics
+            .entries()
+            .into_iter()
+            .map(|(uri, items)| WorkspaceDocumentDiagnosticReport {
+                // the file cache does not know whether the file is opened in the editor
+                version: None,
+                uri: uri.raw(),
+                report: DocumentDiagnosticReport::Full { items },
+            })
+            .collect();
+        Ok(WorkspaceDiagnosticReport { items })
+    }
+
+    /// Asks the client to notify the changes of the files made outside the editor (`workspace/didChangeWatchedFiles`).
+    pub(crate) fn register_file_watcher(&self) -> ELSResult<()> {
+        let supported = self
+            .init_params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+            .and_then(|caps| caps.dynamic_registration)
+            .unwrap_or(false);
+        if !supported {
+            return Ok(());
+        }
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: String::from("**/*.{er,py}"),
+                kind: None,
+            }],
+        };
+        let params = RegistrationParams {
+            registrations: vec![Registration {
+                id: String::from("els-watched-files"),
+                method: String::from("workspace/didChangeWatchedFiles"),
+                register_options: Some(serde_json::to_value(options)?),
+            }],
+        };
+        send(&json!({
+            "jsonrpc": "2.0",
+            "id": REGISTER_FILE_WATCHER_ID,
+            "method": "client/registerCapability",
+            "params": params,
+        }))
+    }
+
+    /// Re-checks the files changed outside the editor (e.g. by `git checkout`) and their dependents.
+    pub(crate) fn handle_watched_files_change(
+        &mut self,
+        params: DidChangeWatchedFilesParams,
+    ) -> ELSResult<()> {
+        for change in params.changes {
+            let uri = NormalizedUrl::new(change.uri);
+            // collect them before the module (and its dependents) are cleared from the graph
+            let dependents = self.transitive_dependents_of(&uri);
+            if change.typ == FileChangeType::DELETED {
+                self.file_cache.remove(&uri);
+                self.clear_cache(&uri);
+                self.send_diagnostics(uri.clone().raw(), vec![])?;
+            } else {
+                // not changed (e.g. saved in the editor, which is notified by `didSave`)
+                if !self.file_cache.reload(&uri)? {
+                    continue;
+                }
+                self.clear_cache(&uri);
+                let code = self.file_cache.get_entire_code(&uri)?;
+                self.check_module(uri.clone(), code, module_mode(&uri))?;
+            }
+            self.check_dependents(&uri, dependents)?;
+        }
+        self.refresh_diagnostics()
+    }
+
     /// Periodically send diagnostics without a request from the server.
     /// This is necessary to perform reactive error highlighting in editors such as Vim, where no action is taken until the buffer is saved.
     pub(crate) fn start_auto_diagnostics(&mut self) {
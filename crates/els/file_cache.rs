@@ -12,6 +12,7 @@ use lsp_types::{
 use erg_common::dict::Dict;
 use erg_common::shared::Shared;
 use erg_common::traits::DequeStream;
+use erg_compiler::erg_parser::cst::ConcreteSyntaxTree;
 use erg_compiler::erg_parser::lex::Lexer;
 use erg_compiler::erg_parser::token::{Token, TokenStream};
 
@@ -34,6 +35,8 @@ pub struct FileCacheEntry {
     pub code: String,
     pub ver: i32,
     pub token_stream: Option<TokenStream>,
+    /// parsed on demand, and discarded when `code` is changed
+    pub cst: Option<ConcreteSyntaxTree>,
 }
 
 impl FileCacheEntry {
@@ -130,6 +133,17 @@ impl FileCache {
         self.files.borrow_mut().get(uri)?.token_stream.clone()
     }
 
+    /// Returns the lossless syntax tree (with comments and whitespaces) of the file.
+    pub fn get_cst(&self, uri: &NormalizedUrl) -> Option<ConcreteSyntaxTree> {
+        let _ = self.load_once(uri);
+        let mut ent = self.files.borrow_mut();
+        let entry = ent.get_mut(uri)?;
+        if entry.cst.is_none() {
+            entry.cst = ConcreteSyntaxTree::parse(entry.code.clone()).ok();
+        }
+        entry.cst.clone()
+    }
+
     pub fn get_token(&self, uri: &NormalizedUrl, pos: Position) -> Option<Token> {
         let _ = self.load_once(uri);
         let ent = self.files.borrow_mut();
@@ -232,6 +246,7 @@ impl FileCache {
                 code,
                 ver,
                 token_stream,
+                cst: None,
             },
         );
     }
@@ -249,6 +264,7 @@ impl FileCache {
         entry.code = code;
         // entry.ver += 1;
         entry.token_stream = token_stream;
+        entry.cst = None;
     }
 
     pub(crate) fn incremental_update(&self, params: DidChangeTextDocumentParams) {
@@ -274,9 +290,25 @@ impl FileCache {
         entry.code = code;
         entry.ver = params.text_document.version;
         entry.token_stream = token_stream;
+        entry.cst = None;
+    }
+
+    /// Reads the file again (e.g. it has been changed outside the editor).
+    /// Returns `false` if the contents have not been changed.
+    pub(crate) fn reload(&self, uri: &NormalizedUrl) -> ELSResult<bool> {
+        let code = _get_code_from_uri(uri)?;
+        if self
+            .files
+            .borrow()
+            .get(uri)
+            .is_some_and(|entry| entry.code == code)
+        {
+            return Ok(false);
+        }
+        self.update(uri, code, None);
+        Ok(true)
     }
 
-    #[allow(unused)]
     pub fn remove(&mut self, uri: &NormalizedUrl) {
         self.files.borrow_mut().remove(uri);
     }
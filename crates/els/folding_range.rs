@@ -0,0 +1,159 @@
+use erg_common::set::Set;
+use erg_common::traits::Locational;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::cst::{ConcreteSyntaxTree, CstLine, TriviaKind};
+use erg_compiler::erg_parser::parse::Parsable;
+
+use lsp_types::{FoldingRange, FoldingRangeKind, FoldingRangeParams};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::NormalizedUrl;
+
+/// `lines` are 1 origin
+fn folding_range(start: u32, end: u32, kind: Option<FoldingRangeKind>) -> FoldingRange {
+    FoldingRange {
+        start_line: start - 1,
+        start_character: None,
+        end_line: end - 1,
+        end_character: None,
+        kind,
+    }
+}
+
+/// The first and the last line of the code of `line` (1 origin)
+fn code_lines(line: &CstLine) -> Option<(u32, u32)> {
+    let start = line.code_tokens().next()?.ln_begin()?;
+    let end = line
+        .code_tokens()
+        .filter_map(|token| token.ln_end())
+        .max()?;
+    Some((start, end))
+}
+
+fn is_import(line: &CstLine) -> bool {
+    line.depth == 0
+        && line
+            .code_tokens()
+            .any(|token| &token.text[..] == "import" || &token.text[..] == "pyimport")
+}
+
+/// Indented blocks (and logical lines spanning multiple lines)
+fn block_ranges(cst: &ConcreteSyntaxTree, ranges: &mut Vec<FoldingRange>) {
+    // (depth, the first line of the header)
+    let mut headers: Vec<(usize, u32)> = vec![];
+    let mut last_end = 0;
+    for line in cst.lines() {
+        let Some((start, end)) = code_lines(line) else {
+            continue;
+        };
+        while headers
+            .last()
+            .is_some_and(|(depth, _)| *depth >= line.depth)
+        {
+            let (_, header) = headers.pop().unwrap();
+            if last_end > header {
+                ranges.push(folding_range(header, last_end, None));
+            }
+        }
+        headers.push((line.depth, start));
+        last_end = end;
+    }
+    for (_, header) in headers.into_iter().rev() {
+        if last_end > header {
+            ranges.push(folding_range(header, last_end, None));
+        }
+    }
+}
+
+/// Consecutive import lines (`x = import "x"`)
+fn import_ranges(cst: &ConcreteSyntaxTree, ranges: &mut Vec<FoldingRange>) {
+    let mut group: Option<(u32, u32)> = None;
+    for line in cst.lines() {
+        match code_lines(line) {
+            Some((start, end)) if is_import(line) => match group.as_mut() {
+                Some((_, group_end)) if *group_end + 1 == start => *group_end = end,
+                _ => {
+                    if let Some((start, end)) = group.replace((start, end)) {
+                        ranges.push(folding_range(start, end, Some(FoldingRangeKind::Imports)));
+                    }
+                }
+            },
+            Some(_) => {
+                if let Some((start, end)) = group.take() {
+                    ranges.push(folding_range(start, end, Some(FoldingRangeKind::Imports)));
+                }
+            }
+            None => {}
+        }
+    }
+    if let Some((start, end)) = group {
+        ranges.push(folding_range(start, end, Some(FoldingRangeKind::Imports)));
+    }
+    ranges.retain(|range| {
+        range.kind != Some(FoldingRangeKind::Imports) || range.start_line < range.end_line
+    });
+}
+
+/// Doc comments, multi-line comments (`#[ ... ]#`) and consecutive line comments
+fn comment_ranges(cst: &ConcreteSyntaxTree, ranges: &mut Vec<FoldingRange>) {
+    let comment = Some(FoldingRangeKind::Comment);
+    for doc in cst.doc_comments() {
+        let (start, end) = doc.range;
+        if start < end {
+            ranges.push(folding_range(start, end, comment.clone()));
+        }
+    }
+    // the comments following code (`x = 1 # ...`) are not folded
+    let code_lines = cst
+        .tokens()
+        .filter(|token| !token.is_layout())
+        .filter_map(|token| token.ln_end())
+        .collect::<Set<_>>();
+    let mut group: Option<(u32, u32)> = None;
+    for trivia in cst.comments().filter(|trivia| !trivia.is_doc_comment()) {
+        let (Some(start), Some(end)) = (trivia.ln_begin(), trivia.ln_end()) else {
+            continue;
+        };
+        if code_lines.contains(&start) {
+            continue;
+        }
+        match (trivia.kind, group.as_mut()) {
+            (TriviaKind::Comment, Some((_, group_end))) if *group_end + 1 == start => {
+                *group_end = end;
+            }
+            (TriviaKind::Comment, _) => {
+                if let Some((start, end)) = group.replace((start, end)) {
+                    ranges.push(folding_range(start, end, comment.clone()));
+                }
+            }
+            _ => {
+                ranges.push(folding_range(start, end, comment.clone()));
+            }
+        }
+    }
+    if let Some((start, end)) = group {
+        ranges.push(folding_range(start, end, comment.clone()));
+    }
+    ranges.retain(|range| range.kind != comment || range.start_line < range.end_line);
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    pub(crate) fn handle_folding_range(
+        &mut self,
+        params: FoldingRangeParams,
+    ) -> ELSResult<Option<Vec<FoldingRange>>> {
+        send_log(format!("folding range requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let Some(cst) = self.file_cache.get_cst(&uri) else {
+            return Ok(None);
+        };
+        let mut ranges = vec![];
+        block_ranges(&cst, &mut ranges);
+        import_ranges(&cst, &mut ranges);
+        comment_ranges(&cst, &mut ranges);
+        ranges.sort_by_key(|range| (range.start_line, range.end_line));
+        ranges.dedup_by_key(|range| (range.start_line, range.end_line));
+        Ok(Some(ranges))
+    }
+}
@@ -0,0 +1,250 @@
+use erg_common::traits::DequeStream;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::format::{format_code, INDENT_WIDTH};
+use erg_compiler::erg_parser::lex::Lexer;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::erg_parser::token::TokenKind;
+
+use lsp_types::{
+    DocumentFormattingParams, DocumentOnTypeFormattingParams, DocumentRangeFormattingParams,
+    Position, Range, TextEdit,
+};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::NormalizedUrl;
+
+/// The line diff is not computed for larger changes (the whole changed region is replaced instead).
+const MAX_DIFF_SIZE: usize = 1_000_000;
+
+/// A replacement of the lines `old_start..old_end` with `new_lines`
+#[derive(Debug)]
+struct Hunk<'a> {
+    old_start: usize,
+    old_end: usize,
+    new_lines: &'a [&'a str],
+}
+
+impl Hunk<'_> {
+    /// Do the replaced lines intersect with the lines `start..=end`?
+    const fn intersects(&self, start: usize, end: usize) -> bool {
+        if self.old_start == self.old_end {
+            start <= self.old_start && self.old_start <= end + 1
+        } else {
+            self.old_start <= end && start < self.old_end
+        }
+    }
+}
+
+/// The formatter mostly changes whitespaces, so lines are aligned ignoring them.
+fn line_key(line: &str) -> String {
+    line.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// Computes the line-based difference from `old` to `new` (each line includes its line break).
+/// Lines that differ only in whitespaces are replaced one by one.
+fn diff_lines<'a>(old: &[&str], new: &'a [&'a str]) -> Vec<Hunk<'a>> {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+    if old_mid.is_empty() && new_mid.is_empty() {
+        return vec![];
+    }
+    if old_mid.len() * new_mid.len() > MAX_DIFF_SIZE {
+        return vec![Hunk {
+            old_start: prefix,
+            old_end: prefix + old_mid.len(),
+            new_lines: new_mid,
+        }];
+    }
+    let old_keys = old_mid
+        .iter()
+        .map(|line| line_key(line))
+        .collect::<Vec<_>>();
+    let new_keys = new_mid
+        .iter()
+        .map(|line| line_key(line))
+        .collect::<Vec<_>>();
+    // lcs[i][j]: the length of the LCS of `old_keys[i..]` and `new_keys[j..]`
+    let mut lcs = vec![vec![0usize; new_mid.len() + 1]; old_mid.len() + 1];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i][j] = if old_keys[i] == new_keys[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut hunks = vec![];
+    let (mut i, mut j) = (0, 0);
+    let (mut hunk_i, mut hunk_j) = (0, 0);
+    while i < old_mid.len() || j < new_mid.len() {
+        if i < old_mid.len() && j < new_mid.len() && old_keys[i] == new_keys[j] {
+            if (hunk_i, hunk_j) != (i, j) {
+                hunks.push(Hunk {
+                    old_start: prefix + hunk_i,
+                    old_end: prefix + i,
+                    new_lines: &new_mid[hunk_j..j],
+                });
+            }
+            if old_mid[i] != new_mid[j] {
+                hunks.push(Hunk {
+                    old_start: prefix + i,
+                    old_end: prefix + i + 1,
+                    new_lines: &new_mid[j..=j],
+                });
+            }
+            i += 1;
+            j += 1;
+            (hunk_i, hunk_j) = (i, j);
+        } else if j < new_mid.len() && (i == old_mid.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            j += 1;
+        } else {
+            i += 1;
+        }
+    }
+    if (hunk_i, hunk_j) != (i, j) {
+        hunks.push(Hunk {
+            old_start: prefix + hunk_i,
+            old_end: prefix + i,
+            new_lines: &new_mid[hunk_j..j],
+        });
+    }
+    hunks
+}
+
+/// The position of the beginning of the line `line` (or the end of the code).
+fn line_position(lines: &[&str], line: usize) -> Position {
+    if line < lines.len() {
+        Position::new(line as u32, 0)
+    } else {
+        match lines.last() {
+            Some(last) if !last.ends_with('\n') => {
+                let col = last.encode_utf16().count();
+                Position::new(lines.len() as u32 - 1, col as u32)
+            }
+            _ => Position::new(lines.len() as u32, 0),
+        }
+    }
+}
+
+/// Edits to transform `code` into `formatted`.
+/// If `range` is specified, only the changes of the lines in the range are returned.
+fn text_edits(code: &str, formatted: &str, range: Option<Range>) -> Vec<TextEdit> {
+    let old = code.split_inclusive('\n').collect::<Vec<_>>();
+    let new = formatted.split_inclusive('\n').collect::<Vec<_>>();
+    diff_lines(&old, &new)
+        .into_iter()
+        .filter(|hunk| match range {
+            Some(range) => hunk.intersects(range.start.line as usize, range.end.line as usize),
+            None => true,
+        })
+        .map(|hunk| {
+            let start = line_position(&old, hunk.old_start);
+            let end = line_position(&old, hunk.old_end);
+            TextEdit::new(Range::new(start, end), hunk.new_lines.concat())
+        })
+        .collect()
+}
+
+/// Does the line end with a token that starts a block (`=`, `->`, `=>`, `:`, or `.`/`::` of a method definition block)?
+fn opens_block(line: &str) -> bool {
+    let Ok(tokens) = Lexer::from_str(line.trim().to_string()).lex() else {
+        return false;
+    };
+    let last = tokens
+        .iter()
+        .rev()
+        .find(|token| !matches!(token.kind, TokenKind::Newline | TokenKind::EOF));
+    last.is_some_and(|token| {
+        matches!(
+            token.kind,
+            TokenKind::Assign
+                | TokenKind::FuncArrow
+                | TokenKind::ProcArrow
+                | TokenKind::Colon
+                | TokenKind::Dot
+                | TokenKind::DblColon
+        )
+    })
+}
+
+fn indent_of(line: &str) -> usize {
+    line.chars().take_while(|c| *c == ' ').count()
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    fn format_edits(
+        &self,
+        uri: &NormalizedUrl,
+        range: Option<Range>,
+    ) -> ELSResult<Option<Vec<TextEdit>>> {
+        let code = self.file_cache.get_entire_code(uri)?;
+        match format_code(&code) {
+            Ok(formatted) => Ok(Some(text_edits(&code, &formatted, range))),
+            Err(errs) => {
+                send_log(format!("cannot format {uri}: {errs}"))?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub(crate) fn handle_formatting(
+        &mut self,
+        params: DocumentFormattingParams,
+    ) -> ELSResult<Option<Vec<TextEdit>>> {
+        send_log(format!("formatting requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        self.format_edits(&uri, None)
+    }
+
+    pub(crate) fn handle_range_formatting(
+        &mut self,
+        params: DocumentRangeFormattingParams,
+    ) -> ELSResult<Option<Vec<TextEdit>>> {
+        send_log(format!("range formatting requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        self.format_edits(&uri, Some(params.range))
+    }
+
+    /// Indents the new line after a block opener.
+    pub(crate) fn handle_on_type_formatting(
+        &mut self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> ELSResult<Option<Vec<TextEdit>>> {
+        send_log(format!("on type formatting requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document_position.text_document.uri);
+        let pos = params.text_document_position.position;
+        if params.ch != "\n" || pos.line == 0 {
+            return Ok(None);
+        }
+        let Some(prev) = self.file_cache.get_line(&uri, pos.line - 1) else {
+            return Ok(None);
+        };
+        if !opens_block(&prev) {
+            return Ok(None);
+        }
+        let current = self.file_cache.get_line(&uri, pos.line).unwrap_or_default();
+        let indent = indent_of(&prev) + INDENT_WIDTH;
+        let current_indent = indent_of(&current);
+        if current_indent == indent {
+            return Ok(None);
+        }
+        let range = Range::new(
+            Position::new(pos.line, 0),
+            Position::new(pos.line, current_indent as u32),
+        );
+        Ok(Some(vec![TextEdit::new(range, " ".repeat(indent))]))
+    }
+}
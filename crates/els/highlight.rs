@@ -0,0 +1,81 @@
+use erg_common::traits::Locational;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::hir::{Accessor, Expr, ReDef};
+
+use lsp_types::{DocumentHighlight, DocumentHighlightKind, DocumentHighlightParams};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    /// The definition and reassignments (`x.y = ...`) are `WRITE`, and the other references are `READ`.
+    pub(crate) fn handle_document_highlight(
+        &mut self,
+        params: DocumentHighlightParams,
+    ) -> ELSResult<Option<Vec<DocumentHighlight>>> {
+        send_log(format!("document highlight requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let path = util::uri_to_path(&uri);
+        let Some(token) = self.file_cache.get_token(&uri, pos) else {
+            return Ok(None);
+        };
+        let Some(visitor) = self.get_visitor(&uri) else {
+            return Ok(None);
+        };
+        let Some(vi) = visitor.get_info(&token) else {
+            return Ok(None);
+        };
+        let mut highlights = vec![];
+        if vi.def_loc.module.as_ref() == Some(&path) {
+            if let Some(range) = util::loc_to_range(vi.def_loc.loc) {
+                highlights.push(DocumentHighlight {
+                    range,
+                    kind: Some(DocumentHighlightKind::WRITE),
+                });
+            }
+        }
+        let referrers = self
+            .get_index()
+            .and_then(|index| index.get_refs(&vi.def_loc))
+            .map(|value| value.referrers.iter().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        for referrer in referrers {
+            if referrer.module.as_ref() != Some(&path) {
+                continue;
+            }
+            let Some(range) = util::loc_to_range(referrer.loc) else {
+                continue;
+            };
+            let is_redef = self
+                .file_cache
+                .get_token(&uri, range.start)
+                .and_then(|token| visitor.get_min_expr(&token))
+                .is_some_and(|expr| match expr {
+                    Expr::ReDef(ReDef {
+                        attr: Accessor::Ident(ident),
+                        ..
+                    }) => ident.loc() == referrer.loc,
+                    Expr::ReDef(ReDef {
+                        attr: Accessor::Attr(attr),
+                        ..
+                    }) => attr.ident.loc() == referrer.loc,
+                    _ => false,
+                });
+            let kind = if is_redef {
+                DocumentHighlightKind::WRITE
+            } else {
+                DocumentHighlightKind::READ
+            };
+            if highlights.iter().all(|highlight| highlight.range != range) {
+                highlights.push(DocumentHighlight {
+                    range,
+                    kind: Some(kind),
+                });
+            }
+        }
+        Ok(Some(highlights))
+    }
+}
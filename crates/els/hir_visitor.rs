@@ -14,6 +14,10 @@ use crate::util::{self, NormalizedUrl};
 /// * namespace where the cursor is located (`get_namespace`)
 /// * cursor(`Token`) -> `Expr` mapping (`get_min_expr`)
 /// * cursor(`Token`) -> `VarInfo` mapping (`get_info`)
+/// * cursor(`Position`) -> enclosing subroutine definition mapping (`get_enclosing_def`)
+/// * subroutine definition -> callees mapping (`get_callees`)
+/// * cursor(`Position`) -> enclosing class/trait definition mapping (`get_type_def`)
+/// * cursor(`Position`) -> enclosing expressions mapping (`get_expr_ancestors`)
 pub struct HIRVisitor<'a> {
     hir: MappedRwLockReadGuard<'a, HIR>,
     file_cache: &'a FileCache,
@@ -713,3 +717,286 @@ impl<'a> HIRVisitor<'a> {
             .or_else(|| self.get_expr_info(&tasc.spec.expr, token))
     }
 }
+
+impl<'a> HIRVisitor<'a> {
+    /// Returns the innermost subroutine definition containing `pos`.
+    /// Returns `None` if `pos` is at the top level of the module.
+    pub fn get_enclosing_def(&self, pos: Position) -> Option<&Def> {
+        self.get_exprs_enclosing_def(self.hir.module.iter(), pos)
+    }
+
+    fn get_exprs_enclosing_def<'e, I: Iterator<Item = &'e Expr>>(
+        &self,
+        exprs: I,
+        pos: Position,
+    ) -> Option<&'e Def> {
+        for expr in exprs {
+            if let Some(def) = self.get_expr_enclosing_def(expr, pos) {
+                return Some(def);
+            }
+        }
+        None
+    }
+
+    fn get_expr_enclosing_def<'e>(&self, expr: &'e Expr, pos: Position) -> Option<&'e Def> {
+        if !util::pos_in_loc(expr, pos) {
+            return None;
+        }
+        match expr {
+            Expr::Def(def) => {
+                let inner = self.get_exprs_enclosing_def(def.body.block.iter(), pos);
+                if def.sig.ident().vi.t.is_subr() {
+                    inner.or(Some(def))
+                } else {
+                    inner
+                }
+            }
+            Expr::ClassDef(class_def) => {
+                self.get_exprs_enclosing_def(class_def.methods.iter(), pos)
+            }
+            Expr::PatchDef(patch_def) => {
+                self.get_exprs_enclosing_def(patch_def.methods.iter(), pos)
+            }
+            Expr::Call(call) => self
+                .get_expr_enclosing_def(&call.obj, pos)
+                .or_else(|| self.get_args_enclosing_def(&call.args, pos)),
+            Expr::Lambda(lambda) => self.get_exprs_enclosing_def(lambda.body.iter(), pos),
+            Expr::BinOp(bin) => self
+                .get_expr_enclosing_def(&bin.lhs, pos)
+                .or_else(|| self.get_expr_enclosing_def(&bin.rhs, pos)),
+            Expr::UnaryOp(unary) => self.get_expr_enclosing_def(&unary.expr, pos),
+            Expr::TypeAsc(tasc) => self.get_expr_enclosing_def(&tasc.expr, pos),
+            Expr::ReDef(redef) => self.get_exprs_enclosing_def(redef.block.iter(), pos),
+            Expr::Record(record) => self.get_exprs_enclosing_def(
+                record.attrs.iter().flat_map(|attr| attr.body.block.iter()),
+                pos,
+            ),
+            Expr::Array(Array::Normal(arr)) => self.get_args_enclosing_def(&arr.elems, pos),
+            Expr::Set(Set::Normal(set)) => self.get_args_enclosing_def(&set.elems, pos),
+            Expr::Tuple(Tuple::Normal(tuple)) => self.get_args_enclosing_def(&tuple.elems, pos),
+            Expr::Dict(Dict::Normal(dict)) => self
+                .get_exprs_enclosing_def(dict.kvs.iter().flat_map(|kv| [&kv.key, &kv.value]), pos),
+            Expr::Dummy(dummy) => self.get_exprs_enclosing_def(dummy.iter(), pos),
+            Expr::Compound(block) | Expr::Code(block) => {
+                self.get_exprs_enclosing_def(block.iter(), pos)
+            }
+            _ => None,
+        }
+    }
+
+    fn get_args_enclosing_def<'e>(&self, args: &'e Args, pos: Position) -> Option<&'e Def> {
+        self.get_exprs_enclosing_def(
+            args.pos_args
+                .iter()
+                .chain(args.var_args.as_deref())
+                .map(|arg| &arg.expr)
+                .chain(args.kw_args.iter().map(|arg| &arg.expr)),
+            pos,
+        )
+    }
+}
+
+impl<'a> HIRVisitor<'a> {
+    /// Returns the identifiers of the called subroutines in `def` (or at the top level of the module if `def` is `None`).
+    /// The calls in nested subroutine definitions are not included.
+    pub fn get_callees<'e>(&'e self, def: Option<&'e Def>) -> Vec<&'e Identifier> {
+        let mut callees = vec![];
+        match def {
+            Some(def) => self.collect_exprs_callees(def.body.block.iter(), &mut callees),
+            None => self.collect_exprs_callees(self.hir.module.iter(), &mut callees),
+        }
+        callees
+    }
+
+    fn collect_exprs_callees<'e, I: Iterator<Item = &'e Expr>>(
+        &self,
+        exprs: I,
+        callees: &mut Vec<&'e Identifier>,
+    ) {
+        for expr in exprs {
+            self.collect_expr_callees(expr, callees);
+        }
+    }
+
+    fn collect_expr_callees<'e>(&self, expr: &'e Expr, callees: &mut Vec<&'e Identifier>) {
+        match expr {
+            Expr::Call(call) => {
+                match (&call.attr_name, call.obj.as_ref()) {
+                    (Some(attr), _) => callees.push(attr),
+                    (None, Expr::Accessor(Accessor::Ident(ident))) => callees.push(ident),
+                    (None, Expr::Accessor(Accessor::Attr(attr))) => callees.push(&attr.ident),
+                    _ => {}
+                }
+                self.collect_expr_callees(&call.obj, callees);
+                self.collect_args_callees(&call.args, callees);
+            }
+            Expr::Def(def) if !def.sig.ident().vi.t.is_subr() => {
+                self.collect_exprs_callees(def.body.block.iter(), callees);
+            }
+            Expr::Accessor(Accessor::Attr(attr)) => self.collect_expr_callees(&attr.obj, callees),
+            Expr::Lambda(lambda) => self.collect_exprs_callees(lambda.body.iter(), callees),
+            Expr::BinOp(bin) => {
+                self.collect_expr_callees(&bin.lhs, callees);
+                self.collect_expr_callees(&bin.rhs, callees);
+            }
+            Expr::UnaryOp(unary) => self.collect_expr_callees(&unary.expr, callees),
+            Expr::TypeAsc(tasc) => self.collect_expr_callees(&tasc.expr, callees),
+            Expr::ReDef(redef) => self.collect_exprs_callees(redef.block.iter(), callees),
+            Expr::Record(record) => self.collect_exprs_callees(
+                record.attrs.iter().flat_map(|attr| attr.body.block.iter()),
+                callees,
+            ),
+            Expr::Array(Array::Normal(arr)) => self.collect_args_callees(&arr.elems, callees),
+            Expr::Set(Set::Normal(set)) => self.collect_args_callees(&set.elems, callees),
+            Expr::Tuple(Tuple::Normal(tuple)) => self.collect_args_callees(&tuple.elems, callees),
+            Expr::Dict(Dict::Normal(dict)) => self.collect_exprs_callees(
+                dict.kvs.iter().flat_map(|kv| [&kv.key, &kv.value]),
+                callees,
+            ),
+            Expr::Dummy(dummy) => self.collect_exprs_callees(dummy.iter(), callees),
+            Expr::Compound(block) | Expr::Code(block) => {
+                self.collect_exprs_callees(block.iter(), callees)
+            }
+            // subroutine definitions, class definitions, literals, etc.
+            _ => {}
+        }
+    }
+
+    fn collect_args_callees<'e>(&self, args: &'e Args, callees: &mut Vec<&'e Identifier>) {
+        self.collect_exprs_callees(
+            args.pos_args
+                .iter()
+                .chain(args.var_args.as_deref())
+                .map(|arg| &arg.expr)
+                .chain(args.kw_args.iter().map(|arg| &arg.expr)),
+            callees,
+        );
+    }
+}
+
+impl<'a> HIRVisitor<'a> {
+    /// Returns the class definition (`ClassDef`) or the trait definition (`Def`) containing `pos`.
+    pub fn get_type_def(&self, pos: Position) -> Option<&Expr> {
+        self.get_exprs_type_def(self.hir.module.iter(), pos)
+    }
+
+    fn get_exprs_type_def<'e, I: Iterator<Item = &'e Expr>>(
+        &self,
+        exprs: I,
+        pos: Position,
+    ) -> Option<&'e Expr> {
+        for expr in exprs {
+            match expr {
+                Expr::ClassDef(class_def) if Self::class_def_contains(class_def, pos) => {
+                    return Some(expr)
+                }
+                Expr::Def(def) if def.def_kind().is_trait() && util::pos_in_loc(def, pos) => {
+                    return Some(expr)
+                }
+                Expr::Dummy(dummy) if util::pos_in_loc(dummy, pos) => {
+                    return self.get_exprs_type_def(dummy.iter(), pos)
+                }
+                Expr::Compound(block) if util::pos_in_loc(block, pos) => {
+                    return self.get_exprs_type_def(block.iter(), pos)
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// `ClassDef::loc` does not cover the lines of `require_or_sup` if there are no methods,
+    /// so the lines are compared.
+    fn class_def_contains(class_def: &ClassDef, pos: Position) -> bool {
+        let ln_begin = class_def.sig.ln_begin().unwrap_or(0);
+        let ln_end = [
+            class_def.sig.ln_end(),
+            class_def.methods.ln_end(),
+            class_def
+                .require_or_sup
+                .as_ref()
+                .and_then(|req_sup| req_sup.ln_end()),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(0);
+        (ln_begin..=ln_end).contains(&(pos.line + 1))
+    }
+}
+
+/// The direct sub-expressions of `expr`
+fn child_exprs(expr: &Expr) -> Vec<&Expr> {
+    fn args_exprs(args: &Args) -> Vec<&Expr> {
+        args.pos_args
+            .iter()
+            .chain(args.var_args.as_deref())
+            .map(|arg| &arg.expr)
+            .chain(args.kw_args.iter().map(|arg| &arg.expr))
+            .collect()
+    }
+    match expr {
+        Expr::Accessor(Accessor::Attr(attr)) => vec![&attr.obj],
+        Expr::BinOp(bin) => vec![&bin.lhs, &bin.rhs],
+        Expr::UnaryOp(unary) => vec![&unary.expr],
+        Expr::Call(call) => {
+            let mut exprs = vec![call.obj.as_ref()];
+            exprs.extend(args_exprs(&call.args));
+            exprs
+        }
+        Expr::Def(def) => def.body.block.iter().collect(),
+        Expr::ClassDef(class_def) => class_def
+            .require_or_sup
+            .as_deref()
+            .into_iter()
+            .chain(class_def.methods.iter())
+            .collect(),
+        Expr::PatchDef(patch_def) => [patch_def.base.as_ref()]
+            .into_iter()
+            .chain(patch_def.methods.iter())
+            .collect(),
+        Expr::Lambda(lambda) => lambda.body.iter().collect(),
+        Expr::Array(Array::Normal(arr)) => args_exprs(&arr.elems),
+        Expr::Set(Set::Normal(set)) => args_exprs(&set.elems),
+        Expr::Tuple(Tuple::Normal(tuple)) => args_exprs(&tuple.elems),
+        Expr::Dict(Dict::Normal(dict)) => dict
+            .kvs
+            .iter()
+            .flat_map(|kv| [&kv.key, &kv.value])
+            .collect(),
+        Expr::Record(record) => record
+            .attrs
+            .iter()
+            .flat_map(|attr| attr.body.block.iter())
+            .collect(),
+        Expr::TypeAsc(tasc) => vec![&tasc.expr],
+        Expr::ReDef(redef) => redef.block.iter().collect(),
+        Expr::Dummy(dummy) => dummy.iter().collect(),
+        Expr::Compound(block) | Expr::Code(block) => block.iter().collect(),
+        _ => vec![],
+    }
+}
+
+impl<'a> HIRVisitor<'a> {
+    /// Returns the expressions containing `pos`, from the outermost one (a chunk of the module) to the innermost one.
+    pub fn get_expr_ancestors(&self, pos: Position) -> Vec<&Expr> {
+        let mut ancestors = vec![];
+        let mut exprs = self.hir.module.iter().collect::<Vec<_>>();
+        while let Some(expr) = exprs
+            .into_iter()
+            .find(|expr| Self::expr_contains(expr, pos))
+        {
+            ancestors.push(expr);
+            exprs = child_exprs(expr);
+        }
+        ancestors
+    }
+
+    /// Unlike `util::pos_in_loc`, the columns of multi-line expressions are also compared.
+    fn expr_contains(expr: &Expr, pos: Position) -> bool {
+        if let Expr::ClassDef(class_def) = expr {
+            return Self::class_def_contains(class_def, pos);
+        }
+        util::loc_to_range(expr.loc()).is_some_and(|range| range.start <= pos && pos <= range.end)
+    }
+}
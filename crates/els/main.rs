@@ -1,3 +1,5 @@
+mod call_hierarchy;
+mod cancel;
 mod channels;
 mod code_action;
 mod code_lens;
@@ -7,15 +9,23 @@ mod definition;
 mod diagnostics;
 mod diff;
 mod file_cache;
+mod folding_range;
+mod formatting;
+mod highlight;
 mod hir_visitor;
 mod hover;
 mod inlay_hint;
 mod message;
+mod progress;
 mod references;
 mod rename;
+mod selection_range;
 mod semantic;
 mod server;
+mod settings;
 mod sig_help;
+mod symbol;
+mod type_hierarchy;
 mod util;
 
 use erg_common::config::ErgConfig;
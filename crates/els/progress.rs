@@ -0,0 +1,134 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use erg_common::dict::Dict;
+use erg_common::shared::Shared;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+
+use lsp_types::{
+    NumberOrString, ProgressParams, ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+use serde_json::{json, Value};
+
+use crate::server::{send, ELSResult, Server};
+
+/// The ids of `window/workDoneProgress/create` requests (the smaller ids are reserved, e.g. `HEALTH_CHECKER_ID`)
+static PROGRESS_REQUEST_ID: AtomicI64 = AtomicI64::new(20000);
+/// If the client does not answer `window/workDoneProgress/create` in time, the progress is not reported
+const PROGRESS_CREATE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The `window/workDoneProgress/create` requests waiting for the responses.
+/// The responses are received by the message reader thread,
+/// since the progress may be started on the main thread, which dispatches the other messages.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProgressRequests(Shared<Dict<i64, mpsc::Sender<bool>>>);
+
+impl ProgressRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn register(&self, id: i64) -> mpsc::Receiver<bool> {
+        let (sender, receiver) = mpsc::channel();
+        self.0.borrow_mut().insert(id, sender);
+        receiver
+    }
+
+    /// Returns `true` if `msg` is the response to a `window/workDoneProgress/create` request.
+    pub fn resolve(&self, msg: &Value) -> bool {
+        if msg.get("method").is_some() {
+            return false;
+        }
+        let Some(id) = msg.get("id").and_then(|id| id.as_i64()) else {
+            return false;
+        };
+        let Some(sender) = self.0.borrow_mut().remove(&id) else {
+            return false;
+        };
+        // the receiver may have timed out
+        let _ = sender.send(msg.get("error").is_none());
+        true
+    }
+}
+
+/// A server-initiated work done progress, reported to the client with `$/progress`
+#[derive(Debug)]
+pub(crate) struct Progress {
+    token: NumberOrString,
+}
+
+impl Progress {
+    fn notify(&self, value: WorkDoneProgress) -> ELSResult<()> {
+        let params = ProgressParams {
+            token: self.token.clone(),
+            value: ProgressParamsValue::WorkDone(value),
+        };
+        send(&json!({
+            "jsonrpc": "2.0",
+            "method": "$/progress",
+            "params": params,
+        }))
+    }
+
+    pub(crate) fn report<S: Into<String>>(&self, message: S, percentage: u32) -> ELSResult<()> {
+        self.notify(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: None,
+            message: Some(message.into()),
+            percentage: Some(percentage.min(100)),
+        }))
+    }
+
+    pub(crate) fn end(self) -> ELSResult<()> {
+        self.notify(WorkDoneProgress::End(WorkDoneProgressEnd { message: None }))
+    }
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    fn supports_work_done_progress(&self) -> bool {
+        self.init_params
+            .capabilities
+            .window
+            .as_ref()
+            .and_then(|window| window.work_done_progress)
+            .unwrap_or(false)
+    }
+
+    /// Creates a progress and sends the `begin` notification.
+    /// Returns `None` if the client does not support work done progress or refuses to create it.
+    pub(crate) fn start_progress<S: Into<String>>(&self, title: S) -> ELSResult<Option<Progress>> {
+        if !self.supports_work_done_progress() {
+            return Ok(None);
+        }
+        let id = PROGRESS_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        let token = NumberOrString::String(format!("els/{id}"));
+        let params = WorkDoneProgressCreateParams {
+            token: token.clone(),
+        };
+        let created = self.progress_requests.register(id);
+        send(&json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "window/workDoneProgress/create",
+            "params": params,
+        }))?;
+        // the token must not be used until the client has created it
+        if !created
+            .recv_timeout(PROGRESS_CREATE_TIMEOUT)
+            .unwrap_or(false)
+        {
+            return Ok(None);
+        }
+        let progress = Progress { token };
+        progress.notify(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: title.into(),
+            cancellable: None,
+            message: None,
+            percentage: Some(0),
+        }))?;
+        Ok(Some(progress))
+    }
+}
@@ -146,7 +146,9 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
 
     /// self is __included__
     pub fn dependencies_of(&self, uri: &NormalizedUrl) -> Vec<NormalizedUrl> {
-        let graph = self.get_graph().unwrap();
+        let Some(graph) = self.get_graph() else {
+            return vec![];
+        };
         let path = NormalizedPathBuf::from(util::uri_to_path(uri));
         graph.sort().unwrap();
         let self_node = graph.get_node(&path).unwrap();
@@ -160,7 +162,9 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
 
     /// self is __not included__
     pub fn dependents_of(&self, uri: &NormalizedUrl) -> Vec<NormalizedUrl> {
-        let graph = self.get_graph().unwrap();
+        let Some(graph) = self.get_graph() else {
+            return vec![];
+        };
         let path = NormalizedPathBuf::from(util::uri_to_path(uri));
         graph
             .ref_inner()
@@ -169,6 +173,32 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             .map(|node| NormalizedUrl::new(Url::from_file_path(node.id.to_path_buf()).unwrap()))
             .collect()
     }
+
+    /// The modules that depend on `uri` directly or indirectly, sorted so that each module comes after its dependencies.
+    /// self is __not included__
+    pub fn transitive_dependents_of(&self, uri: &NormalizedUrl) -> Vec<NormalizedUrl> {
+        let Some(graph) = self.get_graph() else {
+            return vec![];
+        };
+        let path = NormalizedPathBuf::from(util::uri_to_path(uri));
+        let graph = graph.ref_inner();
+        let mut dependents = graph
+            .iter()
+            .filter_map(|node| {
+                let ancestors = graph.ancestors(&node.id);
+                ancestors
+                    .contains(&path)
+                    .then(|| (ancestors.len(), node.id.clone()))
+            })
+            .collect::<Vec<_>>();
+        // a module has more ancestors than any of its dependencies
+        dependents.sort_by_key(|(n_ancestors, _)| *n_ancestors);
+        dependents
+            .into_iter()
+            .filter_map(|(_, path)| Url::from_file_path(path.to_path_buf()).ok())
+            .map(NormalizedUrl::new)
+            .collect()
+    }
 }
 
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
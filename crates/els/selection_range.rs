@@ -0,0 +1,60 @@
+use erg_common::traits::Locational;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+
+use lsp_types::{Position, Range, SelectionRange, SelectionRangeParams};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    /// The ranges containing `pos`, from the innermost one (the token) to the outermost one (the chunk of the module)
+    fn get_selection_ranges(&self, uri: &NormalizedUrl, pos: Position) -> Vec<Range> {
+        let mut ranges = vec![];
+        if let Some(token) = self.file_cache.get_token(uri, pos) {
+            ranges.extend(util::loc_to_range(token.loc()));
+        }
+        if let Some(visitor) = self.get_visitor(uri) {
+            for expr in visitor.get_expr_ancestors(pos).into_iter().rev() {
+                let Some(range) = util::loc_to_range(expr.loc()) else {
+                    continue;
+                };
+                // each range must contain the previous one
+                match ranges.last() {
+                    Some(last) if range == *last => {}
+                    Some(last) if range.start > last.start || range.end < last.end => {}
+                    _ => ranges.push(range),
+                }
+            }
+        }
+        ranges
+    }
+
+    pub(crate) fn handle_selection_range(
+        &mut self,
+        params: SelectionRangeParams,
+    ) -> ELSResult<Option<Vec<SelectionRange>>> {
+        send_log(format!("selection range requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let mut result = vec![];
+        for pos in params.positions {
+            let mut selection = None;
+            for range in self.get_selection_ranges(&uri, pos).into_iter().rev() {
+                selection = Some(Box::new(SelectionRange {
+                    range,
+                    parent: selection,
+                }));
+            }
+            // a result is needed for each position
+            result.push(selection.map_or(
+                SelectionRange {
+                    range: Range::new(pos, pos),
+                    parent: None,
+                },
+                |selection| *selection,
+            ));
+        }
+        Ok(Some(result))
+    }
+}
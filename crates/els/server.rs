@@ -10,7 +10,8 @@ use erg_common::consts::PYTHON_MODE;
 use erg_common::dict::Dict;
 use erg_common::env::erg_path;
 use erg_common::shared::{
-    MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLockReadGuard, RwLockWriteGuard, Shared,
+    CancellationToken, MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLockReadGuard,
+    RwLockWriteGuard, Shared,
 };
 use erg_common::spawn::spawn_new_thread;
 use erg_common::{fn_name, normalize_path};
@@ -26,18 +27,24 @@ use erg_compiler::module::{SharedCompilerResource, SharedModuleGraph, SharedModu
 use erg_compiler::ty::HasType;
 
 use lsp_types::request::{
-    CodeActionRequest, CodeActionResolveRequest, CodeLensRequest, Completion, ExecuteCommand,
-    GotoDefinition, HoverRequest, InlayHintRequest, InlayHintResolveRequest, References, Rename,
-    Request, ResolveCompletionItem, SemanticTokensFullRequest, SignatureHelpRequest,
-    WillRenameFiles,
+    CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+    CodeActionRequest, CodeActionResolveRequest, CodeLensRequest, Completion,
+    DocumentHighlightRequest, DocumentSymbolRequest, ExecuteCommand, FoldingRangeRequest,
+    Formatting, GotoDefinition, GotoImplementation, GotoTypeDefinition, HoverRequest,
+    InlayHintRequest, InlayHintResolveRequest, OnTypeFormatting, RangeFormatting, References,
+    Rename, Request, ResolveCompletionItem, SelectionRangeRequest, SemanticTokensFullRequest,
+    SignatureHelpRequest, WillRenameFiles, WorkspaceSymbol,
 };
 use lsp_types::{
-    CodeActionKind, CodeActionOptions, CodeActionProviderCapability, CodeLensOptions,
-    CompletionOptions, ConfigurationItem, ConfigurationParams, DidChangeTextDocumentParams,
-    DidOpenTextDocumentParams, ExecuteCommandOptions, HoverProviderCapability, InitializeParams,
-    InitializeResult, InlayHintOptions, InlayHintServerCapabilities, OneOf, Position,
-    SemanticTokenType, SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions,
-    SemanticTokensServerCapabilities, ServerCapabilities, SignatureHelpOptions,
+    CallHierarchyServerCapability, CodeActionKind, CodeActionOptions, CodeActionProviderCapability,
+    CodeLensOptions, CompletionOptions, ConfigurationItem, ConfigurationParams,
+    DidChangeConfigurationParams, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+    DidOpenTextDocumentParams, DocumentOnTypeFormattingOptions, ExecuteCommandOptions,
+    FoldingRangeProviderCapability, HoverProviderCapability, ImplementationProviderCapability,
+    InitializeParams, InitializeResult, InlayHintOptions, InlayHintServerCapabilities, OneOf,
+    Position, SelectionRangeProviderCapability, SemanticTokenType, SemanticTokensFullOptions,
+    SemanticTokensLegend, SemanticTokensOptions, SemanticTokensServerCapabilities,
+    ServerCapabilities, SignatureHelpOptions, TypeDefinitionProviderCapability, Url,
     WorkDoneProgressOptions,
 };
 
@@ -45,15 +52,26 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde_json::Value;
 
+use crate::cancel::{PendingChecks, PendingRequests, REQUEST_CANCELLED};
 use crate::channels::{SendChannels, Sendable, WorkerMessage};
 use crate::completion::CompletionCache;
+use crate::diagnostics::{
+    module_mode, DiagnosticsCache, DocumentDiagnosticRequest, WorkspaceDiagnosticRequest,
+};
 use crate::file_cache::FileCache;
 use crate::hir_visitor::HIRVisitor;
 use crate::message::{ErrorMessage, LSPResult, LogMessage, ShowMessage};
+use crate::progress::ProgressRequests;
+use crate::settings::SettingsCache;
+use crate::type_hierarchy::{TypeHierarchyPrepare, TypeHierarchySubtypes, TypeHierarchySupertypes};
 use crate::util::{self, NormalizedUrl};
 
 pub const HEALTH_CHECKER_ID: i64 = 10000;
 pub const ASK_AUTO_SAVE_ID: i64 = 10001;
+pub const REGISTER_FILE_WATCHER_ID: i64 = 10002;
+pub const REFRESH_DIAGNOSTICS_ID: i64 = 10003;
+pub const FETCH_SETTINGS_ID: i64 = 10004;
+pub const REGISTER_SETTINGS_ID: i64 = 10005;
 
 pub type ELSResult<T> = Result<T, Box<dyn std::error::Error>>;
 
@@ -80,9 +98,11 @@ pub enum DefaultFeatures {
     DeepCompletion,
 }
 
-impl From<&str> for DefaultFeatures {
-    fn from(s: &str) -> Self {
-        match s {
+impl TryFrom<&str> for DefaultFeatures {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let feature = match s {
             "codeaction" | "codeAction" | "code-action" => DefaultFeatures::CodeAction,
             "codelens" | "codeLens" | "code-lens" => DefaultFeatures::CodeLens,
             "completion" => DefaultFeatures::Completion,
@@ -96,7 +116,7 @@ impl From<&str> for DefaultFeatures {
             "findreferences" | "findReferences" | "find-references" => {
                 DefaultFeatures::FindReferences
             }
-            "gotodefinition" | "gotoDefinition" | "goto-completion" => {
+            "gotodefinition" | "gotoDefinition" | "goto-definition" | "goto-completion" => {
                 DefaultFeatures::GotoDefinition
             }
             "signaturehelp" | "signatureHelp" | "signature-help" => DefaultFeatures::SignatureHelp,
@@ -106,7 +126,31 @@ impl From<&str> for DefaultFeatures {
             "deepcompletion" | "deepCompletion" | "deep-completion" => {
                 DefaultFeatures::DeepCompletion
             }
-            _ => panic!("unknown feature: {s}"),
+            _ => return Err(format!("unknown feature: {s}")),
+        };
+        Ok(feature)
+    }
+}
+
+impl DefaultFeatures {
+    /// The feature that provides the response to the request (`None` if it cannot be disabled)
+    pub(crate) fn of_request(method: &str) -> Option<Self> {
+        match method {
+            CodeActionRequest::METHOD | CodeActionResolveRequest::METHOD => {
+                Some(DefaultFeatures::CodeAction)
+            }
+            CodeLensRequest::METHOD => Some(DefaultFeatures::CodeLens),
+            Completion::METHOD | ResolveCompletionItem::METHOD => Some(DefaultFeatures::Completion),
+            References::METHOD => Some(DefaultFeatures::FindReferences),
+            GotoDefinition::METHOD => Some(DefaultFeatures::GotoDefinition),
+            HoverRequest::METHOD => Some(DefaultFeatures::Hover),
+            InlayHintRequest::METHOD | InlayHintResolveRequest::METHOD => {
+                Some(DefaultFeatures::InlayHint)
+            }
+            Rename::METHOD => Some(DefaultFeatures::Rename),
+            SemanticTokensFullRequest::METHOD => Some(DefaultFeatures::SemanticTokens),
+            SignatureHelpRequest::METHOD => Some(DefaultFeatures::SignatureHelp),
+            _ => None,
         }
     }
 }
@@ -116,11 +160,13 @@ pub enum OptionalFeatures {
     CheckOnType,
 }
 
-impl From<&str> for OptionalFeatures {
-    fn from(s: &str) -> Self {
+impl TryFrom<&str> for OptionalFeatures {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
         match s {
-            "checkontype" | "checkOnType" | "check-on-type" => OptionalFeatures::CheckOnType,
-            _ => panic!("unknown feature: {s}"),
+            "checkontype" | "checkOnType" | "check-on-type" => Ok(OptionalFeatures::CheckOnType),
+            _ => Err(format!("unknown feature: {s}")),
         }
     }
 }
@@ -153,6 +199,73 @@ fn read_exact(len: usize) -> io::Result<Vec<u8>> {
     Ok(buf)
 }
 
+/// Copied and modified from RLS, https://github.com/rust-lang/rls/blob/master/rls/src/server/io.rs
+fn read_message() -> Result<Value, io::Error> {
+    // Read in the "Content-Length: xx" part.
+    let mut size: Option<usize> = None;
+    loop {
+        let buffer = read_line()?;
+
+        // End of input.
+        if buffer.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "EOF encountered in the middle of reading LSP headers",
+            ));
+        }
+
+        // Header section is finished, break from the loop.
+        if buffer == "\r\n" {
+            break;
+        }
+
+        let res: Vec<&str> = buffer.split(' ').collect();
+
+        // Make sure header is valid.
+        if res.len() != 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Header '{buffer}' is malformed"),
+            ));
+        }
+        let header_name = res[0].to_lowercase();
+        let header_value = res[1].trim();
+
+        match header_name.as_ref() {
+            "content-length:" => {
+                size = Some(header_value.parse::<usize>().map_err(|_e| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Couldn't read size")
+                })?);
+            }
+            "content-type:" => {
+                if header_value != "utf8" && header_value != "utf-8" {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Content type '{header_value}' is invalid"),
+                    ));
+                }
+            }
+            // Ignore unknown headers (specification doesn't say what to do in this case).
+            _ => (),
+        }
+    }
+    let size = match size {
+        Some(size) => size,
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Message is missing 'content-length' header",
+            ));
+        }
+    };
+
+    let content = read_exact(size)?;
+
+    let s =
+        String::from_utf8(content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Value::from_str(&s)?)
+}
+
 pub(crate) fn send<T: ?Sized + Serialize>(message: &T) -> ELSResult<()> {
     send_stdout(message)
 }
@@ -318,13 +431,25 @@ pub struct Server<Checker: BuildRunnable = HIRBuilder, Parser: Parsable = Simple
     pub(crate) client_answers: Shared<Dict<i64, Value>>,
     pub(crate) disabled_features: Vec<DefaultFeatures>,
     pub(crate) opt_features: Vec<OptionalFeatures>,
+    /// the settings of each workspace folder given by the client (`workspace/configuration`)
+    pub(crate) settings: SettingsCache,
     pub(crate) file_cache: FileCache,
     pub(crate) comp_cache: CompletionCache,
     // TODO: remove modules, analysis_result, and add `shared: SharedCompilerResource`
     pub(crate) modules: ModuleCache,
     pub(crate) analysis_result: AnalysisResultCache,
+    pub(crate) diagnostics: DiagnosticsCache,
+    /// Does the client pull the diagnostics (LSP 3.17)? If so, they are not published.
+    pub(crate) pull_diagnostics: bool,
+    /// Can the client be asked to pull the diagnostics again (`workspace/diagnostic/refresh`)?
+    pub(crate) diagnostics_refresh: bool,
     pub(crate) current_sig: Option<Expr>,
     pub(crate) channels: Option<SendChannels>,
+    pub(crate) pending_requests: PendingRequests,
+    pub(crate) pending_checks: PendingChecks,
+    pub(crate) progress_requests: ProgressRequests,
+    /// the token of the request being processed (each worker has its own clone of the server)
+    pub(crate) cancel_token: CancellationToken,
     pub(crate) _parser: std::marker::PhantomData<fn() -> Parser>,
     pub(crate) _checker: std::marker::PhantomData<fn() -> Checker>,
 }
@@ -339,12 +464,20 @@ impl<C: BuildRunnable, P: Parsable> Clone for Server<C, P> {
             client_answers: self.client_answers.clone(),
             disabled_features: self.disabled_features.clone(),
             opt_features: self.opt_features.clone(),
+            settings: self.settings.clone(),
             file_cache: self.file_cache.clone(),
             comp_cache: self.comp_cache.clone(),
             modules: self.modules.clone(),
             analysis_result: self.analysis_result.clone(),
+            diagnostics: self.diagnostics.clone(),
+            pull_diagnostics: self.pull_diagnostics,
+            diagnostics_refresh: self.diagnostics_refresh,
             current_sig: self.current_sig.clone(),
             channels: self.channels.clone(),
+            pending_requests: self.pending_requests.clone(),
+            pending_checks: self.pending_checks.clone(),
+            progress_requests: self.progress_requests.clone(),
+            cancel_token: self.cancel_token.clone(),
             _parser: std::marker::PhantomData,
             _checker: std::marker::PhantomData,
         }
@@ -362,19 +495,28 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             client_answers: Shared::new(Dict::new()),
             disabled_features: vec![],
             opt_features: vec![],
+            settings: SettingsCache::new(),
             file_cache: FileCache::new(),
             modules: ModuleCache::new(),
             analysis_result: AnalysisResultCache::new(),
+            diagnostics: DiagnosticsCache::new(),
+            pull_diagnostics: false,
+            diagnostics_refresh: false,
             current_sig: None,
             channels: None,
+            pending_requests: PendingRequests::new(),
+            pending_checks: PendingChecks::new(),
+            progress_requests: ProgressRequests::new(),
+            cancel_token: CancellationToken::new(),
             _parser: std::marker::PhantomData,
             _checker: std::marker::PhantomData,
         }
     }
 
     pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let receiver = self.start_message_reader();
         loop {
-            let msg = self.read_message()?;
+            let msg = receiver.recv()??;
             if let Err(err) = self.dispatch(msg) {
                 send_error_info(format!("err: {err:?}"))?;
             }
@@ -382,6 +524,41 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         // Ok(())
     }
 
+    /// Messages are read in another thread, so that cancellations take effect even while the main thread is busy (e.g. checking a file).
+    fn start_message_reader(&self) -> mpsc::Receiver<io::Result<Value>> {
+        let (sender, receiver) = mpsc::channel();
+        let pending_requests = self.pending_requests.clone();
+        let progress_requests = self.progress_requests.clone();
+        spawn_new_thread(
+            move || loop {
+                let msg = read_message();
+                if let Ok(msg) = &msg {
+                    if progress_requests.resolve(msg) {
+                        continue;
+                    }
+                    if msg.get("method").and_then(|m| m.as_str()) == Some("$/cancelRequest") {
+                        if let Some(id) = msg["params"]["id"].as_i64() {
+                            pending_requests.cancel(id);
+                        }
+                        continue;
+                    }
+                }
+                let is_err = msg.is_err();
+                if sender.send(msg).is_err() || is_err {
+                    break;
+                }
+            },
+            "read_message",
+        );
+        receiver
+    }
+
+    /// Has the request being processed been cancelled?
+    /// Long-running handlers should check this and return early.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel_token.is_cancelled()
+    }
+
     pub const fn mode(&self) -> &str {
         if PYTHON_MODE {
             "pylyzer"
@@ -402,16 +579,40 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         while let Some(&arg) = args.next() {
             if arg == "--disable" {
                 if let Some(&feature) = args.next() {
-                    self.disabled_features.push(DefaultFeatures::from(feature));
+                    match DefaultFeatures::try_from(feature) {
+                        Ok(feature) => self.disabled_features.push(feature),
+                        Err(err) => send_log(err)?,
+                    }
                 }
             } else if arg == "--enable" {
                 if let Some(&feature) = args.next() {
-                    self.opt_features.push(OptionalFeatures::from(feature));
+                    match OptionalFeatures::try_from(feature) {
+                        Ok(feature) => self.opt_features.push(feature),
+                        Err(err) => send_log(err)?,
+                    }
                 }
             }
         }
+        // lsp-types 0.93 does not have the client capabilities of pull diagnostics (LSP 3.17)
+        let client_capas = &msg["params"]["capabilities"];
+        self.pull_diagnostics = client_capas["textDocument"]["diagnostic"].is_object()
+            && !self
+                .disabled_features
+                .contains(&DefaultFeatures::Diagnostics);
+        self.diagnostics_refresh = client_capas["workspace"]["diagnostics"]["refreshSupport"]
+            .as_bool()
+            .unwrap_or(false);
         let mut result = InitializeResult::default();
         result.capabilities = self.init_capabilities();
+        let mut result = serde_json::to_value(result)?;
+        // lsp-types 0.93 does not have `typeHierarchyProvider` and `diagnosticProvider` (LSP 3.17)
+        result["capabilities"]["typeHierarchyProvider"] = json!(true);
+        if self.pull_diagnostics {
+            result["capabilities"]["diagnosticProvider"] = json!({
+                "interFileDependencies": true,
+                "workspaceDiagnostics": true,
+            });
+        }
         self.init_services();
         send(&json!({
             "jsonrpc": "2.0",
@@ -431,6 +632,13 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         capabilities.rename_provider = Some(OneOf::Left(true));
         capabilities.references_provider = Some(OneOf::Left(true));
         capabilities.definition_provider = Some(OneOf::Left(true));
+        capabilities.type_definition_provider =
+            Some(TypeDefinitionProviderCapability::Simple(true));
+        capabilities.implementation_provider = Some(ImplementationProviderCapability::Simple(true));
+        capabilities.document_highlight_provider = Some(OneOf::Left(true));
+        capabilities.folding_range_provider = Some(FoldingRangeProviderCapability::Simple(true));
+        capabilities.selection_range_provider =
+            Some(SelectionRangeProviderCapability::Simple(true));
         capabilities.hover_provider = self
             .disabled_features
             .contains(&DefaultFeatures::Hover)
@@ -505,6 +713,15 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         capabilities.code_lens_provider = Some(CodeLensOptions {
             resolve_provider: Some(false),
         });
+        capabilities.document_symbol_provider = Some(OneOf::Left(true));
+        capabilities.workspace_symbol_provider = Some(OneOf::Left(true));
+        capabilities.document_formatting_provider = Some(OneOf::Left(true));
+        capabilities.document_range_formatting_provider = Some(OneOf::Left(true));
+        capabilities.document_on_type_formatting_provider = Some(DocumentOnTypeFormattingOptions {
+            first_trigger_character: "\n".to_string(),
+            more_trigger_character: None,
+        });
+        capabilities.call_hierarchy_provider = Some(CallHierarchyServerCapability::Simple(true));
         capabilities
     }
 
@@ -564,6 +781,75 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             receivers.execute_command,
             Self::handle_execute_command,
         );
+        self.start_service::<DocumentSymbolRequest>(
+            receivers.document_symbol,
+            Self::handle_document_symbol,
+        );
+        self.start_service::<WorkspaceSymbol>(
+            receivers.workspace_symbol,
+            Self::handle_workspace_symbol,
+        );
+        self.start_service::<Formatting>(receivers.formatting, Self::handle_formatting);
+        self.start_service::<RangeFormatting>(
+            receivers.range_formatting,
+            Self::handle_range_formatting,
+        );
+        self.start_service::<OnTypeFormatting>(
+            receivers.on_type_formatting,
+            Self::handle_on_type_formatting,
+        );
+        self.start_service::<CallHierarchyPrepare>(
+            receivers.call_hierarchy_prepare,
+            Self::handle_call_hierarchy_prepare,
+        );
+        self.start_service::<CallHierarchyIncomingCalls>(
+            receivers.call_hierarchy_incoming,
+            Self::handle_call_hierarchy_incoming,
+        );
+        self.start_service::<CallHierarchyOutgoingCalls>(
+            receivers.call_hierarchy_outgoing,
+            Self::handle_call_hierarchy_outgoing,
+        );
+        self.start_service::<TypeHierarchyPrepare>(
+            receivers.type_hierarchy_prepare,
+            Self::handle_type_hierarchy_prepare,
+        );
+        self.start_service::<TypeHierarchySupertypes>(
+            receivers.type_hierarchy_supertypes,
+            Self::handle_type_hierarchy_supertypes,
+        );
+        self.start_service::<TypeHierarchySubtypes>(
+            receivers.type_hierarchy_subtypes,
+            Self::handle_type_hierarchy_subtypes,
+        );
+        self.start_service::<GotoTypeDefinition>(
+            receivers.goto_type_definition,
+            Self::handle_goto_type_definition,
+        );
+        self.start_service::<GotoImplementation>(
+            receivers.goto_implementation,
+            Self::handle_goto_implementation,
+        );
+        self.start_service::<DocumentHighlightRequest>(
+            receivers.document_highlight,
+            Self::handle_document_highlight,
+        );
+        self.start_service::<FoldingRangeRequest>(
+            receivers.folding_range,
+            Self::handle_folding_range,
+        );
+        self.start_service::<SelectionRangeRequest>(
+            receivers.selection_range,
+            Self::handle_selection_range,
+        );
+        self.start_service::<DocumentDiagnosticRequest>(
+            receivers.document_diagnostic,
+            Self::handle_document_diagnostic,
+        );
+        self.start_service::<WorkspaceDiagnosticRequest>(
+            receivers.workspace_diagnostic,
+            Self::handle_workspace_diagnostic,
+        );
         self.start_client_health_checker(receivers.health_check);
     }
 
@@ -597,73 +883,6 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         self.start_language_services();
     }
 
-    /// Copied and modified from RLS, https://github.com/rust-lang/rls/blob/master/rls/src/server/io.rs
-    fn read_message(&self) -> Result<Value, io::Error> {
-        // Read in the "Content-Length: xx" part.
-        let mut size: Option<usize> = None;
-        loop {
-            let buffer = read_line()?;
-
-            // End of input.
-            if buffer.is_empty() {
-                return Err(io::Error::new(
-                    io::ErrorKind::UnexpectedEof,
-                    "EOF encountered in the middle of reading LSP headers",
-                ));
-            }
-
-            // Header section is finished, break from the loop.
-            if buffer == "\r\n" {
-                break;
-            }
-
-            let res: Vec<&str> = buffer.split(' ').collect();
-
-            // Make sure header is valid.
-            if res.len() != 2 {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Header '{buffer}' is malformed"),
-                ));
-            }
-            let header_name = res[0].to_lowercase();
-            let header_value = res[1].trim();
-
-            match header_name.as_ref() {
-                "content-length:" => {
-                    size = Some(header_value.parse::<usize>().map_err(|_e| {
-                        io::Error::new(io::ErrorKind::InvalidData, "Couldn't read size")
-                    })?);
-                }
-                "content-type:" => {
-                    if header_value != "utf8" && header_value != "utf-8" {
-                        return Err(io::Error::new(
-                            io::ErrorKind::InvalidData,
-                            format!("Content type '{header_value}' is invalid"),
-                        ));
-                    }
-                }
-                // Ignore unknown headers (specification doesn't say what to do in this case).
-                _ => (),
-            }
-        }
-        let size = match size {
-            Some(size) => size,
-            None => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "Message is missing 'content-length' header",
-                ));
-            }
-        };
-
-        let content = read_exact(size)?;
-
-        let s = String::from_utf8(content)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        Ok(Value::from_str(&s)?)
-    }
-
     fn dispatch(&mut self, msg: Value) -> ELSResult<()> {
         match (
             msg.get("id").and_then(|i| i.as_i64()),
@@ -683,6 +902,12 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         Server<Checker, Parser>: Sendable<R>,
     {
         let params = R::Params::deserialize(&msg["params"])?;
+        let uri = msg["params"]["textDocument"]["uri"]
+            .as_str()
+            .and_then(|uri| NormalizedUrl::parse(uri).ok());
+        let version = uri.as_ref().and_then(|uri| self.file_cache.get_ver(uri));
+        self.pending_requests
+            .register(id, R::METHOD, uri, version, &msg["params"]);
         self.send(id, params);
         Ok(())
     }
@@ -702,7 +927,23 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 let msg = receiver.recv().unwrap();
                 match msg {
                     WorkerMessage::Request(id, params) => {
-                        let _ = send(&LSPResult::new(id, handler(&mut _self, params).unwrap()));
+                        // cancelled by `$/cancelRequest` or superseded by a newer request
+                        let token = _self.pending_requests.get_token(id).unwrap_or_default();
+                        let result = if token.is_cancelled() {
+                            None
+                        } else {
+                            _self.cancel_token = token.clone();
+                            Some(handler(&mut _self, params).unwrap())
+                        };
+                        _self.pending_requests.remove(id);
+                        match result {
+                            Some(result) if !token.is_cancelled() => {
+                                let _ = send(&LSPResult::new(id, result));
+                            }
+                            _ => {
+                                let _ = send_error(Some(id), REQUEST_CANCELLED, "cancelled");
+                            }
+                        }
                     }
                     WorkerMessage::Kill => {
                         break;
@@ -714,6 +955,19 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
     }
 
     fn handle_request(&mut self, msg: &Value, id: i64, method: &str) -> ELSResult<()> {
+        // disabled by the settings of the workspace folder
+        if let Some(feature) = DefaultFeatures::of_request(method) {
+            let uri = msg["params"]["textDocument"]["uri"]
+                .as_str()
+                .and_then(|uri| Url::parse(uri).ok());
+            if self.is_disabled(feature, uri.as_ref()) {
+                return send(&json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": Value::Null,
+                }));
+            }
+        }
         match method {
             "initialize" => self.init(msg, id),
             "shutdown" => self.shutdown(id),
@@ -736,13 +990,46 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             CodeLensRequest::METHOD => self.parse_send::<CodeLensRequest>(id, msg),
             WillRenameFiles::METHOD => self.parse_send::<WillRenameFiles>(id, msg),
             ExecuteCommand::METHOD => self.parse_send::<ExecuteCommand>(id, msg),
+            DocumentSymbolRequest::METHOD => self.parse_send::<DocumentSymbolRequest>(id, msg),
+            WorkspaceSymbol::METHOD => self.parse_send::<WorkspaceSymbol>(id, msg),
+            Formatting::METHOD => self.parse_send::<Formatting>(id, msg),
+            RangeFormatting::METHOD => self.parse_send::<RangeFormatting>(id, msg),
+            OnTypeFormatting::METHOD => self.parse_send::<OnTypeFormatting>(id, msg),
+            CallHierarchyPrepare::METHOD => self.parse_send::<CallHierarchyPrepare>(id, msg),
+            CallHierarchyIncomingCalls::METHOD => {
+                self.parse_send::<CallHierarchyIncomingCalls>(id, msg)
+            }
+            CallHierarchyOutgoingCalls::METHOD => {
+                self.parse_send::<CallHierarchyOutgoingCalls>(id, msg)
+            }
+            TypeHierarchyPrepare::METHOD => self.parse_send::<TypeHierarchyPrepare>(id, msg),
+            TypeHierarchySupertypes::METHOD => self.parse_send::<TypeHierarchySupertypes>(id, msg),
+            TypeHierarchySubtypes::METHOD => self.parse_send::<TypeHierarchySubtypes>(id, msg),
+            GotoTypeDefinition::METHOD => self.parse_send::<GotoTypeDefinition>(id, msg),
+            GotoImplementation::METHOD => self.parse_send::<GotoImplementation>(id, msg),
+            DocumentHighlightRequest::METHOD => {
+                self.parse_send::<DocumentHighlightRequest>(id, msg)
+            }
+            FoldingRangeRequest::METHOD => self.parse_send::<FoldingRangeRequest>(id, msg),
+            SelectionRangeRequest::METHOD => self.parse_send::<SelectionRangeRequest>(id, msg),
+            DocumentDiagnosticRequest::METHOD => {
+                self.parse_send::<DocumentDiagnosticRequest>(id, msg)
+            }
+            WorkspaceDiagnosticRequest::METHOD => {
+                self.parse_send::<WorkspaceDiagnosticRequest>(id, msg)
+            }
             other => send_error(Some(id), -32600, format!("{other} is not supported")),
         }
     }
 
     fn handle_notification(&mut self, msg: &Value, method: &str) -> ELSResult<()> {
         match method {
-            "initialized" => send_log("successfully bound"),
+            "initialized" => {
+                send_log("successfully bound")?;
+                self.register_file_watcher()?;
+                self.register_settings_change()?;
+                self.fetch_settings()
+            }
             "exit" => self.exit(),
             "textDocument/didOpen" => {
                 let params = DidOpenTextDocumentParams::deserialize(msg["params"].clone())?;
@@ -758,8 +1045,12 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                     NormalizedUrl::parse(msg["params"]["textDocument"]["uri"].as_str().unwrap())?;
                 send_log(format!("{method}: {uri}"))?;
                 let code = self.file_cache.get_entire_code(&uri)?;
+                // the dependents are removed from the module graph by `clear_cache`
+                let dependents = self.transitive_dependents_of(&uri);
                 self.clear_cache(&uri);
-                self.check_file(uri, code)
+                self.check_module(uri.clone(), code, module_mode(&uri))?;
+                self.check_dependents(&uri, dependents)?;
+                self.refresh_diagnostics()
             }
             "textDocument/didChange" => {
                 let params = DidChangeTextDocumentParams::deserialize(msg["params"].clone())?;
@@ -774,9 +1065,24 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                     // TODO: reset mutable dependent types
                     self.quick_check_file(uri)?;
                 }
+                let uri = NormalizedUrl::new(params.text_document.uri.clone());
                 self.file_cache.incremental_update(params);
+                if self.is_enabled(OptionalFeatures::CheckOnType, Some(uri.as_ref())) {
+                    let code = self.file_cache.get_entire_code(&uri)?;
+                    self.check_file(uri, code)?;
+                }
                 Ok(())
             }
+            "workspace/didChangeConfiguration" => {
+                let params = DidChangeConfigurationParams::deserialize(msg["params"].clone())?;
+                send_log(format!("{method}: {params:?}"))?;
+                self.handle_did_change_configuration(params)
+            }
+            "workspace/didChangeWatchedFiles" => {
+                let params = DidChangeWatchedFilesParams::deserialize(msg["params"].clone())?;
+                send_log(format!("{method}: {params:?}"))?;
+                self.handle_watched_files_change(params)
+            }
             _ => send_log(format!("received notification: {method}")),
         }
     }
@@ -790,6 +1096,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                     .health_check
                     .send(WorkerMessage::Request(0, ()))?;
             }
+            FETCH_SETTINGS_ID => self.handle_settings_response(msg)?,
             _ => {
                 _log!("msg: {msg}");
                 if msg.get("error").is_none() {
@@ -803,13 +1110,14 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
     /// TODO: Reuse cache.
     /// Because of the difficulty of caching "transitional types" such as assert casting and mutable dependent types,
     /// the cache is deleted after each analysis.
-    pub(crate) fn get_checker(&self, path: PathBuf) -> Checker {
+    pub(crate) fn get_checker(&self, path: PathBuf, cancel_token: CancellationToken) -> Checker {
         if let Some(shared) = self.get_shared() {
-            let shared = shared.clone();
+            let mut shared = shared.clone();
+            shared.cancel_token = cancel_token;
             shared.clear(&path);
-            Checker::inherit(self.cfg.inherit(path), shared)
+            Checker::inherit(self.cfg_of(&path).inherit(path), shared)
         } else {
-            Checker::new(self.cfg.inherit(path))
+            Checker::new(self.cfg_of(&path).inherit(path))
         }
     }
 
@@ -833,6 +1141,16 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             .map(|hir| HIRVisitor::new(hir, &self.file_cache, uri.clone()))
     }
 
+    /// Unlike `get_visitor`, this also finds the modules that are not opened (e.g. imported modules).
+    pub(crate) fn get_cached_visitor(&self, uri: &NormalizedUrl) -> Option<HIRVisitor<'_>> {
+        if let Some(visitor) = self.get_visitor(uri) {
+            return Some(visitor);
+        }
+        let entry = self.get_shared()?.mod_cache.get(&util::uri_to_path(uri))?;
+        let hir = MappedRwLockReadGuard::try_map(entry, |entry| entry.hir.as_ref()).ok()?;
+        Some(HIRVisitor::new(hir, &self.file_cache, uri.clone()))
+    }
+
     pub(crate) fn get_local_ctx(&self, uri: &NormalizedUrl, pos: Position) -> Vec<&Context> {
         let mut ctxs = vec![];
         if let Some(mod_ctx) = &self.modules.get(uri) {
@@ -856,25 +1174,6 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         ctxs
     }
 
-    pub(crate) fn get_neighbor_ctxs(&self, uri: &NormalizedUrl) -> Vec<&Context> {
-        let mut ctxs = vec![];
-        if let Ok(dir) = uri
-            .to_file_path()
-            .and_then(|p| p.parent().unwrap().read_dir().map_err(|_| ()))
-        {
-            for neighbor in dir {
-                let Ok(neighbor) = neighbor else {
-                    continue;
-                };
-                let uri = NormalizedUrl::from_file_path(neighbor.path()).unwrap();
-                if let Some(mod_ctx) = &self.modules.get(&uri) {
-                    ctxs.push(&mod_ctx.context);
-                }
-            }
-        }
-        ctxs
-    }
-
     pub(crate) fn get_receiver_ctxs(
         &self,
         uri: &NormalizedUrl,
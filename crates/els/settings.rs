@@ -0,0 +1,334 @@
+use std::path::{Path, PathBuf};
+
+use erg_common::config::ErgConfig;
+use erg_common::dict::Dict;
+use erg_common::normalize_path;
+use erg_common::python_util::{
+    opt_detect_magic_number_no_shell, opt_get_python_version_no_shell, PythonVersion,
+};
+use erg_common::shared::Shared;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+
+use lsp_types::{
+    ConfigurationItem, ConfigurationParams, DidChangeConfigurationParams, Registration,
+    RegistrationParams, Url,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::diagnostics::module_mode;
+use crate::server::{
+    send, send_log, DefaultFeatures, ELSResult, OptionalFeatures, Server, FETCH_SETTINGS_ID,
+    REGISTER_SETTINGS_ID,
+};
+
+/// The settings given by the client (the `erg` (or `pylyzer`) section of the configuration).
+///
+/// e.g.
+/// ```json
+/// {
+///     "disable": ["inlayHint"],
+///     "enable": ["checkOnType"],
+///     "pythonCommand": "python3.11",
+///     "targetVersion": "3.11",
+///     "optLevel": 1,
+///     "searchPaths": ["lib"]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct ELSSettings {
+    pub disable: Vec<String>,
+    pub enable: Vec<String>,
+    pub python_command: Option<String>,
+    pub target_version: Option<String>,
+    pub opt_level: Option<u8>,
+    /// relative paths are resolved from the workspace folder
+    pub search_paths: Vec<PathBuf>,
+}
+
+/// The settings applied to the files in a workspace folder
+#[derive(Debug, Clone)]
+pub(crate) struct FolderSettings {
+    raw: ELSSettings,
+    cfg: ErgConfig,
+    disabled_features: Vec<DefaultFeatures>,
+    opt_features: Vec<OptionalFeatures>,
+}
+
+/// The settings of each workspace folder.
+/// The settings not bound to any folder are stored with the empty path, which all paths start with.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SettingsCache(Shared<Dict<PathBuf, FolderSettings>>);
+
+impl SettingsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn find<'a>(
+        settings: &'a Dict<PathBuf, FolderSettings>,
+        path: &Path,
+    ) -> Option<&'a FolderSettings> {
+        settings
+            .iter()
+            .filter(|(folder, _)| path.starts_with(folder))
+            .max_by_key(|(folder, _)| folder.components().count())
+            .map(|(_, settings)| settings)
+    }
+
+    /// Returns `true` if the settings of `folder` have been changed.
+    pub fn insert(&self, folder: PathBuf, settings: FolderSettings) -> bool {
+        let changed = !matches!(
+            self.0.borrow().get(&folder),
+            Some(old) if old.raw == settings.raw
+        );
+        self.0.borrow_mut().insert(folder, settings);
+        changed
+    }
+
+    pub fn cfg(&self, path: &Path) -> Option<ErgConfig> {
+        Self::find(&self.0.borrow(), path).map(|settings| settings.cfg.clone())
+    }
+
+    pub fn is_disabled(&self, path: &Path, feature: DefaultFeatures) -> bool {
+        Self::find(&self.0.borrow(), path)
+            .is_some_and(|settings| settings.disabled_features.contains(&feature))
+    }
+
+    pub fn is_enabled(&self, path: &Path, feature: OptionalFeatures) -> bool {
+        Self::find(&self.0.borrow(), path)
+            .is_some_and(|settings| settings.opt_features.contains(&feature))
+    }
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    /// The path of the file/folder which a request or notification is for (the empty path if unknown)
+    fn path_of(uri: Option<&Url>) -> PathBuf {
+        uri.and_then(|uri| uri.to_file_path().ok())
+            .map(normalize_path)
+            .unwrap_or_default()
+    }
+
+    /// `feature` is disabled by the command line arguments or the settings of the workspace folder containing `uri`
+    pub(crate) fn is_disabled(&self, feature: DefaultFeatures, uri: Option<&Url>) -> bool {
+        self.disabled_features.contains(&feature)
+            || self.settings.is_disabled(&Self::path_of(uri), feature)
+    }
+
+    /// `feature` is enabled by the command line arguments or the settings of the workspace folder containing `uri`
+    pub(crate) fn is_enabled(&self, feature: OptionalFeatures, uri: Option<&Url>) -> bool {
+        self.opt_features.contains(&feature)
+            || self.settings.is_enabled(&Self::path_of(uri), feature)
+    }
+
+    /// The configuration used to check `path` (reflects the settings of the workspace folder containing it)
+    pub(crate) fn cfg_of(&self, path: &Path) -> ErgConfig {
+        self.settings.cfg(path).unwrap_or_else(|| self.cfg.clone())
+    }
+
+    fn supports_configuration(&self) -> bool {
+        self.init_params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.configuration)
+            .unwrap_or(false)
+    }
+
+    #[allow(deprecated)]
+    fn workspace_folders(&self) -> Vec<Url> {
+        if let Some(folders) = self.init_params.workspace_folders.as_ref() {
+            folders.iter().map(|folder| folder.uri.clone()).collect()
+        } else {
+            self.init_params.root_uri.iter().cloned().collect()
+        }
+    }
+
+    /// Asks the client to notify the changes of the settings (`workspace/didChangeConfiguration`).
+    pub(crate) fn register_settings_change(&self) -> ELSResult<()> {
+        let supported = self
+            .init_params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_configuration.as_ref())
+            .and_then(|caps| caps.dynamic_registration)
+            .unwrap_or(false);
+        if !supported {
+            return Ok(());
+        }
+        let params = RegistrationParams {
+            registrations: vec![Registration {
+                id: String::from("els-settings"),
+                method: String::from("workspace/didChangeConfiguration"),
+                register_options: Some(json!({ "section": self.mode() })),
+            }],
+        };
+        send(&json!({
+            "jsonrpc": "2.0",
+            "id": REGISTER_SETTINGS_ID,
+            "method": "client/registerCapability",
+            "params": params,
+        }))
+    }
+
+    /// Requests the settings not bound to any folder and those of each workspace folder (`workspace/configuration`).
+    /// The response is handled by `handle_settings_response`.
+    pub(crate) fn fetch_settings(&self) -> ELSResult<()> {
+        if !self.supports_configuration() {
+            return Ok(());
+        }
+        let scopes = [None]
+            .into_iter()
+            .chain(self.workspace_folders().into_iter().map(Some));
+        let params = ConfigurationParams {
+            items: scopes
+                .map(|scope_uri| ConfigurationItem {
+                    scope_uri,
+                    section: Some(self.mode().to_string()),
+                })
+                .collect(),
+        };
+        send(&json!({
+            "jsonrpc": "2.0",
+            "id": FETCH_SETTINGS_ID,
+            "method": "workspace/configuration",
+            "params": params,
+        }))
+    }
+
+    pub(crate) fn handle_settings_response(&mut self, msg: &Value) -> ELSResult<()> {
+        let Some(results) = msg["result"].as_array() else {
+            return Ok(());
+        };
+        // in the same order as the request
+        let folders = [None]
+            .into_iter()
+            .chain(self.workspace_folders().into_iter().map(Some));
+        let mut changed = false;
+        for (folder, value) in folders.zip(results) {
+            changed |= self.update_settings(folder.as_ref(), value)?;
+        }
+        if changed {
+            self.recheck_files()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn handle_did_change_configuration(
+        &mut self,
+        params: DidChangeConfigurationParams,
+    ) -> ELSResult<()> {
+        // the settings pushed by the client are not bound to any folder, so pull them if possible
+        if self.supports_configuration() {
+            return self.fetch_settings();
+        }
+        let value = params
+            .settings
+            .get(self.mode())
+            .cloned()
+            .unwrap_or(params.settings);
+        if self.update_settings(None, &value)? {
+            self.recheck_files()?;
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the settings of `folder` have been changed.
+    fn update_settings(&self, folder: Option<&Url>, value: &Value) -> ELSResult<bool> {
+        let raw = if value.is_null() {
+            ELSSettings::default()
+        } else {
+            match ELSSettings::deserialize(value) {
+                Ok(raw) => raw,
+                Err(err) => {
+                    send_log(format!("invalid settings: {err}"))?;
+                    ELSSettings::default()
+                }
+            }
+        };
+        let folder = Self::path_of(folder);
+        let settings = self.resolve_settings(&folder, raw)?;
+        Ok(self.settings.insert(folder, settings))
+    }
+
+    fn resolve_settings(&self, folder: &Path, raw: ELSSettings) -> ELSResult<FolderSettings> {
+        let mut cfg = self.cfg.clone();
+        let mut disabled_features = vec![];
+        for feature in raw.disable.iter() {
+            match DefaultFeatures::try_from(&feature[..]) {
+                Ok(feature) => disabled_features.push(feature),
+                Err(err) => send_log(err)?,
+            }
+        }
+        let mut opt_features = vec![];
+        for feature in raw.enable.iter() {
+            match OptionalFeatures::try_from(&feature[..]) {
+                Ok(feature) => opt_features.push(feature),
+                Err(err) => send_log(err)?,
+            }
+        }
+        // the settings may come from an untrusted workspace, so `pythonCommand` is not passed to a shell
+        // (`cfg.py_command` is not set because ELS never runs the code)
+        if let Some(py_command) = raw.python_command.as_ref() {
+            let magic_num = opt_detect_magic_number_no_shell(py_command);
+            let version = opt_get_python_version_no_shell(py_command);
+            if let Some((magic_num, version)) = magic_num.zip(version) {
+                cfg.py_magic_num = Some(magic_num);
+                cfg.target_version = Some(version);
+            } else {
+                send_log(format!("invalid Python command: {py_command}"))?;
+            }
+        }
+        if let Some(version) = raw.target_version.as_ref() {
+            match parse_target_version(version) {
+                Some(version) => cfg.target_version = Some(version),
+                None => send_log(format!("invalid target version: {version}"))?,
+            }
+        }
+        if let Some(opt_level) = raw.opt_level {
+            cfg.opt_level = opt_level;
+        }
+        cfg.search_paths.extend(
+            raw.search_paths
+                .iter()
+                .map(|path| normalize_path(folder.join(path))),
+        );
+        Ok(FolderSettings {
+            raw,
+            cfg,
+            disabled_features,
+            opt_features,
+        })
+    }
+
+    /// Re-checks the files with the new settings.
+    fn recheck_files(&mut self) -> ELSResult<()> {
+        send_log("the settings have been changed")?;
+        for uri in self.file_cache.entries() {
+            let Ok(code) = self.file_cache.get_entire_code(&uri) else {
+                continue;
+            };
+            self.clear_cache(&uri);
+            self.check_module(uri.clone(), code, module_mode(&uri))?;
+        }
+        self.refresh_diagnostics()
+    }
+}
+
+/// Unlike `PythonVersion::from_str`, this rejects a version that is not of the form `major[.minor[.micro]]`.
+fn parse_target_version(version: &str) -> Option<PythonVersion> {
+    let mut nums = vec![];
+    for num in version.trim().split('.') {
+        nums.push(num.parse::<u8>().ok()?);
+    }
+    match nums[..] {
+        [major] => Some(PythonVersion::new(major, None, None)),
+        [major, minor] => Some(PythonVersion::new(major, Some(minor), None)),
+        [major, minor, micro] => Some(PythonVersion::new(major, Some(minor), Some(micro))),
+        _ => None,
+    }
+}
@@ -0,0 +1,233 @@
+use erg_common::env::{erg_path, is_std_decl_path};
+use erg_common::error::Location;
+use erg_common::traits::{Locational, NoTypeDisplay, Stream};
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::ast::DefKind;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::hir::{ClassDef, Def, Expr, PatchDef, Signature};
+use erg_compiler::varinfo::{VarInfo, VarKind};
+
+use lsp_types::{
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, SymbolInformation, SymbolKind,
+    WorkspaceSymbolParams,
+};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+/// The maximum number of symbols returned by `workspace/symbol`
+const MAX_WORKSPACE_SYMBOLS: usize = 256;
+
+pub(crate) fn symbol_kind(vi: &VarInfo, def_kind: Option<DefKind>, in_type: bool) -> SymbolKind {
+    match def_kind {
+        Some(DefKind::Class | DefKind::Inherit) => return SymbolKind::CLASS,
+        Some(DefKind::Trait | DefKind::Subsume | DefKind::StructuralTrait) => {
+            return SymbolKind::INTERFACE
+        }
+        Some(DefKind::Patch) => return SymbolKind::CLASS,
+        Some(DefKind::ErgImport | DefKind::PyImport) => return SymbolKind::MODULE,
+        _ => {}
+    }
+    match &vi.kind {
+        VarKind::InstanceAttr => SymbolKind::FIELD,
+        VarKind::Parameter { .. } => SymbolKind::VARIABLE,
+        _ if vi.t.is_class_type() => SymbolKind::CLASS,
+        // non-class types (traits)
+        _ if vi.t.is_type() => SymbolKind::INTERFACE,
+        _ if vi.t.is_module() || vi.t.is_py_module() => SymbolKind::MODULE,
+        _ if vi.t.is_subr() && in_type => SymbolKind::METHOD,
+        _ if vi.t.is_subr() => SymbolKind::FUNCTION,
+        _ if in_type => SymbolKind::PROPERTY,
+        _ if vi.muty.is_const() => SymbolKind::CONSTANT,
+        _ => SymbolKind::VARIABLE,
+    }
+}
+
+/// Case-insensitive fuzzy matching. The smaller the score, the better the match.
+/// Returns `None` if the characters of `query` do not appear in `name` in order.
+fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    let query = query.to_lowercase();
+    let name = name.to_lowercase();
+    if name.starts_with(&query) {
+        return Some(0);
+    }
+    if name.contains(&query) {
+        return Some(1);
+    }
+    let mut chars = name.chars();
+    query.chars().all(|q| chars.any(|c| c == q)).then_some(2)
+}
+
+/// `<module>.C` => `C`
+pub(crate) fn container_name(vi: &VarInfo) -> Option<String> {
+    vi.vis
+        .def_namespace
+        .rsplit_once('.')
+        .map(|(_module, container)| container.to_string())
+}
+
+/// Is `vi` a local variable of a subroutine (`<module>::f`)?
+fn is_local(vi: &VarInfo) -> bool {
+    vi.vis.def_namespace.contains("::")
+}
+
+#[allow(deprecated)]
+fn document_symbol(
+    sig: &Signature,
+    range: Location,
+    kind: SymbolKind,
+    children: Vec<DocumentSymbol>,
+) -> Option<DocumentSymbol> {
+    let ident = sig.ident();
+    let selection_range = util::loc_to_range(ident.loc())?;
+    let range = util::loc_to_range(range).unwrap_or(selection_range);
+    let detail = match kind {
+        SymbolKind::CLASS | SymbolKind::INTERFACE | SymbolKind::MODULE => None,
+        _ => Some(ident.vi.t.to_string()),
+    };
+    Some(DocumentSymbol {
+        name: ident.inspect().to_string(),
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children: (!children.is_empty()).then_some(children),
+    })
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    pub(crate) fn handle_document_symbol(
+        &mut self,
+        params: DocumentSymbolParams,
+    ) -> ELSResult<Option<DocumentSymbolResponse>> {
+        send_log(format!("document symbol requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let Some(hir) = self.analysis_result.get_hir(&uri) else {
+            return Ok(None);
+        };
+        let symbols = Self::exprs_symbols(hir.module.iter(), false);
+        Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+    }
+
+    fn exprs_symbols<'e>(
+        exprs: impl Iterator<Item = &'e Expr>,
+        in_type: bool,
+    ) -> Vec<DocumentSymbol> {
+        let mut symbols = vec![];
+        for expr in exprs {
+            match expr {
+                Expr::Def(def) => symbols.extend(Self::def_symbol(def, in_type)),
+                Expr::ClassDef(class_def) => symbols.extend(Self::class_def_symbol(class_def)),
+                Expr::PatchDef(patch_def) => symbols.extend(Self::patch_def_symbol(patch_def)),
+                Expr::Dummy(dummy) => symbols.extend(Self::exprs_symbols(dummy.iter(), in_type)),
+                _ => {}
+            }
+        }
+        symbols
+    }
+
+    fn def_symbol(def: &Def, in_type: bool) -> Option<DocumentSymbol> {
+        let kind = symbol_kind(&def.sig.ident().vi, Some(def.def_kind()), in_type);
+        // the local definitions of a subroutine
+        let children = if def.sig.is_subr() {
+            Self::exprs_symbols(def.body.block.iter(), false)
+        } else {
+            vec![]
+        };
+        document_symbol(&def.sig, def.loc(), kind, children)
+    }
+
+    fn class_def_symbol(class_def: &ClassDef) -> Option<DocumentSymbol> {
+        let mut children = vec![];
+        // fields (`C = Class { .x = Int }`)
+        if let Some(Expr::Record(record)) = class_def.require_or_sup.as_deref() {
+            for attr in record.attrs.iter() {
+                let field = document_symbol(&attr.sig, attr.loc(), SymbolKind::FIELD, vec![]);
+                // the type of a field is the value of the attribute (e.g. `Int`), not `{Int}`
+                let detail = attr
+                    .body
+                    .block
+                    .last()
+                    .and_then(|expr| expr.show_acc())
+                    .unwrap_or_else(|| attr.body.block.to_string_notype());
+                children.extend(field.map(|field| DocumentSymbol {
+                    detail: Some(detail),
+                    ..field
+                }));
+            }
+        }
+        children.extend(Self::exprs_symbols(class_def.methods.iter(), true));
+        document_symbol(&class_def.sig, class_def.loc(), SymbolKind::CLASS, children)
+    }
+
+    fn patch_def_symbol(patch_def: &PatchDef) -> Option<DocumentSymbol> {
+        let children = Self::exprs_symbols(patch_def.methods.iter(), true);
+        document_symbol(&patch_def.sig, patch_def.loc(), SymbolKind::CLASS, children)
+    }
+
+    #[allow(deprecated)]
+    pub(crate) fn handle_workspace_symbol(
+        &mut self,
+        params: WorkspaceSymbolParams,
+    ) -> ELSResult<Option<Vec<SymbolInformation>>> {
+        send_log(format!("workspace symbol requested: {params:?}"))?;
+        let Some(index) = self.get_index() else {
+            return Ok(None);
+        };
+        let mut symbols = vec![];
+        for (loc, value) in index.members().iter() {
+            // the response is discarded anyway
+            if self.is_cancelled() {
+                return Ok(None);
+            }
+            let vi = &value.vi;
+            let Some(path) = loc.module.as_ref() else {
+                continue;
+            };
+            if is_std_decl_path(path) || path.starts_with(erg_path()) {
+                continue;
+            }
+            if matches!(
+                vi.kind,
+                VarKind::Parameter { .. } | VarKind::Builtin | VarKind::DoesNotExist
+            ) {
+                continue;
+            }
+            if is_local(vi) {
+                continue;
+            }
+            let Some(score) = fuzzy_score(&params.query, &value.name) else {
+                continue;
+            };
+            let Some(location) = util::abs_loc_to_lsp_loc(loc) else {
+                continue;
+            };
+            let container = container_name(vi);
+            let in_type = container.is_some();
+            let symbol = SymbolInformation {
+                name: value.name.to_string(),
+                kind: symbol_kind(vi, None, in_type),
+                tags: None,
+                deprecated: None,
+                location,
+                container_name: container,
+            };
+            symbols.push((score, symbol));
+        }
+        symbols.sort_by(|(l_score, l), (r_score, r)| {
+            l_score
+                .cmp(r_score)
+                .then_with(|| l.name.len().cmp(&r.name.len()))
+                .then_with(|| l.name.cmp(&r.name))
+        });
+        let symbols = symbols
+            .into_iter()
+            .take(MAX_WORKSPACE_SYMBOLS)
+            .map(|(_, symbol)| symbol)
+            .collect();
+        Ok(Some(symbols))
+    }
+}
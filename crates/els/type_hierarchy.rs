@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+
+use erg_common::traits::Locational;
+use erg_common::Str;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::context::Context;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::hir::Expr;
+use erg_compiler::ty::Type;
+use erg_compiler::varinfo::AbsLocation;
+
+use lsp_types::request::Request;
+use lsp_types::{
+    CallHierarchyItem, PartialResultParams, Position, SymbolKind, TextDocumentPositionParams,
+    WorkDoneProgressParams,
+};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+// lsp-types 0.93 does not have the type hierarchy API (LSP 3.17), so it is defined here.
+
+pub type TypeHierarchyItem = CallHierarchyItem;
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyPrepareParams {
+    #[serde(flatten)]
+    pub text_document_position_params: TextDocumentPositionParams,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchySupertypesParams {
+    pub item: TypeHierarchyItem,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchySubtypesParams {
+    pub item: TypeHierarchyItem,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Debug)]
+pub enum TypeHierarchyPrepare {}
+
+impl Request for TypeHierarchyPrepare {
+    type Params = TypeHierarchyPrepareParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "textDocument/prepareTypeHierarchy";
+}
+
+#[derive(Debug)]
+pub enum TypeHierarchySupertypes {}
+
+impl Request for TypeHierarchySupertypes {
+    type Params = TypeHierarchySupertypesParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "typeHierarchy/supertypes";
+}
+
+#[derive(Debug)]
+pub enum TypeHierarchySubtypes {}
+
+impl Request for TypeHierarchySubtypes {
+    type Params = TypeHierarchySubtypesParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "typeHierarchy/subtypes";
+}
+
+/// A class/trait definition (see `HIRVisitor::get_type_def`)
+struct TypeDef {
+    name: Str,
+    def_loc: AbsLocation,
+    /// The traits implemented with `Impl := T` (they are not registered as the super traits)
+    impl_traits: Vec<Type>,
+    item: TypeHierarchyItem,
+}
+
+impl TypeDef {
+    fn new(type_def: &Expr, uri: &NormalizedUrl) -> Option<Self> {
+        let (sig, kind, impl_traits) = match type_def {
+            Expr::ClassDef(class_def) => {
+                let impl_traits = class_def
+                    .obj
+                    .impls()
+                    .map(|impls| impls.typ().ands().into_iter().collect())
+                    .unwrap_or_default();
+                (&class_def.sig, SymbolKind::CLASS, impl_traits)
+            }
+            Expr::Def(def) => (&def.sig, SymbolKind::INTERFACE, vec![]),
+            _ => return None,
+        };
+        let ident = sig.ident();
+        let selection_range = util::loc_to_range(ident.loc())?;
+        let range = util::loc_to_range(type_def.loc()).unwrap_or(selection_range);
+        let item = TypeHierarchyItem {
+            name: ident.inspect().to_string(),
+            kind,
+            tags: None,
+            detail: None,
+            uri: uri.clone().raw(),
+            range,
+            selection_range,
+            data: None,
+        };
+        Some(Self {
+            name: ident.inspect().clone(),
+            def_loc: ident.vi.def_loc.clone(),
+            impl_traits,
+            item,
+        })
+    }
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    fn with_module_ctx<T>(&self, uri: &NormalizedUrl, f: impl FnOnce(&Context) -> T) -> Option<T> {
+        if let Some(module) = self.modules.get(uri) {
+            return Some(f(&module.context));
+        }
+        let entry = self.get_shared()?.mod_cache.get(&util::uri_to_path(uri))?;
+        Some(f(&entry.module.context))
+    }
+
+    fn get_type_def(&self, uri: &NormalizedUrl, pos: Position) -> Option<TypeDef> {
+        let visitor = self.get_cached_visitor(uri)?;
+        TypeDef::new(visitor.get_type_def(pos)?, uri)
+    }
+
+    /// Returns the item of the class/trait defined at `def_loc`.
+    fn type_def_loc_to_item(&self, def_loc: &AbsLocation) -> Option<TypeHierarchyItem> {
+        let uri = NormalizedUrl::from_file_path(def_loc.module.as_ref()?).ok()?;
+        let pos = util::loc_to_range(def_loc.loc)?.start;
+        let type_def = self.get_type_def(&uri, pos)?;
+        (&type_def.def_loc == def_loc).then_some(type_def.item)
+    }
+
+    /// Returns the definition locations of the direct super classes/traits of `type_def` defined in `uri`.
+    fn get_direct_super_type_locs(
+        &self,
+        uri: &NormalizedUrl,
+        type_def: &TypeDef,
+    ) -> Vec<AbsLocation> {
+        self.with_module_ctx(uri, |ctx| {
+            ctx.get_direct_super_types_by_str(&type_def.name)
+                .unwrap_or_default()
+                .iter()
+                .chain(type_def.impl_traits.iter())
+                .filter_map(|sup| {
+                    // the types defined in the module (`<module>::C`) are not found by `get_type_info`
+                    ctx.get_type_info(sup)
+                        .or_else(|| ctx.get_var_info(&sup.local_name()))
+                })
+                .map(|(_, vi)| vi.def_loc.clone())
+                .filter(|def_loc| def_loc.module.is_some())
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    /// Returns the classes/traits directly inheriting (or implementing) the type defined at `def_loc`.
+    /// The candidates are the class/trait definitions that refer to the type.
+    pub(crate) fn get_subtype_items(&self, def_loc: &AbsLocation) -> Vec<TypeHierarchyItem> {
+        let Some(referrers) = self
+            .get_index()
+            .and_then(|index| index.get_refs(def_loc))
+            .map(|value| value.referrers.iter().cloned().collect::<Vec<_>>())
+        else {
+            return vec![];
+        };
+        let mut items: Vec<TypeHierarchyItem> = vec![];
+        for referrer in referrers {
+            if self.is_cancelled() {
+                break;
+            }
+            let Some(path) = referrer.module.as_ref() else {
+                continue;
+            };
+            let (Ok(uri), Some(range)) = (
+                NormalizedUrl::from_file_path(path),
+                util::loc_to_range(referrer.loc),
+            ) else {
+                continue;
+            };
+            let Some(type_def) = self.get_type_def(&uri, range.start) else {
+                continue;
+            };
+            if items.iter().any(|item| {
+                item.uri == type_def.item.uri
+                    && item.selection_range == type_def.item.selection_range
+            }) {
+                continue;
+            }
+            // exclude the references that are not in the inheritance clauses (e.g. `x: T` in a method)
+            if !self
+                .get_direct_super_type_locs(&uri, &type_def)
+                .contains(def_loc)
+            {
+                continue;
+            }
+            items.push(type_def.item);
+        }
+        items
+    }
+
+    pub(crate) fn handle_type_hierarchy_prepare(
+        &mut self,
+        params: TypeHierarchyPrepareParams,
+    ) -> ELSResult<Option<Vec<TypeHierarchyItem>>> {
+        send_log(format!("type hierarchy preparation requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let Some(token) = self.file_cache.get_token(&uri, pos) else {
+            return Ok(None);
+        };
+        let Some(vi) = self
+            .get_visitor(&uri)
+            .and_then(|visitor| visitor.get_info(&token))
+        else {
+            return Ok(None);
+        };
+        Ok(self
+            .type_def_loc_to_item(&vi.def_loc)
+            .map(|item| vec![item]))
+    }
+
+    pub(crate) fn handle_type_hierarchy_supertypes(
+        &mut self,
+        params: TypeHierarchySupertypesParams,
+    ) -> ELSResult<Option<Vec<TypeHierarchyItem>>> {
+        send_log(format!("supertypes requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.item.uri);
+        let Some(type_def) = self.get_type_def(&uri, params.item.selection_range.start) else {
+            return Ok(None);
+        };
+        let items = self
+            .get_direct_super_type_locs(&uri, &type_def)
+            .iter()
+            .filter_map(|def_loc| self.type_def_loc_to_item(def_loc))
+            .collect();
+        Ok(Some(items))
+    }
+
+    pub(crate) fn handle_type_hierarchy_subtypes(
+        &mut self,
+        params: TypeHierarchySubtypesParams,
+    ) -> ELSResult<Option<Vec<TypeHierarchyItem>>> {
+        send_log(format!("subtypes requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.item.uri);
+        let Some(type_def) = self.get_type_def(&uri, params.item.selection_range.start) else {
+            return Ok(None);
+        };
+        Ok(Some(self.get_subtype_items(&type_def.def_loc)))
+    }
+}
@@ -9,6 +9,7 @@ use std::process;
 use std::str::FromStr;
 
 use crate::help_messages::{command_message, mode_message, OPTIONS};
+pub use crate::io::Package;
 use crate::io::{Input, Output};
 use crate::levenshtein::get_similar_name;
 use crate::normalize_path;
@@ -24,6 +25,10 @@ pub enum ErgMode {
     FullCheck,
     Compile,
     Transpile,
+    Format,
+    Test,
+    Pack,
+    Doc,
     Execute,
     LanguageServer,
     Read,
@@ -40,6 +45,10 @@ impl TryFrom<&str> for ErgMode {
             "fullcheck" | "check" | "checker" => Ok(Self::FullCheck),
             "comp" | "compile" | "compiler" => Ok(Self::Compile),
             "trans" | "transpile" | "transpiler" => Ok(Self::Transpile),
+            "fmt" | "format" | "formatter" => Ok(Self::Format),
+            "test" | "tester" => Ok(Self::Test),
+            "pack" | "package" => Ok(Self::Pack),
+            "doc" | "document" => Ok(Self::Doc),
             "run" | "execute" => Ok(Self::Execute),
             "server" | "language-server" => Ok(Self::LanguageServer),
             "byteread" | "read" | "reader" | "dis" => Ok(Self::Read),
@@ -58,6 +67,10 @@ impl From<ErgMode> for &str {
             ErgMode::FullCheck => "fullcheck",
             ErgMode::Compile => "compile",
             ErgMode::Transpile => "transpile",
+            ErgMode::Format => "format",
+            ErgMode::Test => "test",
+            ErgMode::Pack => "pack",
+            ErgMode::Doc => "doc",
             ErgMode::Execute => "execute",
             ErgMode::LanguageServer => "language-server",
             ErgMode::Read => "read",
@@ -106,6 +119,10 @@ pub struct ErgConfig {
     pub py_server_timeout: u64,
     pub quiet_repl: bool,
     pub show_type: bool,
+    /// `fmt --check`: only report whether the input is formatted
+    pub check_only: bool,
+    /// `--watch`: re-run when the main file or its dependencies change
+    pub watch: bool,
     pub input: Input,
     pub output: Output,
     pub dist_dir: Option<&'static str>,
@@ -119,6 +136,10 @@ pub struct ErgConfig {
     pub ps1: &'static str,
     pub ps2: &'static str,
     pub runtime_args: Vec<&'static str>,
+    /// the current package and its (transitive) dependencies
+    pub packages: Vec<Package>,
+    /// extra directories to search for modules (e.g. given by the language server settings)
+    pub search_paths: Vec<PathBuf>,
 }
 
 impl Default for ErgConfig {
@@ -135,6 +156,8 @@ impl Default for ErgConfig {
             py_server_timeout: 10,
             quiet_repl: false,
             show_type: false,
+            check_only: false,
+            watch: false,
             input: Input::repl(),
             output: Output::stdout(),
             dist_dir: None,
@@ -143,6 +166,8 @@ impl Default for ErgConfig {
             ps1: ">>> ",
             ps2: "... ",
             runtime_args: vec![],
+            packages: vec![],
+            search_paths: vec![],
         }
     }
 }
@@ -228,7 +253,11 @@ impl ErgConfig {
                     cfg.input = Input::str(args.next().expect("the value of `-c` is not passed"));
                 }
                 "--check" => {
-                    cfg.mode = ErgMode::FullCheck;
+                    if cfg.mode == ErgMode::Format {
+                        cfg.check_only = true;
+                    } else {
+                        cfg.mode = ErgMode::FullCheck;
+                    }
                 }
                 "--compile" | "--dump-as-pyc" => {
                     cfg.mode = ErgMode::Compile;
@@ -263,6 +292,11 @@ impl ErgConfig {
                         eprintln!("invalid mode: {mode}");
                         process::exit(1);
                     });
+                    if matches!(cfg.mode, ErgMode::Pack | ErgMode::Doc) {
+                        for arg in args.by_ref() {
+                            cfg.runtime_args.push(Box::leak(arg.into_boxed_str()));
+                        }
+                    }
                 }
                 "--ping" => {
                     println!("pong");
@@ -338,6 +372,9 @@ impl ErgConfig {
                 "-t" | "--show-type" => {
                     cfg.show_type = true;
                 }
+                "--watch" => {
+                    cfg.watch = true;
+                }
                 "--target-version" => {
                     let target_version = args
                         .next()
@@ -403,7 +440,17 @@ USAGE:
                 }
                 _ => {
                     if let Ok(mode) = ErgMode::try_from(&arg[..]) {
+                        // `erg --check fmt`
+                        if mode == ErgMode::Format && cfg.mode == ErgMode::FullCheck {
+                            cfg.check_only = true;
+                        }
                         cfg.mode = mode;
+                        // `erg pack <command> [ARGS]...`, `erg doc [PATH] [OPTIONS]...`
+                        if matches!(mode, ErgMode::Pack | ErgMode::Doc) {
+                            for arg in args.by_ref() {
+                                cfg.runtime_args.push(Box::leak(arg.into_boxed_str()));
+                            }
+                        }
                     } else {
                         let path = PathBuf::from_str(&arg[..])
                             .unwrap_or_else(|_| panic!("invalid file path: {arg}"));
@@ -427,7 +474,13 @@ USAGE:
                 }
             }
         }
-        if cfg.input.is_repl() && cfg.mode != ErgMode::LanguageServer {
+        // `erg test` without arguments searches the current directory
+        if cfg.input.is_repl()
+            && !matches!(
+                cfg.mode,
+                ErgMode::LanguageServer | ErgMode::Test | ErgMode::Pack | ErgMode::Doc
+            )
+        {
             let is_stdin_piped = !stdin().is_terminal();
             let input = if is_stdin_piped {
                 let mut buffer = String::new();
@@ -73,6 +73,7 @@ pub static ERG_STD_DECL_PATH: OnceLock<PathBuf> = OnceLock::new();
 pub static ERG_PYSTD_PATH: OnceLock<PathBuf> = OnceLock::new();
 pub static ERG_EXTERNAL_LIB_PATH: OnceLock<PathBuf> = OnceLock::new();
 pub static PYTHON_SITE_PACKAGES: OnceLock<Vec<PathBuf>> = OnceLock::new();
+pub static ERG_PKGS_PATH: OnceLock<PathBuf> = OnceLock::new();
 
 pub fn erg_path() -> &'static PathBuf {
     ERG_PATH.get_or_init(|| normalize_path(_erg_path())) // .with(|s| s.clone())
@@ -98,6 +99,12 @@ pub fn python_site_packages() -> &'static Vec<PathBuf> {
     PYTHON_SITE_PACKAGES.get_or_init(|| _python_site_packages().collect())
 }
 
+/// The local package cache (`ERG_PATH/lib/pkgs/{name}/{version}`).
+/// Unlike the other paths, this may not exist.
+pub fn erg_pkgs_path() -> &'static PathBuf {
+    ERG_PKGS_PATH.get_or_init(|| normalize_path(erg_path().join("lib").join("pkgs")))
+}
+
 pub fn is_std_decl_path(path: &Path) -> bool {
     path.starts_with(erg_pystd_path().as_path())
         || path.starts_with(erg_std_decl_path().as_path())
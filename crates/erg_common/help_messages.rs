@@ -23,6 +23,7 @@ OPTIONS
     --mode (mode)                        指定モードで実行(詳細は--mode --helpを参照)
     --code/-c (string)                   文字列として渡したプログラムを実行
     --module/-m (string)                 モジュールを実行
+    --watch                              ファイルの変更を監視し、影響を受けるモジュールを再検査・再実行(check/compile/run)
 
 COMMAND
     lex                                  字句解析
@@ -31,6 +32,10 @@ COMMAND
     check                                全ての検査(所有権検査, 副作用検査などを含む)
     compile                              コンパイル
     transpile                            トランスパイル
+    fmt                                  コードを整形 (--checkで整形済みか確認)
+    test                                 @Testサブルーチンを実行
+    pack                                 パッケージを管理 (init|build|update|compat)
+    doc                                  APIドキュメントを生成
     run|exec                             実行(デフォルト)
     server                               言語サーバーを起動",
 
@@ -54,6 +59,7 @@ OPTIONS
     --mode (mode)                        执行模式 (更多信息见`--mode --help`)
     --code/-c (string)                   作为字符串传入程序
     --module/-m (string)                 要执行的模块
+    --watch                              监视文件变更, 重新检查/执行受影响的模块 (check/compile/run)
 
 COMMAND
     lex                                  字词解析
@@ -62,6 +68,10 @@ COMMAND
     check                                全部检查(包括所有权检查, 副作用检查等)
     compile                              编译
     transpile                            转译
+    fmt                                  格式化代码 (--check: 仅检查是否已格式化)
+    test                                 运行 @Test 子程序
+    pack                                 管理包 (init|build|update|compat)
+    doc                                  生成 API 文档
     run|exec                             执行(默认模式)
     server                               执行语言服务器",
 
@@ -85,6 +95,7 @@ OPTIONS
     --mode (mode)                        執行模式 (更多信息見`--mode --help`)
     --code/-c (string)                   作為字串傳入程式
     --module/-m (string)                 要執行的模塊
+    --watch                              監視檔案變更, 重新檢查/執行受影響的模組 (check/compile/run)
 
 COMMAND
     lex                                  字詞解析
@@ -93,6 +104,10 @@ COMMAND
     check                                全部檢查(包括所有權檢查, 副作用檢查等)
     compile                              編譯
     transpile                            轉譯
+    fmt                                  格式化代碼 (--check: 僅檢查是否已格式化)
+    test                                 執行 @Test 子程式
+    pack                                 管理套件 (init|build|update|compat)
+    doc                                  產生 API 文件
     run|exec                             執行(預設模式)
     server                               執行語言伺服器",
 
@@ -116,6 +131,7 @@ OPTIONS
     --mode (mode)                        execution mode (See `--mode --help` for details)
     --code/-c (string)                   program passed in as string
     --module/-m (string)                 module to be executed
+    --watch                              watch the files and re-check/re-run the affected modules on change (check/compile/run)
 
 COMMAND
     lex                                  lexical analysis
@@ -124,6 +140,10 @@ COMMAND
     check                                full check (including ownership check, effect check, etc.)
     compile                              compile
     transpile                            transpile
+    fmt                                  format the code (--check: only check if it is formatted)
+    test                                 run the @Test subroutines
+    pack                                 manage the package (init|build|update|compat)
+    doc                                  generate the API documents
     run|exec                             execute (default mode)
     server                               execute language server",
     )
@@ -134,7 +154,7 @@ pub fn mode_message<'a>() -> &'a str {
         "japanese" =>
         "\
 USAGE:
-    erg --mode [lex | parse | lower | check | compile | exec | read] [SUBCOMMAND] [ARGS]...
+    erg --mode [lex | parse | lower | check | compile | transpile | fmt | test | pack | doc | exec | read] [SUBCOMMAND] [ARGS]...
 
 lex
     <filename>.erやREPLなどから入力を受け取り、字句を解析
@@ -155,11 +175,30 @@ check
 compile
     checkを実行
     HIRからバイトコードを生成し、<filename>.pycを出力する
+    ディレクトリが指定された場合、全モジュールを<dist>/<dirname>/にPythonパッケージとして出力する
 
 transpile
     checkを実行
     HIRからPythonスクリプトを生成し、<filename>.pyを出力
 
+fmt
+    lexとparseを実行し、インデント・演算子周りの空白・空行を整形
+    コメントは保持され、--checkを指定すると整形済みかどうかのみ確認する
+
+test
+    <dir>/tests/*.erと*.test.erを探索し、@Testが付いたサブルーチンを実行
+    結果の要約を表示する
+
+pack
+    init [app|lib|hybrid]: カレントディレクトリにpackage.erとsrcディレクトリを生成
+    build [--release]: package.erを読み込み、src以下のファイルをbuild/debug(release)にコンパイル (依存関係を解決し、package.lock.erを更新)
+    update: package.lock.erを無視して依存関係を解決し直す
+    compat <OLD_PACKAGE_ROOT>: 旧バージョンとAPIを比較し、必要なバージョンの上げ幅を確認
+
+doc [PATH] [--html] [--output DIR]
+    PATH(デフォルトはカレントディレクトリ)以下のモジュールの公開APIのドキュメントをMarkdown(--htmlでHTML)で生成
+    パッケージの場合はsrc以下が対象で、出力先のデフォルトは<PATH>/build/doc
+
 run/exec
     compileを実行し、更に<filename>.pycを実行
 
@@ -169,7 +208,7 @@ read
     "simplified_chinese" =>
     "\
 USAGE:
-    erg --mode [lex | parse | lower | check | compile | exec | read] [SUBCOMMAND] [ARGS]...
+    erg --mode [lex | parse | lower | check | compile | transpile | fmt | test | pack | doc | exec | read] [SUBCOMMAND] [ARGS]...
 
 lex
     从 <filename>.er, REPL 等接受输入, 并标记文本
@@ -190,11 +229,30 @@ check
 compile
     运行 check 以获取检查完成的 AST
     编译 AST 并返回 <文件名>.pyc
+    如果指定了目录, 则将所有模块输出为 Python 包 <dist>/<目录名>/
 
 transpile
     运行 check 以获取检查完成的 AST
     将 AST 转换为 Python 代码并返回 <文件名>.py
 
+fmt
+    运行 lex 和 parse, 整理缩进、运算符周围的空格和空行
+    保留注释, 指定 --check 时仅检查是否已格式化
+
+test
+    搜索 <dir>/tests/*.er 和 *.test.er, 执行带有 @Test 的子程序
+    显示结果摘要
+
+pack
+    init [app|lib|hybrid]: 在当前目录生成 package.er 和 src 目录
+    build [--release]: 读取 package.er, 将 src 下的文件编译到 build/debug(release) (解析依赖并更新 package.lock.er)
+    update: 忽略 package.lock.er 重新解析依赖
+    compat <OLD_PACKAGE_ROOT>: 与旧版本比较 API, 检查所需的版本升级
+
+doc [PATH] [--html] [--output DIR]
+    为 PATH (默认为当前目录) 下模块的公共 API 生成 Markdown (--html 时为 HTML) 文档
+    对于包, 以 src 下的文件为对象, 默认输出到 <PATH>/build/doc
+
 run/exec
     运行 check 以获取检查完成的 AST
     在执行 <文件名>.pyc 后删除 <文件名>.pyc
@@ -205,7 +263,7 @@ read
     "traditional_chinese" =>
     "\
 USAGE:
-        erg --mode [lex | parse | lower | check | compile | exec | read] [SUBCOMMAND] [ARGS]...
+        erg --mode [lex | parse | lower | check | compile | transpile | fmt | test | pack | doc | exec | read] [SUBCOMMAND] [ARGS]...
 
 lex
     從 <檔名>.er, REPL 等接受輸入, 並標記文字
@@ -226,11 +284,30 @@ check
 compile
     運行 check 以獲取檢查完成的 AST
     編譯 AST 並返回 <檔名>.pyc
+    如果指定了目錄, 則將所有模組輸出為 Python 包 <dist>/<目錄名>/
 
 transpile
     運行 check 以獲取檢查完成的 AST
     從 HIR 生成 Python 腳本並返回 <檔名>.py
 
+fmt
+    運行 lex 和 parse, 整理縮進、運算符周圍的空格和空行
+    保留註釋, 指定 --check 時僅檢查是否已格式化
+
+test
+    搜尋 <dir>/tests/*.er 和 *.test.er, 執行帶有 @Test 的子程式
+    顯示結果摘要
+
+pack
+    init [app|lib|hybrid]: 在當前目錄生成 package.er 和 src 目錄
+    build [--release]: 讀取 package.er, 將 src 下的檔案編譯到 build/debug(release) (解析依賴並更新 package.lock.er)
+    update: 忽略 package.lock.er 重新解析依賴
+    compat <OLD_PACKAGE_ROOT>: 與舊版本比較 API, 檢查所需的版本升級
+
+doc [PATH] [--html] [--output DIR]
+    為 PATH (預設為當前目錄) 下模組的公開 API 產生 Markdown (--html 時為 HTML) 文件
+    對於套件, 以 src 下的檔案為對象, 預設輸出到 <PATH>/build/doc
+
 exec
     運行check以獲取檢查完成的 AST
     在執行 <檔名>.pyc 後删除 <檔名>.pyc
@@ -241,7 +318,7 @@ read
     "english" =>
     "\
 USAGE:
-    erg --mode [lex | parse | lower | check | compile | exec | read] [SUBCOMMAND] [ARGS]...
+    erg --mode [lex | parse | lower | check | compile | transpile | fmt | test | pack | doc | exec | read] [SUBCOMMAND] [ARGS]...
 
 lex
     Receive input from <filename>.er, REPL, etc. and lex the text
@@ -262,11 +339,30 @@ check
 compile
     Execute check
     Generates bytecode from HIR and outputs <filename>.pyc
+    If a directory is given, outputs all modules as a Python package <dist>/<dirname>/
 
 transpile
     Execute check
     Generates Python script from HIR and outputs <filename>.py
 
+fmt
+    Execute lex and parse, and normalize indentation, spaces around operators and blank lines
+    Comments are preserved. With --check, only checks whether the code is formatted
+
+test
+    Search <dir>/tests/*.er and *.test.er, and execute the subroutines decorated with @Test
+    Prints a summary of the results
+
+pack
+    init [app|lib|hybrid]: generate package.er and the src directory in the current directory
+    build [--release]: read package.er and compile the files under src into build/debug(release) (resolves the dependencies and updates package.lock.er)
+    update: resolve the dependencies again ignoring package.lock.er
+    compat <OLD_PACKAGE_ROOT>: compare the API with the old version and check the required version bump
+
+doc [PATH] [--html] [--output DIR]
+    Generate Markdown (HTML with --html) documents of the public API of the modules under PATH (default: the current directory)
+    For a package, the files under src are documented. The default output directory is <PATH>/build/doc
+
 run/exec
     Execute compile and then <filename>.pyc
 
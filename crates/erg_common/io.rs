@@ -56,6 +56,26 @@ impl DummyStdin {
     }
 }
 
+/// A package resolved by the package manager.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// the directory containing `package.er`
+    pub root: PathBuf,
+    /// (the name used in the package, the root of the dependency)
+    pub deps: Vec<(String, PathBuf)>,
+}
+
+impl Package {
+    pub fn get_dep(&self, as_name: &str) -> Option<&Path> {
+        self.deps
+            .iter()
+            .find(|(name, _)| name == as_name)
+            .map(|(_, root)| root.as_path())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum InputKind {
     File(PathBuf),
@@ -175,6 +195,10 @@ impl Input {
         self.kind.is_repl()
     }
 
+    pub const fn is_file(&self) -> bool {
+        matches!(self.kind, InputKind::File(_))
+    }
+
     pub const fn id(&self) -> u64 {
         self.id
     }
@@ -477,12 +501,19 @@ impl Input {
         Ok(normalize_path(path))
     }
 
-    pub fn resolve_py(&self, path: &Path) -> Result<PathBuf, std::io::Error> {
+    pub fn resolve_py(
+        &self,
+        path: &Path,
+        search_paths: &[PathBuf],
+    ) -> Result<PathBuf, std::io::Error> {
         if ERG_MODE || path.starts_with("./") {
             if let Ok(path) = self.resolve_local_py(path) {
                 return Ok(path);
             }
         }
+        if let Some(path) = Self::resolve_search_path(path, search_paths, "py") {
+            return Ok(path);
+        }
         for sys_path in self.sys_path()? {
             let mut dir = sys_path;
             dir.push(path);
@@ -506,19 +537,92 @@ impl Input {
         ))
     }
 
-    pub fn resolve_path(&self, path: &Path) -> Option<PathBuf> {
-        self.resolve_real_path(path)
-            .or_else(|| self.resolve_decl_path(path))
+    pub fn resolve_path(
+        &self,
+        path: &Path,
+        packages: &[Package],
+        search_paths: &[PathBuf],
+    ) -> Option<PathBuf> {
+        self.resolve_real_path(path, packages, search_paths)
+            .or_else(|| self.resolve_decl_path(path, packages, search_paths))
+    }
+
+    /// Resolves `{dep}/{path/to}` in the dependencies of the package which contains this input.
+    ///
+    /// resolution order:
+    /// 1. `{dep_root}/src/lib.{ext}` (if `{path/to}` is empty)
+    /// 2. `{dep_root}/src/{path/to}.{ext}`
+    /// 3. `{dep_root}/src/{path/to}/__init__.{ext}`
+    fn resolve_package_path(
+        &self,
+        path: &Path,
+        packages: &[Package],
+        ext: &str,
+    ) -> Option<PathBuf> {
+        let mut comps = path.iter();
+        let as_name = comps.next()?.to_str()?;
+        let rest = comps.collect::<PathBuf>();
+        let this = self.path().canonicalize().ok()?;
+        // the innermost package containing this input
+        let pkg = packages
+            .iter()
+            .filter(|pkg| this.starts_with(&pkg.root))
+            .max_by_key(|pkg| pkg.root.components().count())?;
+        let src = pkg.get_dep(as_name)?.join("src");
+        let candidates = if rest.as_os_str().is_empty() {
+            vec![src.join(format!("lib.{ext}"))]
+        } else {
+            let mut file = src.join(&rest);
+            file.set_extension(ext);
+            vec![file, src.join(&rest).join(format!("__init__.{ext}"))]
+        };
+        candidates
+            .into_iter()
+            .find_map(|path| path.canonicalize().ok())
+            .map(normalize_path)
+    }
+
+    /// Resolves `{path/to}` in the extra search paths.
+    ///
+    /// resolution order:
+    /// 1. `{search_path}/{path/to}.{ext}`
+    /// 2. `{search_path}/{path/to}/__init__.{ext}`
+    fn resolve_search_path(path: &Path, search_paths: &[PathBuf], ext: &str) -> Option<PathBuf> {
+        search_paths
+            .iter()
+            .find_map(|dir| {
+                let mut file = dir.join(path);
+                file.set_extension(ext);
+                file.canonicalize()
+                    .or_else(|_| {
+                        dir.join(path)
+                            .join(format!("__init__.{ext}"))
+                            .canonicalize()
+                    })
+                    .ok()
+            })
+            .map(normalize_path)
     }
 
     /// resolution order:
     /// 1. `./{path/to}.er`
     /// 2. `./{path/to}/__init__.er`
-    /// 3. `std/{path/to}.er`
-    /// 4. `std/{path/to}/__init__.er`
-    pub fn resolve_real_path(&self, path: &Path) -> Option<PathBuf> {
+    /// 3. `{dep}/src/{path/to}.er` (see `resolve_package_path`)
+    /// 4. `{search_path}/{path/to}.er` (see `resolve_search_path`)
+    /// 5. `std/{path/to}.er`
+    /// 6. `std/{path/to}/__init__.er`
+    pub fn resolve_real_path(
+        &self,
+        path: &Path,
+        packages: &[Package],
+        search_paths: &[PathBuf],
+    ) -> Option<PathBuf> {
         if let Ok(path) = self.resolve_local(path) {
             Some(path)
+        } else if let Some(path) = self.resolve_package_path(path, packages, "er") {
+            Some(path)
+        } else if let Some(path) = Self::resolve_search_path(path, search_paths, "er") {
+            Some(path)
         } else if let Ok(path) = erg_std_path()
             .join(format!("{}.er", path.display()))
             .canonicalize()
@@ -543,11 +647,18 @@ impl Input {
     /// 5.  `{path.d/to.d}/__init__.d.er`
     /// 6.  `{path.d/to.d}/__pycache__/__init__.d.er`
     /// (and repeat for the project root)
-    /// 7.  `std/{path/to}.d.er`
-    /// 8.  `std/{path/to}/__init__.d.er`
-    /// 9.  `site-packages/{path}/__pycache__/{to}.d.er`
-    /// 10. `site-packages/{path/to}/__pycache__/__init__.d.er`
-    pub fn resolve_decl_path(&self, path: &Path) -> Option<PathBuf> {
+    /// 7.  `{dep}/src/{path/to}.d.er` (see `resolve_package_path`)
+    /// 8.  `{search_path}/{path/to}.d.er` (and the other local patterns for each search path)
+    /// 9.  `std/{path/to}.d.er`
+    /// 10. `std/{path/to}/__init__.d.er`
+    /// 11. `site-packages/{path}/__pycache__/{to}.d.er`
+    /// 12. `site-packages/{path/to}/__pycache__/__init__.d.er`
+    pub fn resolve_decl_path(
+        &self,
+        path: &Path,
+        packages: &[Package],
+        search_paths: &[PathBuf],
+    ) -> Option<PathBuf> {
         if let Ok(path) = self.resolve_local_decl(self.dir(), path) {
             return Some(path);
         }
@@ -566,6 +677,14 @@ impl Input {
                 }
             }
         }
+        if let Some(path) = self.resolve_package_path(path, packages, "d.er") {
+            return Some(path);
+        }
+        for dir in search_paths.iter() {
+            if let Ok(path) = self.resolve_local_decl(dir.clone(), path) {
+                return Some(path);
+            }
+        }
         let py_roots = [erg_pystd_path, erg_py_external_lib_path];
         for root in py_roots {
             if let Some(path) = Self::resolve_std_decl_path(root(), path) {
@@ -113,6 +113,43 @@ impl FileKind {
     }
 }
 
+/// Collects the files under `dir` recursively (sorted), skipping hidden entries and `__pycache__`.
+/// Only the directories satisfying `enter_dir` are visited, and only the files satisfying `is_target` are collected.
+pub fn collect_files(
+    dir: &Path,
+    enter_dir: impl Fn(&Path) -> bool,
+    is_target: impl Fn(&Path) -> bool,
+) -> Vec<PathBuf> {
+    fn collect(
+        dir: &Path,
+        enter_dir: &dyn Fn(&Path) -> bool,
+        is_target: &dyn Fn(&Path) -> bool,
+        files: &mut Vec<PathBuf>,
+    ) {
+        let Ok(entries) = dir.read_dir() else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            if name.to_string_lossy().starts_with('.') || name == "__pycache__" {
+                continue;
+            }
+            if path.is_dir() {
+                if enter_dir(&path) {
+                    collect(&path, enter_dir, is_target, files);
+                }
+            } else if is_target(&path) {
+                files.push(path);
+            }
+        }
+    }
+    let mut files = vec![];
+    collect(dir, &enter_dir, &is_target, &mut files);
+    files.sort();
+    files
+}
+
 pub fn is_cur_dir<P: AsRef<Path>>(path: P) -> bool {
     path.as_ref()
         .components()
@@ -582,6 +582,11 @@ fn which_python() -> String {
 }
 
 pub fn detect_magic_number(py_command: &str) -> u32 {
+    opt_detect_magic_number(py_command).expect("cannot get the magic number from python")
+}
+
+/// Returns `None` if `py_command` is not a valid Python command
+pub fn opt_detect_magic_number(py_command: &str) -> Option<u32> {
     let out = if cfg!(windows) {
         Command::new("cmd")
             .arg("/C")
@@ -589,7 +594,7 @@ pub fn detect_magic_number(py_command: &str) -> u32 {
             .arg("-c")
             .arg("import importlib.util as util;print(util.MAGIC_NUMBER.hex())")
             .output()
-            .expect("cannot get the magic number from python")
+            .ok()?
     } else {
         let exec_command = format!(
             "{py_command} -c 'import importlib.util as util;print(util.MAGIC_NUMBER.hex())'",
@@ -598,12 +603,28 @@ pub fn detect_magic_number(py_command: &str) -> u32 {
             .arg("-c")
             .arg(exec_command)
             .output()
-            .expect("cannot get the magic number from python")
+            .ok()?
     };
-    let s_hex_magic_num = String::from_utf8(out.stdout).unwrap();
-    let first_byte = u8::from_str_radix(&s_hex_magic_num[0..=1], 16).unwrap();
-    let second_byte = u8::from_str_radix(&s_hex_magic_num[2..=3], 16).unwrap();
-    get_magic_num_from_bytes(&[first_byte, second_byte, 0, 0])
+    parse_magic_number(out)
+}
+
+/// Unlike `opt_detect_magic_number`, `py_exe` is executed directly (not via a shell),
+/// so it must be an executable, not a command line.
+/// Use this if `py_exe` comes from an untrusted source (e.g. a workspace setting).
+pub fn opt_detect_magic_number_no_shell(py_exe: &str) -> Option<u32> {
+    let out = Command::new(py_exe)
+        .arg("-c")
+        .arg("import importlib.util as util;print(util.MAGIC_NUMBER.hex())")
+        .output()
+        .ok()?;
+    parse_magic_number(out)
+}
+
+fn parse_magic_number(out: std::process::Output) -> Option<u32> {
+    let s_hex_magic_num = String::from_utf8(out.stdout).ok()?;
+    let first_byte = u8::from_str_radix(s_hex_magic_num.get(0..=1)?, 16).ok()?;
+    let second_byte = u8::from_str_radix(s_hex_magic_num.get(2..=3)?, 16).ok()?;
+    Some(get_magic_num_from_bytes(&[first_byte, second_byte, 0, 0]))
 }
 
 pub fn env_magic_number() -> u32 {
@@ -686,32 +707,48 @@ impl std::str::FromStr for PythonVersion {
 }
 
 pub fn get_python_version(py_command: &str) -> PythonVersion {
+    opt_get_python_version(py_command).expect("cannot get the python version")
+}
+
+/// Returns `None` if `py_command` is not a valid Python command
+pub fn opt_get_python_version(py_command: &str) -> Option<PythonVersion> {
     let out = if cfg!(windows) {
         Command::new("cmd")
             .arg("/C")
             .arg(py_command)
             .arg("--version")
             .output()
-            .expect("cannot get the python version")
+            .ok()?
     } else {
         let exec_command = format!("{py_command} --version");
         Command::new("sh")
             .arg("-c")
             .arg(exec_command)
             .output()
-            .expect("cannot get the python version")
+            .ok()?
     };
-    let s_version = String::from_utf8(out.stdout).unwrap();
+    parse_python_version(out)
+}
+
+/// Unlike `opt_get_python_version`, `py_exe` is executed directly (not via a shell).
+/// See `opt_detect_magic_number_no_shell`.
+pub fn opt_get_python_version_no_shell(py_exe: &str) -> Option<PythonVersion> {
+    let out = Command::new(py_exe).arg("--version").output().ok()?;
+    parse_python_version(out)
+}
+
+fn parse_python_version(out: std::process::Output) -> Option<PythonVersion> {
+    let s_version = String::from_utf8(out.stdout).ok()?;
     let mut iter = s_version.split(' ');
-    let mut iter = iter.nth(1).unwrap().split('.');
+    let mut iter = iter.nth(1)?.split('.');
     let major = iter.next().and_then(|i| i.parse().ok()).unwrap_or(3);
     let minor = iter.next().and_then(|i| i.parse().ok());
     let micro = iter.next().and_then(|i| i.trim_end().parse().ok());
-    PythonVersion {
+    Some(PythonVersion {
         major,
         minor,
         micro,
-    }
+    })
 }
 
 pub fn env_python_version() -> PythonVersion {
@@ -701,7 +701,7 @@ pub trait Runnable: Sized + Default {
         if src.ends_with("->") && !src.starts_with("->") {
             return BlockKind::Lambda;
         }
-        if src.contains("Class") || src.contains("Inherit") {
+        if src.contains("Class") || src.contains("Inherit") || src.contains("Newtype") {
             return BlockKind::ClassDef;
         }
         BlockKind::None
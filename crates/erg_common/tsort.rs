@@ -102,6 +102,43 @@ pub fn tsort<T: Eq + Hash + Clone, U>(g: Graph<T, U>) -> Result<Graph<T, U>, Top
     Ok(reorder_by_key(g, idx))
 }
 
+fn find_cycle_from<T: Eq + Hash + Clone, U>(
+    g: &Graph<T, U>,
+    v: &T,
+    done: &mut Set<T>,
+    path: &mut Vec<T>,
+) -> Option<Vec<T>> {
+    if let Some(pos) = path.iter().position(|id| id == v) {
+        return Some(path[pos..].to_vec());
+    }
+    if done.contains(v) {
+        return None;
+    }
+    let vertex = g.iter().find(|n| &n.id == v)?;
+    path.push(v.clone());
+    for node_id in vertex.depends_on.iter() {
+        if let Some(cycle) = find_cycle_from(g, node_id, done, path) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    done.insert(v.clone());
+    None
+}
+
+/// Find a cyclic reference in a graph.
+/// Returns the nodes in the cycle in the order of dependencies (`[a, b]` means `a -> b -> a`).
+/// Dependencies not contained in the graph are ignored.
+pub fn find_cycle<T: Eq + Hash + Clone, U>(g: &Graph<T, U>) -> Option<Vec<T>> {
+    let mut done = Set::new();
+    for v in g.iter() {
+        if let Some(cycle) = find_cycle_from(g, &v.id, &mut done, &mut vec![]) {
+            return Some(cycle);
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,4 +187,22 @@ mod tests {
         assert_eq!(sorted[4], odd);
         Ok(())
     }
+
+    #[test]
+    fn test_find_cycle() {
+        // ```
+        // A = B + 1
+        // B = C
+        // C = A
+        // D = 1
+        // ```
+        let a = Node::new("A", (), set!["B"]);
+        let b = Node::new("B", (), set!["C"]);
+        let c = Node::new("C", (), set!["A"]);
+        let d = Node::new("D", (), set![]);
+        let g = vec![d.clone(), a, b, c];
+        assert_eq!(find_cycle(&g), Some(vec!["A", "B", "C"]));
+        let g = vec![d, Node::new("E", (), set!["D", "F"])];
+        assert_eq!(find_cycle(&g), None);
+    }
 }
@@ -180,6 +180,15 @@ impl HIRBuilder {
         self.check(artifact.ast, mode)
     }
 
+    /// Declare only the top-level names of the module without lowering their bodies.
+    /// Errors are discarded because they are reported by the subsequent (full) build of the module.
+    pub(crate) fn declare_toplevel(&mut self, src: String) {
+        let mut ast_builder = ASTBuilder::new(self.cfg().copy());
+        if let Ok(artifact) = ast_builder.build(src) {
+            self.lowerer.declare_toplevel(artifact.ast);
+        }
+    }
+
     pub fn build_module(&mut self) -> Result<CompleteArtifact, IncompleteArtifact> {
         let src = self.cfg_mut().input.read();
         self.build(src, "exec")
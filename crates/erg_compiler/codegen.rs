@@ -87,7 +87,7 @@ fn escape_name(name: &str, vis: &VisibilityModifier, def_line: u32, def_col: u32
     }
 }
 
-fn escape_ident(ident: Identifier) -> Str {
+pub(crate) fn escape_ident(ident: Identifier) -> Str {
     let vis = ident.vis();
     if &ident.inspect()[..] == "Self" {
         let Ok(ty) = <&Type>::try_from(ident.vi.t.singleton_value().unwrap()) else {
@@ -990,7 +990,9 @@ impl PyCodeGenerator {
                 if is_record {
                     a.ident.raw.vis = VisModifierSpec::Public(DOT);
                 }
-                if let Some(varname) = debind(&a.ident) {
+                if a.ident.is_newtype_base() {
+                    self.emit_expr(*a.obj);
+                } else if let Some(varname) = debind(&a.ident) {
                     a.ident.raw.vis = VisModifierSpec::Private;
                     a.ident.raw.name = VarName::from_str(varname);
                     self.emit_load_name_instr(a.ident);
@@ -1232,6 +1234,9 @@ impl PyCodeGenerator {
 
     fn emit_class_def(&mut self, class_def: ClassDef) {
         log!(info "entered {} ({})", fn_name!(), class_def.sig);
+        if class_def.obj.is_newtype() {
+            return self.emit_newtype_def(class_def);
+        }
         self.emit_push_null();
         let ident = class_def.sig.ident().clone();
         let require_or_sup = class_def.require_or_sup.clone().map(|x| *x);
@@ -1257,19 +1262,56 @@ impl PyCodeGenerator {
         self.stack_dec();
     }
 
+    /// UserId = Newtype Nat => UserId = Nat
+    /// Values of newtypes are represented by the base type, so methods are compiled like patch methods.
+    fn emit_newtype_def(&mut self, class_def: ClassDef) {
+        log!(info "entered {} ({})", fn_name!(), class_def.sig);
+        let ident = class_def.sig.ident().clone();
+        // `Newtype` always requires the base type
+        let Some(base) = class_def.require_or_sup else {
+            CompileError::compiler_bug(
+                0,
+                self.cfg.input.clone(),
+                class_def.loc(),
+                fn_name_full!(),
+                line!(),
+            )
+            .write_to_stderr();
+            return;
+        };
+        self.emit_expr(*base);
+        self.emit_store_instr(ident.clone(), Name);
+        let methods = class_def.methods.into_iter().filter_map(|expr| match expr {
+            Expr::Def(def) => Some(def),
+            _ => None,
+        });
+        self.emit_debound_methods(&ident, methods);
+    }
+
     fn emit_patch_def(&mut self, patch_def: PatchDef) {
         log!(info "entered {} ({})", fn_name!(), patch_def.sig);
-        for def in patch_def.methods {
+        let methods = patch_def.methods.into_iter().map(|def| {
+            let Expr::Def(def) = def else { todo!() };
+            def
+        });
+        self.emit_debound_methods(patch_def.sig.ident(), methods);
+    }
+
+    fn emit_debound_methods(
+        &mut self,
+        type_ident: &Identifier,
+        methods: impl Iterator<Item = Def>,
+    ) {
+        for mut def in methods {
             // Invert.
             //     invert self = ...
             // ↓
             // def Invert::invert(self): ...
-            let Expr::Def(mut def) = def else { todo!() };
             let namespace = self.cur_block_codeobj().name.trim_start_matches("::");
             let name = format!(
                 "{}{}{}",
                 namespace,
-                patch_def.sig.ident().to_string_notype(),
+                type_ident.to_string_notype(),
                 def.sig.ident().to_string_notype()
             );
             def.sig.ident_mut().raw.name = VarName::from_str(Str::from(name));
@@ -2319,11 +2361,24 @@ impl PyCodeGenerator {
     fn emit_call(&mut self, call: Call) {
         log!(info "entered {} ({call})", fn_name!());
         let init_stack_len = self.stack_len();
+        if call.is_newtype_ctor_call() {
+            let mut args = call.args;
+            self.emit_expr(args.remove(0));
+            debug_assert_eq!(self.stack_len(), init_stack_len + 1);
+            return;
+        }
         // Python cannot distinguish at compile time between a method call and a attribute call
         if let Some(attr_name) = call.attr_name {
             self.emit_call_method(*call.obj, attr_name, call.args);
         } else {
             match *call.obj {
+                // type arguments of user-defined polymorphic classes are erased (`Box(Int)` => `Box`)
+                other
+                    if other.ref_t().is_poly_type_meta()
+                        && other.var_info().is_some_and(|vi| vi.kind.is_defined()) =>
+                {
+                    self.emit_expr(other);
+                }
                 Expr::Accessor(Accessor::Ident(ident)) if ident.vis().is_private() => {
                     self.emit_call_local(ident, call.args)
                 }
@@ -0,0 +1,343 @@
+//! checks the API compatibility between two versions of a module.
+//!
+//! A change is considered compatible if the new type of each public symbol is a subtype of the old one.
+//! Note that this is not a full verification: type-compatible but semantically incompatible changes are possible.
+use std::fmt;
+
+use erg_common::dict::Dict;
+use erg_common::Str;
+
+use erg_parser::ast::VarName;
+
+use crate::context::{Context, ModuleContext};
+use crate::package::SemVer;
+use crate::ty::{HasType, ParamTy, Type};
+use crate::varinfo::VarInfo;
+
+/// The version increment required by API changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum VersionBump {
+    #[default]
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for VersionBump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Patch => write!(f, "patch"),
+            Self::Minor => write!(f, "minor"),
+            Self::Major => write!(f, "major"),
+        }
+    }
+}
+
+impl VersionBump {
+    /// The smallest version after `old` satisfying this bump.
+    ///
+    /// As in Cargo, the minor version of `0.y.z` is treated as the major version:
+    /// a major bump of `0.y.z` is `0.(y+1).0`, and a minor bump of `0.y.z` is `0.y.(z+1)`.
+    pub fn apply(&self, old: &SemVer) -> SemVer {
+        let (major, minor, patch) = match (self, old.major) {
+            (Self::None, _) => return old.clone(),
+            (Self::Patch, _) | (Self::Minor, 0) => (old.major, old.minor, old.patch + 1),
+            (Self::Minor, _) => (old.major, old.minor + 1, 0),
+            (Self::Major, 0) => (0, old.minor + 1, 0),
+            (Self::Major, _) => (old.major + 1, 0, 0),
+        };
+        SemVer {
+            major,
+            minor,
+            patch,
+            pre: None,
+            build: None,
+        }
+    }
+
+    /// Is `new` a valid next version of `old` for this bump?
+    pub fn is_satisfied_by(&self, old: &SemVer, new: &SemVer) -> bool {
+        match self {
+            Self::None => new >= old,
+            _ => new >= &self.apply(old),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiChange {
+    Added {
+        name: Str,
+        t: Type,
+    },
+    Removed {
+        name: Str,
+        t: Type,
+    },
+    /// a parameter does not accept some values that were accepted before
+    NarrowedParam {
+        name: Str,
+        param: Str,
+        old: Type,
+        new: Type,
+    },
+    /// a required parameter is renamed (this breaks the callers passing it as a keyword argument)
+    RenamedParam {
+        name: Str,
+        old: Str,
+        new: Str,
+    },
+    /// the return value can be of a type that was not returned before
+    WidenedReturn {
+        name: Str,
+        old: Type,
+        new: Type,
+    },
+    /// the new type is not a subtype of the old one (e.g. the number of parameters changed)
+    Incompatible {
+        name: Str,
+        old: Type,
+        new: Type,
+    },
+    /// the new type is a subtype of the old one
+    Compatible {
+        name: Str,
+        old: Type,
+        new: Type,
+    },
+}
+
+impl fmt::Display for ApiChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { name, t } => write!(f, "added `{name}: {t}`"),
+            Self::Removed { name, t } => write!(f, "removed `{name}: {t}`"),
+            Self::NarrowedParam {
+                name,
+                param,
+                old,
+                new,
+            } => write!(
+                f,
+                "the parameter `{param}` of `{name}` is narrowed: {old} -> {new}"
+            ),
+            Self::RenamedParam { name, old, new } => {
+                write!(f, "the parameter `{old}` of `{name}` is renamed to `{new}`")
+            }
+            Self::WidenedReturn { name, old, new } => {
+                write!(f, "the return type of `{name}` is widened: {old} -> {new}")
+            }
+            Self::Incompatible { name, old, new } => {
+                write!(f, "`{name}` is changed incompatibly: {old} -> {new}")
+            }
+            Self::Compatible { name, old, new } => {
+                write!(f, "`{name}` is changed compatibly: {old} -> {new}")
+            }
+        }
+    }
+}
+
+impl ApiChange {
+    pub fn name(&self) -> &Str {
+        match self {
+            Self::Added { name, .. }
+            | Self::Removed { name, .. }
+            | Self::NarrowedParam { name, .. }
+            | Self::RenamedParam { name, .. }
+            | Self::WidenedReturn { name, .. }
+            | Self::Incompatible { name, .. }
+            | Self::Compatible { name, .. } => name,
+        }
+    }
+
+    pub const fn bump(&self) -> VersionBump {
+        match self {
+            Self::Added { .. } | Self::Compatible { .. } => VersionBump::Minor,
+            _ => VersionBump::Major,
+        }
+    }
+
+    pub const fn is_breaking(&self) -> bool {
+        matches!(self.bump(), VersionBump::Major)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompatReport {
+    /// sorted by the symbol names
+    pub changes: Vec<ApiChange>,
+}
+
+impl fmt::Display for CompatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in self.changes.iter() {
+            let mark = if change.is_breaking() { "!" } else { "+" };
+            writeln!(f, "{mark} {change}")?;
+        }
+        write!(f, "required version bump: {}", self.required_bump())
+    }
+}
+
+impl CompatReport {
+    /// `VersionBump::Patch` if there is no API change.
+    pub fn required_bump(&self) -> VersionBump {
+        self.changes
+            .iter()
+            .map(|change| change.bump())
+            .max()
+            .unwrap_or(VersionBump::Patch)
+    }
+
+    pub fn is_compatible(&self) -> bool {
+        self.changes.iter().all(|change| !change.is_breaking())
+    }
+}
+
+/// The public attributes of the module (`C.attr` for the attributes of class `C`).
+fn public_api(module: &ModuleContext) -> Dict<Str, Type> {
+    fn publics<'a>(
+        vars: impl Iterator<Item = (&'a VarName, &'a VarInfo)>,
+    ) -> impl Iterator<Item = (&'a Str, &'a VarInfo)> {
+        vars.filter(|(_, vi)| vi.vis.is_public())
+            .map(|(name, vi)| (name.inspect(), vi))
+    }
+    let ctx = &module.context;
+    let mut api = Dict::new();
+    for (name, vi) in publics(ctx.locals.iter().chain(ctx.decls.iter())) {
+        api.insert(name.clone(), vi.t.clone());
+    }
+    for (name, (_, class_ctx)) in ctx.mono_types.iter().chain(ctx.poly_types.iter()) {
+        if !api.contains_key(name.inspect()) {
+            continue;
+        }
+        for (_, methods) in class_ctx.methods_list.iter() {
+            for (attr, vi) in publics(methods.locals.iter().chain(methods.decls.iter())) {
+                api.insert(Str::from(format!("{name}.{attr}")), vi.ref_t().clone());
+            }
+        }
+    }
+    api
+}
+
+fn param_name(param: &ParamTy, idx: usize) -> Str {
+    param
+        .name()
+        .cloned()
+        .unwrap_or_else(|| Str::from(format!("#{idx}")))
+}
+
+/// Compares the old and new types of a subroutine parameter by parameter.
+fn compare_subr(ctx: &Context, name: &Str, old: &Type, new: &Type) -> Vec<ApiChange> {
+    let incompatible = || ApiChange::Incompatible {
+        name: name.clone(),
+        old: old.clone(),
+        new: new.clone(),
+    };
+    let (Some(old_nd), Some(new_nd), Some(old_d), Some(new_d)) = (
+        old.non_default_params(),
+        new.non_default_params(),
+        old.default_params(),
+        new.default_params(),
+    ) else {
+        return vec![incompatible()];
+    };
+    // adding/removing required parameters breaks the callers
+    if old_nd.len() != new_nd.len() || (old.var_params().is_some() && new.var_params().is_none()) {
+        return vec![incompatible()];
+    }
+    let mut changes = vec![];
+    for (old_p, new_p) in old_nd.iter().zip(new_nd.iter()) {
+        if let (Some(old_name), Some(new_name)) = (old_p.name(), new_p.name()) {
+            if old_name != new_name {
+                changes.push(ApiChange::RenamedParam {
+                    name: name.clone(),
+                    old: old_name.clone(),
+                    new: new_name.clone(),
+                });
+            }
+        }
+    }
+    let mut check_param = |idx: usize, old_p: &ParamTy, new_p: &ParamTy| {
+        if !ctx.subtype_of(old_p.typ(), new_p.typ()) {
+            changes.push(ApiChange::NarrowedParam {
+                name: name.clone(),
+                param: param_name(old_p, idx),
+                old: old_p.typ().clone(),
+                new: new_p.typ().clone(),
+            });
+        }
+    };
+    for (idx, (old_p, new_p)) in old_nd.iter().zip(new_nd.iter()).enumerate() {
+        check_param(idx, old_p, new_p);
+    }
+    for (idx, old_p) in old_d.iter().enumerate() {
+        let Some(new_p) = new_d.iter().find(|new_p| new_p.name() == old_p.name()) else {
+            return vec![incompatible()];
+        };
+        check_param(old_nd.len() + idx, old_p, new_p);
+    }
+    if let (Some(old_ret), Some(new_ret)) = (old.return_t(), new.return_t()) {
+        if !ctx.subtype_of(new_ret, old_ret) {
+            changes.push(ApiChange::WidenedReturn {
+                name: name.clone(),
+                old: old_ret.clone(),
+                new: new_ret.clone(),
+            });
+        }
+    }
+    changes
+}
+
+/// Reports the API changes from `old` to `new`.
+/// The types are compared in the context of `new`.
+pub fn check_api_compat(old: &ModuleContext, new: &ModuleContext) -> CompatReport {
+    let ctx = &new.context;
+    let old_api = public_api(old);
+    let new_api = public_api(new);
+    let mut changes = vec![];
+    for (name, old_t) in old_api.iter() {
+        let Some(new_t) = new_api.get(name) else {
+            changes.push(ApiChange::Removed {
+                name: name.clone(),
+                t: old_t.clone(),
+            });
+            continue;
+        };
+        // this is checked first, since parameter renames are not detected by `same_type_of`
+        if old_t.is_subr() && new_t.is_subr() {
+            let breaking = compare_subr(ctx, name, old_t, new_t);
+            if !breaking.is_empty() {
+                changes.extend(breaking);
+                continue;
+            }
+        }
+        if ctx.same_type_of(old_t, new_t) {
+            continue;
+        }
+        if ctx.subtype_of(new_t, old_t) {
+            changes.push(ApiChange::Compatible {
+                name: name.clone(),
+                old: old_t.clone(),
+                new: new_t.clone(),
+            });
+        } else {
+            changes.push(ApiChange::Incompatible {
+                name: name.clone(),
+                old: old_t.clone(),
+                new: new_t.clone(),
+            });
+        }
+    }
+    for (name, new_t) in new_api.iter() {
+        if !old_api.contains_key(name) {
+            changes.push(ApiChange::Added {
+                name: name.clone(),
+                t: new_t.clone(),
+            });
+        }
+    }
+    changes.sort_by(|l, r| l.name()[..].cmp(&r.name()[..]));
+    CompatReport { changes }
+}
@@ -5,9 +5,9 @@ use std::path::Path;
 
 use erg_common::config::ErgConfig;
 use erg_common::dict::Dict;
-use erg_common::error::MultiErrorDisplay;
-use erg_common::log;
-use erg_common::traits::{ExitStatus, Runnable, Stream};
+use erg_common::error::{Location, MultiErrorDisplay};
+use erg_common::traits::{ExitStatus, Locational, Runnable, Stream};
+use erg_common::{log, Str};
 use erg_parser::ast::VarName;
 
 use crate::artifact::{CompleteArtifact, ErrorArtifact};
@@ -16,10 +16,10 @@ use crate::optimize::HIROptimizer;
 use crate::ty::codeobj::CodeObj;
 
 use crate::build_hir::HIRBuilder;
-use crate::codegen::PyCodeGenerator;
+use crate::codegen::{escape_ident, PyCodeGenerator};
 use crate::desugar_hir::HIRDesugarer;
 use crate::error::{CompileError, CompileErrors, CompileWarnings};
-use crate::hir::Expr;
+use crate::hir::{Expr, Signature, HIR};
 use crate::link_hir::HIRLinker;
 use crate::module::SharedCompilerResource;
 use crate::varinfo::VarInfo;
@@ -109,6 +109,37 @@ impl AccessKind {
     }
 }
 
+/// A top-level subroutine decorated with `@Test`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: Str,
+    /// the name of the function in the compiled code
+    pub py_name: Str,
+    pub loc: Location,
+    /// the number of parameters (tests with parameters are not runnable)
+    pub params: usize,
+}
+
+impl TestCase {
+    fn collect(hir: &HIR) -> Vec<Self> {
+        hir.module
+            .iter()
+            .filter_map(|chunk| match chunk {
+                Expr::Def(def) => match &def.sig {
+                    Signature::Subr(sig) if sig.ident.vi.is_test() => Some(Self {
+                        name: sig.ident.inspect().clone(),
+                        py_name: escape_ident(sig.ident.clone()),
+                        loc: sig.ident.loc(),
+                        params: sig.params.len(),
+                    }),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+}
+
 /// Generates a `CodeObj` from an String or other File inputs.
 #[derive(Debug)]
 pub struct Compiler {
@@ -131,12 +162,7 @@ impl Runnable for Compiler {
 
     fn new(cfg: ErgConfig) -> Self {
         let shared = SharedCompilerResource::new(cfg.copy());
-        Self {
-            shared: shared.clone(),
-            builder: HIRBuilder::new_with_cache(cfg.copy(), "<module>", shared),
-            code_generator: PyCodeGenerator::new(cfg.copy()),
-            cfg,
-        }
+        Self::new_with_cache(cfg, shared)
     }
 
     #[inline]
@@ -200,6 +226,15 @@ impl ContextProvider for Compiler {
 }
 
 impl Compiler {
+    pub fn new_with_cache(cfg: ErgConfig, shared: SharedCompilerResource) -> Self {
+        Self {
+            shared: shared.clone(),
+            builder: HIRBuilder::new_with_cache(cfg.copy(), "<module>", shared),
+            code_generator: PyCodeGenerator::new(cfg.copy()),
+            cfg,
+        }
+    }
+
     pub fn compile_and_dump_as_pyc<P: AsRef<Path>>(
         &mut self,
         pyc_path: P,
@@ -239,6 +274,17 @@ impl Compiler {
         Ok(CompleteArtifact::new(codeobj, arti.warns))
     }
 
+    /// Compiles the module and collects the `@Test` subroutines in it.
+    pub fn compile_tests(
+        &mut self,
+        src: String,
+    ) -> Result<CompleteArtifact<(CodeObj, Vec<TestCase>)>, ErrorArtifact> {
+        let arti = self.build_link_desugar_optimize(src, "exec")?;
+        let tests = TestCase::collect(&arti.object);
+        let codeobj = self.code_generator.emit(arti.object);
+        Ok(CompleteArtifact::new((codeobj, tests), arti.warns))
+    }
+
     pub fn compile_module(&mut self) -> Result<CompleteArtifact<CodeObj>, ErrorArtifact> {
         let src = self.cfg.input.read();
         self.compile(src, "exec")
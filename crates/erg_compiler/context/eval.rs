@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::mem;
 use std::ops::Drop;
 
@@ -93,6 +94,62 @@ fn op_to_name(op: OpKind) -> &'static str {
     }
 }
 
+/// The maximum nesting depth of user-defined compile-time function calls
+const MAX_CONST_CALL_DEPTH: usize = 128;
+/// The maximum number of user-defined compile-time function calls in one evaluation
+const MAX_CONST_CALL_STEPS: usize = 10_000;
+
+thread_local! {
+    /// (depth, steps) of the compile-time function calls currently being evaluated
+    static CONST_CALL_STATE: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+}
+
+/// Counts the depth and the steps of user-defined compile-time function calls.
+/// The depth is restored when the guard is dropped, the steps are reset at the outermost call.
+struct ConstCallGuard;
+
+impl Drop for ConstCallGuard {
+    fn drop(&mut self) {
+        CONST_CALL_STATE.with(|state| {
+            let (depth, steps) = state.get();
+            state.set((depth.saturating_sub(1), steps));
+        });
+    }
+}
+
+impl ConstCallGuard {
+    fn enter(ctx: &Context, name: &str, loc: Location) -> EvalResult<Self> {
+        let (depth, steps) = CONST_CALL_STATE.with(|state| {
+            let (depth, steps) = state.get();
+            let steps = if depth == 0 { 1 } else { steps + 1 };
+            state.set((depth + 1, steps));
+            (depth + 1, steps)
+        });
+        let guard = Self;
+        if depth > MAX_CONST_CALL_DEPTH {
+            Err(EvalErrors::from(EvalError::const_recursion_limit_error(
+                ctx.cfg.input.clone(),
+                line!() as usize,
+                loc,
+                ctx.caused_by(),
+                name,
+                MAX_CONST_CALL_DEPTH,
+            )))
+        } else if steps > MAX_CONST_CALL_STEPS {
+            Err(EvalErrors::from(EvalError::const_step_limit_error(
+                ctx.cfg.input.clone(),
+                line!() as usize,
+                loc,
+                ctx.caused_by(),
+                name,
+                MAX_CONST_CALL_STEPS,
+            )))
+        } else {
+            Ok(guard)
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct UndoableLinkedList {
     tys: Shared<Vec<Type>>, // not Set
@@ -549,8 +606,20 @@ impl Context {
     }
 
     fn tp_eval_const_call(&self, call: &Call) -> EvalResult<TyParam> {
+        // TODO: eval method call
+        if call.attr_name.is_some() {
+            return Err(EvalErrors::from(EvalError::not_const_expr(
+                self.cfg.input.clone(),
+                line!() as usize,
+                call.loc(),
+                self.caused_by(),
+            )));
+        }
         if let Expr::Accessor(acc) = call.obj.as_ref() {
             match acc {
+                Accessor::Ident(ident) if &ident.inspect()[..] == "if" => {
+                    self.tp_eval_const_if(call)
+                }
                 Accessor::Ident(ident) => {
                     let obj = self.rec_get_const_obj(ident.inspect()).ok_or_else(|| {
                         EvalError::no_var_error(
@@ -562,6 +631,11 @@ impl Context {
                             self.get_similar_name(ident.inspect()),
                         )
                     })?;
+                    if let ValueObj::Type(TypeObj::Generated(gen)) = obj {
+                        if !gen.typ().is_monomorphic() {
+                            return self.tp_eval_type_app(gen, call);
+                        }
+                    }
                     let subr = option_enum_unwrap!(obj, ValueObj::Subr)
                         .ok_or_else(|| {
                             EvalError::type_mismatch_error(
@@ -613,9 +687,95 @@ impl Context {
         }
     }
 
+    /// If `call` is an application of a user-defined polymorphic class (e.g. `Box(Int)`), evaluate it.
+    pub(crate) fn eval_poly_type_app(&self, call: &Call) -> Option<TypeObj> {
+        if call.attr_name.is_some() {
+            return None;
+        }
+        let Expr::Accessor(Accessor::Ident(ident)) = call.obj.as_ref() else {
+            return None;
+        };
+        let Some(ValueObj::Type(TypeObj::Generated(gen))) = self.rec_get_const_obj(ident.inspect())
+        else {
+            return None;
+        };
+        if gen.typ().is_monomorphic() {
+            return None;
+        }
+        match self.tp_eval_type_app(gen, call) {
+            Ok(TyParam::Value(ValueObj::Type(t))) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// `Box(Int)` (`Box T = Class { .v = T }`) => `Box(Int)`
+    fn tp_eval_type_app(&self, gen: &GenTypeObj, call: &Call) -> EvalResult<TyParam> {
+        let args = self.eval_args(&call.args)?;
+        if !args.kw_args.is_empty() || args.pos_args.len() != gen.typ().typarams_len().unwrap_or(0)
+        {
+            return Err(EvalErrors::from(EvalError::not_const_expr(
+                self.cfg.input.clone(),
+                line!() as usize,
+                call.loc(),
+                self.caused_by(),
+            )));
+        }
+        let typarams = args
+            .pos_args
+            .into_iter()
+            .map(|arg| match arg {
+                ValueObj::Type(t) => TyParam::t(t.into_typ()),
+                other => TyParam::value(other),
+            })
+            .collect();
+        let mut gen = gen.clone();
+        *gen.typ_mut() = poly(gen.typ().qual_name(), typarams);
+        Ok(TyParam::Value(ValueObj::gen_t(gen)))
+    }
+
+    /// `if` is not a constant function, but branches are evaluated lazily
+    /// so that recursive compile-time functions can terminate.
+    fn tp_eval_const_if(&self, call: &Call) -> EvalResult<TyParam> {
+        let not_const = || {
+            EvalErrors::from(EvalError::not_const_expr(
+                self.cfg.input.clone(),
+                line!() as usize,
+                call.loc(),
+                self.caused_by(),
+            ))
+        };
+        let cond = call.args.get_left_or_key("cond").ok_or_else(not_const)?;
+        let ValueObj::Bool(cond) = self.eval_const_expr(cond)? else {
+            return Err(not_const());
+        };
+        let branch = if cond {
+            call.args.nth_or_key(1, "then")
+        } else {
+            call.args.nth_or_key(2, "else")
+        };
+        match branch {
+            Some(Expr::Lambda(lambda)) if lambda.sig.params.is_empty() => {
+                // HACK: should avoid cloning
+                let mut branch_ctx = Context::instant(
+                    Str::ever("<lambda>"),
+                    self.cfg.clone(),
+                    0,
+                    self.shared.clone(),
+                    self.clone(),
+                );
+                branch_ctx
+                    .eval_const_block(&lambda.body)
+                    .map(TyParam::value)
+            }
+            Some(other) => self.eval_const_expr(other).map(TyParam::value),
+            None => Ok(TyParam::value(ValueObj::None)),
+        }
+    }
+
     fn call(&self, subr: ConstSubr, args: ValueArgs, loc: Location) -> EvalResult<TyParam> {
         match subr {
             ConstSubr::User(user) => {
+                let _guard = ConstCallGuard::enter(self, &user.name, loc)?;
                 // HACK: should avoid cloning
                 let mut subr_ctx = Context::instant(
                     user.name.clone(),
@@ -624,17 +784,63 @@ impl Context {
                     self.shared.clone(),
                     self.clone(),
                 );
-                // TODO: var_args
-                for (arg, sig) in args
-                    .pos_args
-                    .into_iter()
-                    .zip(user.params.non_defaults.iter())
-                {
-                    let name = VarName::from_str(sig.inspect().unwrap().clone());
-                    subr_ctx.consts.insert(name, arg);
+                let params_len = user.params.len();
+                let (pos_args_len, kw_args_len) = (args.pos_args.len(), args.kw_args.len());
+                let mut pos_args = args.pos_args.into_iter();
+                let mut kw_args = args.kw_args;
+                let mut missing = vec![];
+                for sig in user.params.non_defaults.iter() {
+                    let Some(name) = sig.inspect() else {
+                        pos_args.next();
+                        continue;
+                    };
+                    if let Some(arg) = pos_args.next().or_else(|| kw_args.remove(name)) {
+                        subr_ctx.consts.insert(VarName::from_str(name.clone()), arg);
+                    } else {
+                        missing.push(name.clone());
+                    }
+                }
+                if !missing.is_empty() {
+                    return Err(EvalErrors::from(EvalError::args_missing_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        loc,
+                        &user.name,
+                        self.caused_by(),
+                        missing,
+                    )));
                 }
-                for (name, arg) in args.kw_args.into_iter() {
-                    subr_ctx.consts.insert(VarName::from_str(name), arg);
+                if let Some(var_params) = user.params.var_params.as_ref() {
+                    let rest =
+                        ValueObj::Array(ArcArray::from(pos_args.by_ref().collect::<Vec<_>>()));
+                    if let Some(name) = var_params.inspect() {
+                        subr_ctx
+                            .consts
+                            .insert(VarName::from_str(name.clone()), rest);
+                    }
+                }
+                for sig in user.params.defaults.iter() {
+                    let Some(name) = sig.sig.inspect() else {
+                        pos_args.next();
+                        continue;
+                    };
+                    let arg = match pos_args.next().or_else(|| kw_args.remove(name)) {
+                        Some(arg) => arg,
+                        None => self.eval_const_expr(&sig.default_val)?,
+                    };
+                    subr_ctx.consts.insert(VarName::from_str(name.clone()), arg);
+                }
+                if pos_args.next().is_some() || !kw_args.is_empty() {
+                    return Err(EvalErrors::from(EvalError::too_many_args_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        loc,
+                        &user.name,
+                        self.caused_by(),
+                        params_len,
+                        pos_args_len,
+                        kw_args_len,
+                    )));
                 }
                 subr_ctx.eval_const_block(&user.block()).map(TyParam::value)
             }
@@ -1315,6 +1521,10 @@ impl Context {
                     .eval_pred_func(value_args.remove(0))
                     .map(TyParam::Value),
                 _ => {
+                    if let Some(ValueObj::Subr(subr)) = self.rec_get_const_obj(&name) {
+                        let args = ValueArgs::new(value_args, dict! {});
+                        return self.call(subr.clone(), args, Location::Unknown);
+                    }
                     log!(err "eval_app({name}({}))", fmt_vec(&args));
                     Ok(TyParam::app(name, args))
                 }
@@ -634,7 +634,15 @@ impl<'c, 'q, 'l, L: Locational> Dereferencer<'c, 'q, 'l, L> {
                     )
                 })?;
                 let variances = ctx.type_params_variance();
-                for (param, variance) in params.iter_mut().zip(variances.into_iter()) {
+                let phantoms = ctx.newtype_phantom_params();
+                for (i, (param, variance)) in
+                    params.iter_mut().zip(variances.into_iter()).enumerate()
+                {
+                    // phantom parameters of newtypes are invariant, so the type variables cannot be eliminated
+                    // e.g. `|U|(l: Length(U)) -> Nat` (`Length Unit = Newtype Nat`)
+                    if phantoms.get(i) == Some(&true) && param.has_qvar() {
+                        continue;
+                    }
                     self.push_variance(variance);
                     *param = self.deref_tp(mem::take(param))?;
                     self.pop_variance();
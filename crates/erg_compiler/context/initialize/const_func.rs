@@ -150,6 +150,18 @@ pub(crate) fn patch_func(mut args: ValueArgs, ctx: &Context) -> EvalValueResult<
     Ok(ValueObj::gen_t(GenTypeObj::patch(t, base, impls)).into())
 }
 
+/// Base: Type -> ClassType
+pub(crate) fn newtype_func(mut args: ValueArgs, ctx: &Context) -> EvalValueResult<TyParam> {
+    let base = args
+        .remove_left_or_key("Base")
+        .ok_or_else(|| not_passed("Base"))?;
+    let Some(base) = base.as_type(ctx) else {
+        return Err(type_mismatch("type", base, "Base"));
+    };
+    let t = mono(ctx.name.clone());
+    Ok(ValueObj::gen_t(GenTypeObj::newtype(t, base)).into())
+}
+
 /// Super: TraitType, Impl := Type, Additional := Type -> TraitType
 pub(crate) fn subsume_func(mut args: ValueArgs, ctx: &Context) -> EvalValueResult<TyParam> {
     let sup = args
@@ -551,6 +551,14 @@ impl Context {
             None,
         ));
         self.register_builtin_const(INHERIT, vis.clone(), ValueObj::Subr(inherit));
+        let newtype_t = func(vec![kw(KW_BASE_TYPE, Type)], None, vec![], ClassType);
+        let newtype = ConstSubr::Builtin(BuiltinConstSubr::new(
+            NEWTYPE,
+            newtype_func,
+            newtype_t,
+            None,
+        ));
+        self.register_builtin_const(NEWTYPE, vis.clone(), ValueObj::Subr(newtype));
         let trait_t = func(
             vec![kw(KW_REQUIREMENT, Type)],
             None,
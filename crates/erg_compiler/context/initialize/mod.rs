@@ -377,6 +377,7 @@ const SUBSUME: &str = "Subsume";
 const INHERIT: &str = "Inherit";
 const INHERITABLE: &str = "Inheritable";
 const DEL: &str = "Del";
+const NEWTYPE: &str = "Newtype";
 const PATCH: &str = "Patch";
 const STRUCTURAL: &str = "Structural";
 const KEYS: &str = "keys";
@@ -534,6 +535,7 @@ const KW_CODE: &str = "code";
 const KW_STOP: &str = "stop";
 const KW_STEP: &str = "step";
 const KW_REQUIREMENT: &str = "Requirement";
+const KW_BASE_TYPE: &str = "Base";
 const KW_IMPL: &str = "Impl";
 const KW_ADDITIONAL: &str = "Additional";
 const KW_SUPER: &str = "Super";
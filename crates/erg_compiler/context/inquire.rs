@@ -61,7 +61,7 @@ impl Context {
         if self.shared.is_some()
             && self.promises().is_registered(path)
             && !self.promises().is_finished(path)
-            && (self.mod_cache().get(path).is_none() && self.py_mod_cache().get(path).is_none())
+            && !self.is_declared_cyclic_mod(path)
         {
             let _result = self.promises().join(path);
         }
@@ -71,6 +71,12 @@ impl Context {
             .map(|mod_ctx| &mod_ctx.context)
     }
 
+    /// Modules in an import cycle are not joined. Only their declarations (registered in the first phase) are available.
+    fn is_declared_cyclic_mod(&self, path: &Path) -> bool {
+        (self.mod_cache().get(path).is_some() || self.py_mod_cache().get(path).is_some())
+            && self.graph().ancestors(path).contains(self.module_path())
+    }
+
     pub(crate) fn get_current_scope_var(&self, name: &VarName) -> Option<&VarInfo> {
         #[cfg(feature = "py_compat")]
         let search_name = self
@@ -193,6 +199,28 @@ impl Context {
                 Ok(ctxs)
             }
             hir::Expr::TypeAsc(tasc) => self.get_singular_ctxs_by_hir_expr(&tasc.expr, namespace),
+            // e.g. `Box(Int)`: {Box(Int)}
+            hir::Expr::Call(call) => {
+                if let Type::Refinement(refine) = call.ref_t() {
+                    if let Predicate::Equal { rhs, .. } = refine.pred.as_ref() {
+                        if let Some(t) =
+                            <&Type>::try_from(rhs).ok().filter(|t| !t.has_unbound_var())
+                        {
+                            if let Some(ctxs) = self.get_nominal_super_type_ctxs(t) {
+                                return Ok(ctxs);
+                            }
+                        }
+                    }
+                }
+                Err(TyCheckError::no_var_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    obj.loc(),
+                    self.caused_by(),
+                    &obj.to_string(),
+                    None,
+                ))
+            }
             // TODO: change error
             _ => Err(TyCheckError::no_var_error(
                 self.cfg.input.clone(),
@@ -334,6 +362,74 @@ impl Context {
             .map(|(_, ctx)| ctx)
     }
 
+    /// Variants of a GADT, i.e. subclasses of the polymorphic class `t` defined in the same module as `t`.
+    /// e.g. `IntLit = Inherit Expr(Int)` is a variant of `Expr(T)`
+    fn get_gadt_variants(&self, t: &Type) -> Vec<&Type> {
+        let Some((class, _)) = self.get_nominal_type_ctx(t) else {
+            return vec![];
+        };
+        if class.is_monomorphic() {
+            return vec![];
+        }
+        // `t` may be imported (e.g. `ast.Expr(T)`)
+        let Some(module) = self
+            .get_namespace(&class.namespace())
+            .or_else(|| self.get_module())
+        else {
+            return vec![];
+        };
+        module
+            .mono_types
+            .values()
+            .filter(|(_, ctx)| {
+                ctx.super_classes
+                    .iter()
+                    .any(|sup| sup.qual_name() == class.qual_name())
+            })
+            .map(|(variant, _)| variant)
+            .collect()
+    }
+
+    /// `match (e: Expr(T)): (i: IntLit) -> ...; (s: StrLit) -> ...` is exhaustive
+    /// if `IntLit` and `StrLit` are all the variants of `Expr(T)`.
+    /// Variants that cannot be a `target` (e.g. `StrLit` for `Expr(Int)`) need not be covered.
+    fn covers_gadt_variants(&self, target: &Type, union_pat_t: &Type) -> bool {
+        let variants = self.get_gadt_variants(target);
+        !variants.is_empty()
+            && variants.into_iter().all(|variant| {
+                !self.subtype_of(variant, target) || self.subtype_of(variant, union_pat_t)
+            })
+    }
+
+    /// Type variables of the match target refined by the pattern of an arm.
+    /// e.g. target: `Expr(T)`, pattern: `IntLit` (<: `Expr(Int)`) => `[(T, Int)]`
+    pub(crate) fn get_gadt_refinements(&self, target: &Type, pat: &Type) -> Vec<(Type, Type)> {
+        let Some((class, _)) = self.get_nominal_type_ctx(target) else {
+            return vec![];
+        };
+        if class.is_monomorphic() {
+            return vec![];
+        }
+        let Some(sup) = self
+            .get_super_classes(pat)
+            .and_then(|mut sups| sups.find(|sup| sup.qual_name() == class.qual_name()))
+        else {
+            return vec![];
+        };
+        let target_params = target.typarams();
+        let sup_params = sup.typarams();
+        target_params
+            .iter()
+            .zip(sup_params.iter())
+            .filter_map(|(var, refined)| {
+                let var = <&Type>::try_from(var).ok()?;
+                let refined = <&Type>::try_from(refined).ok()?;
+                (var.is_unbound_var() && !refined.has_qvar())
+                    .then(|| (var.clone(), refined.clone()))
+            })
+            .collect()
+    }
+
     fn get_match_call_t(
         &self,
         kind: SubrKind,
@@ -408,18 +504,22 @@ impl Context {
         // NG: expr_t: Nat, union_pat_t: {1, 2}
         // OK: expr_t: Int, union_pat_t: {1} or 'T
         if let Err(err) = self.sub_unify(match_target_expr_t, &union_pat_t, &pos_args[0], None) {
-            if cfg!(feature = "debug") {
-                eprintln!("match error: {err}");
+            if self.covers_gadt_variants(match_target_expr_t, &union_pat_t) {
+                log!(info "all the variants of {match_target_expr_t} are covered");
+            } else {
+                if cfg!(feature = "debug") {
+                    eprintln!("match error: {err}");
+                }
+                return Err(TyCheckErrors::from(TyCheckError::match_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    pos_args[0].loc(),
+                    self.caused_by(),
+                    match_target_expr_t,
+                    &union_pat_t,
+                    arm_ts,
+                )));
             }
-            return Err(TyCheckErrors::from(TyCheckError::match_error(
-                self.cfg.input.clone(),
-                line!() as usize,
-                pos_args[0].loc(),
-                self.caused_by(),
-                match_target_expr_t,
-                &union_pat_t,
-                arm_ts,
-            )));
         }
         let branch_ts = pos_args
             .iter()
@@ -636,7 +736,7 @@ impl Context {
             for ctx in singular_ctxs {
                 match ctx.rec_get_var_info(ident, AccessKind::UnboundAttr, input, namespace) {
                     Triple::Ok(vi) => {
-                        return Triple::Ok(vi);
+                        return Triple::Ok(self.substitute_singular_attr(obj, vi));
                     }
                     Triple::Err(e) => {
                         return Triple::Err(e);
@@ -707,6 +807,46 @@ impl Context {
         Triple::None
     }
 
+    /// `Box(Int).new: |T|({.v = T}) -> Box(T)` => `({.v = Int}) -> Box(Int)`
+    fn substitute_singular_attr(&self, obj: &hir::Expr, vi: VarInfo) -> VarInfo {
+        let Type::Refinement(refine) = obj.ref_t() else {
+            return vi;
+        };
+        let Predicate::Equal { rhs, .. } = refine.pred.as_ref() else {
+            return vi;
+        };
+        let Ok(t) = <&Type>::try_from(rhs) else {
+            return vi;
+        };
+        self.substitute_poly_class_attr(t, vi)
+    }
+
+    /// `(b: Box(Int)).v: T` => `Int`
+    fn substitute_poly_class_attr(&self, t: &Type, mut vi: VarInfo) -> VarInfo {
+        let Some((class, _)) = self.get_nominal_type_ctx(t) else {
+            return vi;
+        };
+        if class.is_monomorphic() || !vi.t.has_qvar() {
+            return vi;
+        }
+        let quantified = vi.t.is_quantified_subr();
+        let mut attr_t = match vi.t {
+            Type::Quantified(quant) => *quant,
+            other => other,
+        };
+        for (param, arg) in class.typarams().iter().zip(t.typarams().iter()) {
+            if let (Ok(param), Ok(arg)) = (<&Type>::try_from(param), <&Type>::try_from(arg)) {
+                attr_t = attr_t.replace(param, arg);
+            }
+        }
+        vi.t = if quantified && attr_t.has_qvar() {
+            attr_t.quantify()
+        } else {
+            attr_t
+        };
+        vi
+    }
+
     fn get_bound_attr_from_nominal_t(
         &self,
         obj: &hir::Expr,
@@ -719,7 +859,7 @@ impl Context {
             for ctx in sups {
                 match ctx.rec_get_var_info(ident, AccessKind::BoundAttr, input, namespace) {
                     Triple::Ok(vi) => {
-                        return Triple::Ok(vi);
+                        return Triple::Ok(self.substitute_poly_class_attr(&self_t, vi));
                     }
                     Triple::Err(e) => {
                         return Triple::Err(e);
@@ -1076,7 +1216,7 @@ impl Context {
                     .or_else(|| ctx.decls.get(attr_name.inspect()))
                 {
                     self.validate_visibility(attr_name, vi, input, namespace)?;
-                    return Ok(vi.clone());
+                    return Ok(self.substitute_singular_attr(obj, vi.clone()));
                 }
                 for (_, method_ctx) in ctx.methods_list.iter() {
                     if let Some(vi) = method_ctx
@@ -1085,7 +1225,7 @@ impl Context {
                         .or_else(|| method_ctx.decls.get(attr_name.inspect()))
                     {
                         self.validate_visibility(attr_name, vi, input, namespace)?;
-                        return Ok(vi.clone());
+                        return Ok(self.substitute_singular_attr(obj, vi.clone()));
                     }
                 }
             }
@@ -2163,6 +2303,31 @@ impl Context {
             .collect()
     }
 
+    /// Returns whether each type parameter of the newtype is a phantom parameter (does not appear in the base type).
+    /// e.g. `Length Unit = Newtype Nat` -> `vec![true]`
+    /// Returns an empty vector if `self` is not a newtype.
+    pub(crate) fn newtype_phantom_params(&self) -> Vec<bool> {
+        let base = VarName::from_static("base");
+        let Some(base) = self.methods_list.iter().find_map(|(_, methods)| {
+            methods.get_current_scope_var(&base).filter(|vi| {
+                vi.py_name
+                    .as_ref()
+                    .is_some_and(|py_name| py_name == hir::NEWTYPE_BASE)
+            })
+        }) else {
+            return vec![];
+        };
+        let qvars = base.t.qvars();
+        self.params
+            .iter()
+            .map(|(opt_name, _)| {
+                opt_name
+                    .as_ref()
+                    .is_some_and(|name| !qvars.iter().any(|(qvar, _)| qvar == name.inspect()))
+            })
+            .collect()
+    }
+
     /// Perform types linearization.
     /// TODO: Current implementation may be very inefficient.
     ///
@@ -2630,9 +2795,17 @@ impl Context {
             return None;
         };
         if mod_t.is_erg_module() {
-            self.cfg.input.resolve_path(Path::new(&path[..]))
+            self.cfg.input.resolve_path(
+                Path::new(&path[..]),
+                &self.cfg.packages,
+                &self.cfg.search_paths,
+            )
         } else if mod_t.is_py_module() {
-            self.cfg.input.resolve_decl_path(Path::new(&path[..]))
+            self.cfg.input.resolve_decl_path(
+                Path::new(&path[..]),
+                &self.cfg.packages,
+                &self.cfg.search_paths,
+            )
         } else {
             None
         }
@@ -2713,7 +2886,10 @@ impl Context {
             str_namespace.push_str(namespaces.remove(0));
         }
         let path = Path::new(&str_namespace);
-        let mut path = self.cfg.input.resolve_path(path)?;
+        let mut path =
+            self.cfg
+                .input
+                .resolve_path(path, &self.cfg.packages, &self.cfg.search_paths)?;
         for p in namespaces.into_iter() {
             path = Input::try_push_path(path, Path::new(p)).ok()?;
         }
@@ -2851,6 +3027,28 @@ impl Context {
         self.get_type(name).and_then(|(t, _)| self.get_type_info(t))
     }
 
+    /// Returns the direct super classes and super traits of the type `name`.
+    /// `super_classes` and `super_traits` of a type context include the indirect ones, so they are excluded.
+    pub fn get_direct_super_types_by_str(&self, name: &str) -> Option<Vec<Type>> {
+        let (_, ctx) = self.get_type(name)?;
+        let sups = ctx.super_classes.iter().chain(ctx.super_traits.iter());
+        let indirect_sups = sups
+            .clone()
+            .filter_map(|sup| self.get_nominal_type_ctx(sup))
+            .flat_map(|(_, sup_ctx)| {
+                sup_ctx
+                    .super_classes
+                    .iter()
+                    .chain(sup_ctx.super_traits.iter())
+            })
+            .collect::<Vec<_>>();
+        Some(
+            sups.filter(|sup| !indirect_sups.contains(sup))
+                .cloned()
+                .collect(),
+        )
+    }
+
     /// you should use `get_type` instead of this
     pub(crate) fn rec_local_get_type(&self, name: &str) -> Option<(&Type, &Context)> {
         #[cfg(feature = "py_compat")]
@@ -12,7 +12,7 @@ use erg_common::consts::{ERG_MODE, PYTHON_MODE};
 use erg_common::dict::Dict;
 use erg_common::env::{is_pystd_main_module, is_std_decl_path};
 use erg_common::erg_util::BUILTIN_ERG_MODS;
-use erg_common::io::Input;
+use erg_common::error::Location;
 use erg_common::levenshtein::get_similar_name;
 use erg_common::pathutil::{DirKind, FileKind};
 use erg_common::python_util::BUILTIN_PYTHON_MODS;
@@ -20,22 +20,26 @@ use erg_common::set::Set;
 use erg_common::spawn::spawn_new_thread;
 use erg_common::traits::{Locational, Stream, StructuralEq};
 use erg_common::triple::Triple;
-use erg_common::{dict, get_hash, log, set, unique_in_place, Str};
+use erg_common::tsort::{find_cycle, Graph, Node};
+use erg_common::{get_hash, log, set, unique_in_place, Str};
 
 use ast::{
     ConstIdentifier, Decorator, DefId, Identifier, OperationKind, PolyTypeSpec, PreDeclTypeSpec,
     VarName,
 };
 use erg_parser::ast;
+use erg_parser::Parser;
 
 use crate::ty::constructors::{
-    free_var, func, func0, func1, proc, ref_, ref_mut, tp_enum, unknown_len_array_t, v_enum,
+    free_var, func, func0, func1, instanceof, mono_q, poly, proc, ref_, ref_mut, tp_enum,
+    unknown_len_array_t, v_enum,
 };
 use crate::ty::free::{Constraint, HasLevel};
 use crate::ty::typaram::TyParam;
 use crate::ty::value::{GenTypeObj, TypeObj, ValueObj};
 use crate::ty::{
-    CastTarget, Field, GuardType, HasType, ParamTy, SubrType, Type, Visibility, VisibilityModifier,
+    CastTarget, ConstSubr, Field, GuardType, HasType, ParamTy, SubrType, Type, UserConstSubr,
+    Visibility, VisibilityModifier,
 };
 
 use crate::build_hir::HIRBuilder;
@@ -44,7 +48,7 @@ use crate::error::readable_name;
 use crate::error::{
     CompileError, CompileErrors, CompileResult, TyCheckError, TyCheckErrors, TyCheckResult,
 };
-use crate::hir::Literal;
+use crate::hir::{Literal, NEWTYPE_BASE};
 use crate::varinfo::{AbsLocation, Mutability, VarInfo, VarKind};
 use crate::{feature_error, hir};
 use Mutability::*;
@@ -53,7 +57,7 @@ use RegistrationMode::*;
 use super::eval::Substituter;
 use super::instantiate::TyVarCache;
 use super::instantiate_spec::ParamKind;
-use super::{ModuleContext, ParamSpec};
+use super::ParamSpec;
 
 pub fn valid_mod_name(name: &str) -> bool {
     !name.is_empty() && !name.starts_with('/') && name.trim() == name
@@ -199,11 +203,7 @@ impl Context {
         let kind = id.map_or(VarKind::Declared, VarKind::Defined);
         let sig_t =
             self.instantiate_var_sig_t(sig.t_spec.as_ref().map(|ts| &ts.t_spec), PreRegister)?;
-        let py_name = if let ContextKind::PatchMethodDefs(_base) = &self.kind {
-            Some(Str::from(format!("::{}{}", self.name, ident)))
-        } else {
-            None
-        };
+        let py_name = self.debound_method_py_name(ident);
         if self
             .remove_class_attr(ident.name.inspect())
             .is_some_and(|(_, decl)| !decl.kind.is_auto())
@@ -257,11 +257,7 @@ impl Context {
             Ok(t) => (TyCheckErrors::empty(), t),
             Err((errs, t)) => (errs, t),
         };
-        let py_name = if let ContextKind::PatchMethodDefs(_base) = &self.kind {
-            Some(Str::from(format!("::{}{}", self.name, sig.ident)))
-        } else {
-            None
-        };
+        let py_name = self.debound_method_py_name(&sig.ident);
         let vi = VarInfo::new(
             t,
             muty,
@@ -731,7 +727,7 @@ impl Context {
     ) -> Result<VarInfo, (TyCheckErrors, VarInfo)> {
         let mut errs = TyCheckErrors::empty();
         // already defined as const
-        if sig.ident.is_const() {
+        if sig.ident.is_const() && !self.is_user_const_subr(sig.ident.inspect()) {
             let vi = self.decls.remove(sig.ident.inspect()).unwrap();
             self.locals.insert(sig.ident.name.clone(), vi.clone());
             return Ok(vi);
@@ -943,8 +939,17 @@ impl Context {
 
     pub(crate) fn register_const(&mut self, block: &ast::Block) -> TyCheckResult<()> {
         let mut total_errs = TyCheckErrors::empty();
+        let cyclic = match self.check_const_def_cycle(block) {
+            Ok(()) => Set::new(),
+            Err((cyclic, errs)) => {
+                total_errs.extend(errs);
+                cyclic
+            }
+        };
         for expr in block.iter() {
             match expr {
+                ast::Expr::Def(def)
+                    if def.sig.name_as_str().is_some_and(|n| cyclic.contains(n)) => {}
                 ast::Expr::Def(def) => {
                     if let Err(errs) = self.register_const_def(def) {
                         total_errs.extend(errs);
@@ -980,6 +985,132 @@ impl Context {
         }
     }
 
+    /// Detect cyclic references between constant definitions (e.g. `A = B + 1; B = A`) before evaluating them.
+    /// If a cycle is found, returns the names of the definitions in it (they should not be evaluated).
+    fn check_const_def_cycle(&self, block: &ast::Block) -> Result<(), (Set<Str>, TyCheckErrors)> {
+        let mut defs = vec![];
+        Self::collect_const_defs(block, &mut defs);
+        // classes and traits are preregistered, so they can refer to themselves
+        let vars = defs
+            .iter()
+            .filter(|def| matches!(def.sig, ast::Signature::Var(_)) && !Self::is_preregistered(def))
+            .filter_map(|def| def.sig.name_as_str())
+            .collect::<Set<_>>();
+        let mut graph: Graph<Str, Location> = vec![];
+        for def in defs.iter() {
+            let (Some(ident), Some(name)) = (def.sig.ident(), def.sig.name_as_str()) else {
+                continue;
+            };
+            let mut refs = Set::new();
+            for chunk in def.body.block.iter() {
+                Self::collect_const_refs(chunk, &mut refs);
+            }
+            // compile-time functions are evaluated lazily, so only references to constant variables can make a cycle
+            let deps = refs.into_iter().filter(|r| vars.contains(r)).collect();
+            graph.push(Node::new(name.clone(), ident.loc(), deps));
+        }
+        let Some(cycle) = find_cycle(&graph) else {
+            return Ok(());
+        };
+        let chain = cycle
+            .iter()
+            .filter_map(|name| {
+                let node = graph.iter().find(|node| &node.id == name)?;
+                Some((name.clone(), node.data))
+            })
+            .collect::<Vec<_>>();
+        let err = TyCheckError::cyclic_const_def_error(
+            self.cfg.input.clone(),
+            line!() as usize,
+            self.caused_by(),
+            &chain,
+        );
+        Err((cycle.into_iter().collect(), TyCheckErrors::from(err)))
+    }
+
+    fn collect_const_defs<'a>(block: &'a ast::Block, defs: &mut Vec<&'a ast::Def>) {
+        for expr in block.iter() {
+            match expr {
+                ast::Expr::Def(def) if def.sig.is_const() => defs.push(def),
+                ast::Expr::ClassDef(class_def) if class_def.def.sig.is_const() => {
+                    defs.push(&class_def.def)
+                }
+                ast::Expr::PatchDef(patch_def) if patch_def.def.sig.is_const() => {
+                    defs.push(&patch_def.def)
+                }
+                ast::Expr::Dummy(dummy) => Self::collect_const_defs(&dummy.exprs, defs),
+                _ => {}
+            }
+        }
+    }
+
+    /// Collect constant names referred in `expr`.
+    fn collect_const_refs(expr: &ast::Expr, refs: &mut Set<Str>) {
+        let collect_args = |args: &ast::Args, refs: &mut Set<Str>| {
+            for arg in args.pos_args() {
+                Self::collect_const_refs(&arg.expr, refs);
+            }
+            for arg in args.kw_args() {
+                Self::collect_const_refs(&arg.expr, refs);
+            }
+        };
+        match expr {
+            ast::Expr::Accessor(ast::Accessor::Ident(ident)) if ident.is_const() => {
+                refs.insert(ident.inspect().clone());
+            }
+            ast::Expr::Accessor(ast::Accessor::Attr(attr)) => {
+                Self::collect_const_refs(&attr.obj, refs);
+            }
+            ast::Expr::Accessor(ast::Accessor::TupleAttr(attr)) => {
+                Self::collect_const_refs(&attr.obj, refs);
+            }
+            ast::Expr::Accessor(ast::Accessor::Subscr(subscr)) => {
+                Self::collect_const_refs(&subscr.obj, refs);
+                Self::collect_const_refs(&subscr.index, refs);
+            }
+            ast::Expr::Accessor(ast::Accessor::TypeApp(type_app)) => {
+                Self::collect_const_refs(&type_app.obj, refs);
+            }
+            ast::Expr::Array(ast::Array::Normal(arr)) => collect_args(&arr.elems, refs),
+            ast::Expr::Tuple(ast::Tuple::Normal(tup)) => collect_args(&tup.elems, refs),
+            ast::Expr::Set(ast::Set::Normal(set)) => collect_args(&set.elems, refs),
+            ast::Expr::Dict(ast::Dict::Normal(dict)) => {
+                for kv in dict.kvs.iter() {
+                    Self::collect_const_refs(&kv.key, refs);
+                    Self::collect_const_refs(&kv.value, refs);
+                }
+            }
+            ast::Expr::Record(ast::Record::Normal(rec)) => {
+                for attr in rec.attrs.iter() {
+                    for chunk in attr.body.block.iter() {
+                        Self::collect_const_refs(chunk, refs);
+                    }
+                }
+            }
+            ast::Expr::BinOp(bin) => {
+                for arg in bin.args.iter() {
+                    Self::collect_const_refs(arg, refs);
+                }
+            }
+            ast::Expr::UnaryOp(unary) => {
+                for arg in unary.args.iter() {
+                    Self::collect_const_refs(arg, refs);
+                }
+            }
+            ast::Expr::Call(call) => {
+                Self::collect_const_refs(&call.obj, refs);
+                collect_args(&call.args, refs);
+            }
+            ast::Expr::Lambda(lambda) => {
+                for chunk in lambda.body.iter() {
+                    Self::collect_const_refs(chunk, refs);
+                }
+            }
+            ast::Expr::TypeAscription(tasc) => Self::collect_const_refs(&tasc.expr, refs),
+            _ => {}
+        }
+    }
+
     /// HACK: The constant expression evaluator can evaluate attributes when the type of the receiver is known.
     /// import/pyimport is not a constant function, but specially assumes that the type of the module is known in the eval phase.
     fn pre_import(&mut self, def: &ast::Def) -> TyCheckResult<()> {
@@ -1033,6 +1164,45 @@ impl Context {
         }
     }
 
+    /// Methods of patches and newtypes have no runtime receiver class,
+    /// so they are compiled into functions (e.g. `::<module>::Invertinvert`).
+    fn debound_method_py_name(&self, ident: &ast::Identifier) -> Option<Str> {
+        let debound = match &self.kind {
+            ContextKind::PatchMethodDefs(_) => true,
+            ContextKind::MethodDefs(_) => self.is_newtype_methods(),
+            _ => false,
+        };
+        debound.then(|| Str::from(format!("::{}{}", self.name, ident)))
+    }
+
+    pub(crate) fn is_newtype_methods(&self) -> bool {
+        let Some(outer) = self.get_outer() else {
+            return false;
+        };
+        let class_name = self
+            .name
+            .split_with(&[".", "::"])
+            .last()
+            .copied()
+            .unwrap_or("");
+        matches!(
+            outer.rec_get_const_obj(class_name),
+            Some(ValueObj::Type(TypeObj::Generated(gen))) if gen.is_newtype()
+        )
+    }
+
+    /// Is `def` registered by `preregister_const` (e.g. `C = Class { ... }`)?
+    fn is_preregistered(def: &ast::Def) -> bool {
+        let Some(ast::Expr::Call(call)) = def.body.block.first() else {
+            return false;
+        };
+        matches!(
+            call.obj.as_ref(),
+            ast::Expr::Accessor(ast::Accessor::Ident(ident))
+                if &ident.inspect()[..] == "Class" || &ident.inspect()[..] == "Trait"
+        )
+    }
+
     fn preregister_type(&mut self, var: &ast::VarSignature, call: &ast::Call) -> TyCheckResult<()> {
         match call.obj.as_ref() {
             ast::Expr::Accessor(ast::Accessor::Ident(ident)) => match &ident.inspect()[..] {
@@ -1061,6 +1231,14 @@ impl Context {
         let id = Some(def.body.id);
         let __name__ = def.sig.ident().map(|i| i.inspect()).unwrap_or(UBAR);
         match &def.sig {
+            ast::Signature::Subr(sig)
+                if sig.is_const() && !sig.params.is_empty() && def.def_kind().is_class() =>
+            {
+                self.register_poly_type_def(sig, &def.body)?;
+            }
+            ast::Signature::Subr(sig) if sig.is_const() && !sig.params.is_empty() => {
+                self.register_const_subr(sig, &def.body)?;
+            }
             ast::Signature::Subr(sig) => {
                 if sig.is_const() {
                     let tv_cache = self.instantiate_ty_bounds(&sig.bounds, PreRegister)?;
@@ -1147,6 +1325,83 @@ impl Context {
         Ok(())
     }
 
+    /// Register a polymorphic class (e.g. `Box T = Class { .v = T }`).
+    /// The body is evaluated with the parameters bound to type variables, and the generated type becomes `Box(T)`.
+    fn register_poly_type_def(
+        &mut self,
+        sig: &ast::SubrSignature,
+        body: &ast::DefBody,
+    ) -> TyCheckResult<()> {
+        let vis = self.instantiate_vis_modifier(sig.vis())?;
+        self.grow(sig.ident.inspect(), ContextKind::Class, vis, None);
+        let mut typarams = vec![];
+        for param in sig.params.non_defaults.iter() {
+            let (Some(name), None) = (param.name(), param.t_spec.as_ref()) else {
+                self.pop();
+                return feature_error!(
+                    TyCheckErrors,
+                    TyCheckError,
+                    self,
+                    param.loc(),
+                    "non-type parameters of polymorphic classes"
+                );
+            };
+            let t = mono_q(name.inspect(), instanceof(Type::Type));
+            self.consts
+                .insert(name.clone(), ValueObj::builtin_type(t.clone()));
+            typarams.push(TyParam::t(t));
+        }
+        let obj = self.eval_const_block(&body.block);
+        self.pop();
+        match obj? {
+            ValueObj::Type(TypeObj::Generated(mut gen)) => {
+                *gen.typ_mut() = poly(gen.typ().qual_name(), typarams);
+                self.register_gen_type(&sig.ident, gen)
+            }
+            _ => self.register_const_subr(sig, body),
+        }
+    }
+
+    /// Register a constant subroutine with parameters (e.g. `Add1(N: Nat): Nat = N + 1`).
+    /// It is declared as a normal subroutine, and its body is evaluated each time it is called at compile-time.
+    fn register_const_subr(
+        &mut self,
+        sig: &ast::SubrSignature,
+        body: &ast::DefBody,
+    ) -> TyCheckResult<()> {
+        self.declare_sub(sig, Some(body.id))?;
+        let block = Parser::validate_const_block(body.block.clone()).map_err(|_| {
+            TyCheckErrors::from(TyCheckError::not_const_expr(
+                self.cfg.input.clone(),
+                line!() as usize,
+                body.loc(),
+                self.caused_by(),
+            ))
+        })?;
+        let sig_t = self
+            .decls
+            .get(sig.ident.inspect())
+            .map_or(Type::Failure, |vi| vi.t.clone());
+        let subr = UserConstSubr::new(
+            sig.ident.inspect().clone(),
+            sig.params.clone(),
+            block,
+            sig_t,
+        );
+        self.consts.insert(
+            sig.ident.name.clone(),
+            ValueObj::Subr(ConstSubr::User(subr)),
+        );
+        Ok(())
+    }
+
+    pub(crate) fn is_user_const_subr(&self, name: &str) -> bool {
+        matches!(
+            self.consts.get(name),
+            Some(ValueObj::Subr(ConstSubr::User(_)))
+        )
+    }
+
     /// e.g. .new
     fn register_auto_impl(
         &mut self,
@@ -1396,7 +1651,7 @@ impl Context {
         gen: GenTypeObj,
     ) -> CompileResult<()> {
         match gen {
-            GenTypeObj::Class(_) => {
+            GenTypeObj::Class(_) | GenTypeObj::Newtype(_) => {
                 if gen.typ().is_monomorphic() {
                     // let super_traits = gen.impls.iter().map(|to| to.typ().clone()).collect();
                     let mut ctx = Self::mono_class(
@@ -1656,12 +1911,13 @@ impl Context {
                     self.register_instance_attrs(ctx, rec)?;
                 }
                 other => {
+                    let py_name = gen.is_newtype().then(|| Str::ever(NEWTYPE_BASE));
                     methods.register_fixed_auto_impl(
                         "base",
                         other.typ().clone(),
                         Immutable,
                         Visibility::BUILTIN_PRIVATE,
-                        None,
+                        py_name,
                     )?;
                 }
             }
@@ -1669,6 +1925,12 @@ impl Context {
         } else {
             func0(gen.typ().clone())
         };
+        // `Box T = Class { .v = T }` => `Box.__new__: |T|({.v = T}) -> Box(T)`
+        let new_t = if new_t.has_qvar() {
+            new_t.quantify()
+        } else {
+            new_t
+        };
         if ERG_MODE {
             methods.register_fixed_auto_impl(
                 "__new__",
@@ -1947,7 +2209,11 @@ impl Context {
     }
 
     fn import_erg_mod(&self, __name__: &Str, loc: &impl Locational) -> CompileResult<PathBuf> {
-        let path = match self.cfg.input.resolve_real_path(Path::new(&__name__[..])) {
+        let path = match self.cfg.input.resolve_real_path(
+            Path::new(&__name__[..]),
+            &self.cfg.packages,
+            &self.cfg.search_paths,
+        ) {
             Some(path) => path,
             None => {
                 return Err(self.import_err(line!(), __name__, loc));
@@ -1958,54 +2224,56 @@ impl Context {
         }
         let referrer = self.cfg.input.path();
         if self.shared().graph.inc_ref(referrer, path.clone()).is_err() {
-            self.build_cyclic_mod(&path);
+            // the module is already being analyzed (it depends on this module)
+            self.build_cyclic_mod(&path, __name__);
+            return Ok(path);
         }
         self.build_erg_mod(path, __name__, loc)
     }
 
+    /// Mutually recursive modules are analyzed in two phases.
+    /// When a cycle is detected, only the top-level names of the imported module are declared here,
+    /// and the module body is lowered later by its own build process (so errors are reported once, in the right file).
+    ///
     /// e.g.
     /// ```erg
     /// # a.er
     /// b = import "b"
-    /// .f() = b.f()
-    /// ```
-    /// ```erg
-    /// # b.er (entry point)
-    /// a = import "a"
-    /// .f() = 1
-    /// print! a.f()
+    /// .f(x: Int): Int = b.g(x)
     /// ```
-    /// ↓
     /// ```erg
-    /// # a.er (pseudo code)
-    /// b = {
-    ///     # a = import "a"
-    ///     .f() = 1
-    ///     # print! a.f()
-    /// }
-    /// .f() = b.f()
+    /// # b.er
+    /// a = import "a" # cycle detected: declare `a.f: Int -> Int` without lowering a.er
+    /// .g(x: Int): Int = if x <= 0, do 0, do a.f(x - 1)
     /// ```
-    fn build_cyclic_mod(&self, path: &Path) {
-        let mod_ctx = ModuleContext::new(self.clone(), dict! {});
-        let mut builder = HIRBuilder::new_with_ctx(mod_ctx);
-        let src = Input::file(path.to_path_buf()).read();
-        let mode = if path.to_string_lossy().ends_with("d.er") {
-            "declare"
+    fn build_cyclic_mod(&self, path: &Path, __name__: &Str) {
+        let is_decl = path.to_string_lossy().ends_with(".d.er");
+        let cache = if is_decl {
+            &self.shared().py_mod_cache
         } else {
-            "exec"
+            &self.shared().mod_cache
         };
-        let res = builder.build(src, mode);
-        let hir = match res {
-            Ok(art) => Some(art.object),
-            Err(art) => art.object,
+        if cache.get(path).is_some() {
+            return;
+        }
+        let mut cfg = self.cfg.inherit(path.to_path_buf());
+        let Ok(src) = cfg.input.try_read() else {
+            return;
         };
-        let ctx = builder.pop_mod_ctx().unwrap();
-        let cache = if path.to_string_lossy().ends_with("d.er") {
-            &self.shared().py_mod_cache
+        let shared = self.shared().inherit(path.to_path_buf());
+        let mut builder = HIRBuilder::new_with_cache(cfg, __name__.clone(), shared);
+        let hir = if is_decl {
+            match builder.build(src, "declare") {
+                Ok(art) => Some(art.object),
+                Err(art) => art.object,
+            }
         } else {
-            &self.shared().mod_cache
+            builder.declare_toplevel(src);
+            None
         };
-        cache.register(path.to_path_buf(), hir, ctx);
+        let ctx = builder.pop_mod_ctx().unwrap();
+        // the module may have been built by another thread in the meantime
+        cache.register_if_absent(path.to_path_buf(), hir, ctx);
     }
 
     /// If the path is like `foo/bar`, check if `bar` is a public module (the definition is in `foo/__init__.er`)
@@ -2174,7 +2442,11 @@ impl Context {
     }
 
     fn get_decl_path(&self, __name__: &Str, loc: &impl Locational) -> CompileResult<PathBuf> {
-        match self.cfg.input.resolve_decl_path(Path::new(&__name__[..])) {
+        match self.cfg.input.resolve_decl_path(
+            Path::new(&__name__[..]),
+            &self.cfg.packages,
+            &self.cfg.search_paths,
+        ) {
             Some(path) => {
                 if self.cfg.input.decl_file_is(&path) {
                     return Ok(path);
@@ -2223,7 +2495,11 @@ impl Context {
     }
 
     fn try_gen_py_decl_file(&self, __name__: &Str) -> Result<PathBuf, ()> {
-        if let Ok(path) = self.cfg.input.resolve_py(Path::new(&__name__[..])) {
+        if let Ok(path) = self
+            .cfg
+            .input
+            .resolve_py(Path::new(&__name__[..]), &self.cfg.search_paths)
+        {
             if self.cfg.input.path() == path.as_path() {
                 return Ok(path);
             }
@@ -2244,7 +2520,11 @@ impl Context {
                 .spawn()
                 .and_then(|mut child| child.wait())
             {
-                if let Some(path) = self.cfg.input.resolve_decl_path(Path::new(&__name__[..])) {
+                if let Some(path) = self.cfg.input.resolve_decl_path(
+                    Path::new(&__name__[..]),
+                    &self.cfg.packages,
+                    &self.cfg.search_paths,
+                ) {
                     let size = metadata(&path).unwrap().len();
                     // if pylyzer crashed
                     if !status.success() && size == 0 {
@@ -2269,7 +2549,7 @@ impl Context {
         }
         let referrer = self.cfg.input.path();
         if self.shared().graph.inc_ref(referrer, path.clone()).is_err() {
-            self.build_cyclic_mod(&path);
+            self.build_cyclic_mod(&path, __name__);
         }
         if py_mod_cache.get(&path).is_some() {
             return Ok(path);
@@ -2562,6 +2842,15 @@ impl Context {
                 }
                 res
             }
+            ast::Expr::Call(call) => {
+                let mut res = self.inc_ref_expr(&call.obj, namespace, tmp_tv_cache);
+                for arg in call.args.pos_args() {
+                    if self.inc_ref_expr(&arg.expr, namespace, tmp_tv_cache) {
+                        res = true;
+                    }
+                }
+                res
+            }
             other => {
                 log!(err "inc_ref_expr: {other}");
                 false
@@ -1221,8 +1221,18 @@ impl<'c, 'l, 'u, L: Locational> Unifier<'c, 'l, 'u, L> {
                 if ln != rn {
                     self.nominal_sub_unify(maybe_sub, maybe_sup, rps)?;
                 } else {
-                    for (l_maybe_sub, r_maybe_sup) in lps.iter().zip(rps.iter()) {
+                    // phantom type parameters of newtypes (e.g. `Length Unit = Newtype Nat`) are invariant
+                    let phantoms = self
+                        .ctx
+                        .get_nominal_type_ctx(maybe_sub)
+                        .map(|(_, ctx)| ctx.newtype_phantom_params())
+                        .unwrap_or_default();
+                    for (i, (l_maybe_sub, r_maybe_sup)) in lps.iter().zip(rps.iter()).enumerate() {
                         self.sub_unify_tp(l_maybe_sub, r_maybe_sup, None, false)?;
+                        // e.g. Length(Meter) <: Length(?U) ==> ?U == Meter
+                        if phantoms.get(i) == Some(&true) {
+                            self.sub_unify_tp(r_maybe_sup, l_maybe_sub, None, false)?;
+                        }
                     }
                 }
             }
@@ -0,0 +1,851 @@
+//! defines `DocGenerator`, which generates API documents from Erg modules (`erg doc`).
+//!
+//! The signatures are taken from the module contexts (so inferred types are also shown),
+//! and the documents are the doc comments placed immediately before the definitions.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use erg_common::config::ErgConfig;
+use erg_common::io::Input;
+use erg_common::lang::LanguageCode;
+use erg_common::pathutil::collect_files;
+use erg_common::traits::{ExitStatus, Runnable, Stream};
+use erg_common::{normalize_path, Str};
+
+use erg_parser::cst::{ConcreteSyntaxTree, DocComment};
+
+use crate::build_hir::HIRBuilder;
+use crate::context::{Context, ContextKind, ModuleContext};
+use crate::error::{CompileError, CompileErrors};
+use crate::package::{DependencyResolver, Lockfile, PackageManifest};
+use crate::project::is_source_file;
+use crate::ty::Type;
+use crate::varinfo::VarInfo;
+
+const USAGE: &str = "\
+USAGE:
+    erg doc [PATH] [--html] [--output DIR]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DocFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// The kinds of documented items (in the order of the sections).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ItemKind {
+    Trait,
+    Class,
+    Patch,
+    Function,
+    Procedure,
+    Variable,
+    /// a method or an attribute of a class/trait
+    Member,
+}
+
+impl ItemKind {
+    const fn section(&self) -> &'static str {
+        match self {
+            Self::Trait => "Traits",
+            Self::Class => "Classes",
+            Self::Patch => "Patches",
+            Self::Function => "Functions",
+            Self::Procedure => "Procedures",
+            Self::Variable => "Variables",
+            Self::Member => "Members",
+        }
+    }
+
+    const fn is_type(&self) -> bool {
+        matches!(self, Self::Trait | Self::Class | Self::Patch)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemDoc {
+    pub name: Str,
+    pub kind: ItemKind,
+    /// the type of the item (e.g. `(x: Int, y: Int) -> Int`)
+    pub t: String,
+    /// Markdown
+    pub doc: Vec<String>,
+    /// the line of the definition
+    pub line: u32,
+    pub is_subr: bool,
+    /// superclasses and supertraits
+    pub bases: Vec<String>,
+    /// methods (classes) or required attributes (traits)
+    pub members: Vec<ItemDoc>,
+    /// (the implemented trait, the methods)
+    pub impls: Vec<(String, Vec<ItemDoc>)>,
+    /// (the module index, the class name), filled by `DocSite::link_implementors`
+    pub implementors: Vec<(usize, Str)>,
+}
+
+impl ItemDoc {
+    fn new(name: Str, kind: ItemKind, vi: &VarInfo, docs: &[DocComment]) -> Self {
+        let line = vi.def_loc.loc.ln_begin().unwrap_or(0);
+        Self {
+            name,
+            kind,
+            t: vi.t.to_string(),
+            doc: attached_doc(docs, line),
+            line,
+            is_subr: vi.t.is_subr(),
+            bases: vec![],
+            members: vec![],
+            impls: vec![],
+            implementors: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleDoc {
+    /// the path from the documented root without the extension (e.g. `shapes/circle`)
+    pub name: String,
+    pub path: PathBuf,
+    /// the doc comment at the top of the file which is not attached to any definition
+    pub doc: Vec<String>,
+    /// sorted by (kind, line)
+    pub items: Vec<ItemDoc>,
+}
+
+/// Extracts the text of doc comments.
+///
+/// A doc comment starting with a language code (e.g. `'''japanese`) is used only if the code matches the build features,
+/// and one with `erg` or `python` is rendered as a code block.
+fn doc_text(docs: &[&DocComment]) -> Vec<String> {
+    let mut text = vec![];
+    for doc in docs {
+        let (lang, lines) = match doc.lines.first().map(|line| line.trim().parse()) {
+            Some(Ok(lang)) if doc.lines.len() > 1 => (lang, &doc.lines[1..]),
+            _ => (LanguageCode::English, &doc.lines[..]),
+        };
+        if !text.is_empty() {
+            text.push("".to_string());
+        }
+        if lang.is_pl() {
+            text.push(format!(
+                "```{}",
+                if lang.is_erg() { "erg" } else { "python" }
+            ));
+            text.extend(lines.iter().cloned());
+            text.push("```".to_string());
+        } else if lang.matches_feature() {
+            text.extend(lines.iter().cloned());
+        } else if text.last().is_some_and(|line| line.is_empty()) {
+            text.pop();
+        }
+    }
+    text
+}
+
+/// The doc comments placed immediately before `line` (consecutive doc comments are concatenated).
+fn attached_doc(docs: &[DocComment], line: u32) -> Vec<String> {
+    let mut attached = vec![];
+    let mut next = line;
+    for doc in docs.iter().rev() {
+        if doc.range.1 + 1 == next {
+            attached.push(doc);
+            next = doc.range.0;
+        } else if doc.range.1 < next {
+            break;
+        }
+    }
+    attached.reverse();
+    doc_text(&attached)
+}
+
+fn publics(ctx: &Context) -> Vec<(&Str, &VarInfo)> {
+    let mut vars = ctx
+        .locals
+        .iter()
+        .chain(ctx.decls.iter())
+        .filter(|(_, vi)| vi.vis.is_public())
+        .map(|(name, vi)| (name.inspect(), vi))
+        .collect::<Vec<_>>();
+    vars.sort_by_key(|(name, vi)| (vi.def_loc.loc.ln_begin().unwrap_or(0), name.to_string()));
+    vars
+}
+
+fn members(ctx: &Context, docs: &[DocComment]) -> Vec<ItemDoc> {
+    publics(ctx)
+        .into_iter()
+        .map(|(name, vi)| ItemDoc::new(name.clone(), ItemKind::Member, vi, docs))
+        .collect()
+}
+
+impl ModuleDoc {
+    pub fn new(name: String, path: PathBuf, src: &str, module: &ModuleContext) -> Self {
+        let docs = ConcreteSyntaxTree::parse(src.to_string())
+            .map(|cst| cst.doc_comments())
+            .unwrap_or_default();
+        let ctx = &module.context;
+        let mut items = vec![];
+        for (name, vi) in publics(ctx) {
+            let type_ctx = ctx
+                .mono_types
+                .get(&name[..])
+                .or_else(|| ctx.poly_types.get(&name[..]))
+                .map(|(_, ctx)| ctx)
+                .or_else(|| ctx.patches.get(&name[..]));
+            let kind = match type_ctx.map(|ctx| &ctx.kind) {
+                Some(ContextKind::Class) => ItemKind::Class,
+                Some(ContextKind::Trait | ContextKind::StructuralTrait) => ItemKind::Trait,
+                Some(_) => ItemKind::Patch,
+                None if vi.t.is_procedure() => ItemKind::Procedure,
+                None if vi.t.is_subr() => ItemKind::Function,
+                None => ItemKind::Variable,
+            };
+            let mut item = ItemDoc::new(name.clone(), kind, vi, &docs);
+            if let Some(type_ctx) = type_ctx {
+                item.bases = type_ctx
+                    .super_classes
+                    .iter()
+                    .chain(type_ctx.super_traits.iter())
+                    .filter(|t| *t != Type::OBJ)
+                    .map(|t| t.to_string())
+                    .collect();
+                // the required attributes of a trait
+                item.members = members(type_ctx, &docs);
+                for (def_t, methods) in type_ctx.methods_list.iter() {
+                    let methods = members(methods, &docs);
+                    if let Some(impl_trait) = def_t.get_impl_trait() {
+                        let impl_trait = impl_trait.to_string();
+                        // the implemented traits are shown with the implementations
+                        item.bases.retain(|base| base != &impl_trait);
+                        item.impls.push((impl_trait, methods));
+                    } else {
+                        item.members.extend(methods);
+                    }
+                }
+            }
+            items.push(item);
+        }
+        items.sort_by_key(|item| (item.kind, item.line));
+        // the first doc comment is the module document if it is not attached to any item
+        let doc = match docs.first() {
+            Some(first) if items.iter().all(|item| first.range.1 + 1 < item.line) => {
+                doc_text(&[first])
+            }
+            _ => vec![],
+        };
+        Self {
+            name,
+            path,
+            doc,
+            items,
+        }
+    }
+
+    fn get_type(&self, name: &str) -> Option<&ItemDoc> {
+        self.items
+            .iter()
+            .find(|item| item.kind.is_type() && &item.name[..] == name)
+    }
+
+    pub fn page(&self, format: DocFormat) -> String {
+        format!("{}.{}", self.name.replace('/', "."), format.extension())
+    }
+}
+
+/// All documented modules.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DocSite {
+    pub modules: Vec<ModuleDoc>,
+}
+
+impl DocSite {
+    /// Type-checks `files` and collects their documents.
+    /// The module names are the paths relative to `root`.
+    pub fn build(cfg: &ErgConfig, root: &Path, files: &[PathBuf]) -> Result<Self, CompileErrors> {
+        let mut site = Self::default();
+        let mut errors = CompileErrors::empty();
+        for file in files {
+            let mut cfg = cfg.inherit(file.clone());
+            let src = cfg.input.read();
+            let mut builder = HIRBuilder::new(cfg);
+            if let Err(artifact) = builder.build(src.clone(), "exec") {
+                errors.extend(artifact.errors);
+                continue;
+            }
+            let Some(module) = builder.pop_mod_ctx() else {
+                continue;
+            };
+            let rel = file.strip_prefix(root).unwrap_or(file).with_extension("");
+            let mut name = rel.to_string_lossy().replace('\\', "/");
+            if let Some(parent) = name.strip_suffix("/__init__") {
+                name = parent.to_string();
+            }
+            site.modules
+                .push(ModuleDoc::new(name, file.clone(), &src, &module));
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        site.link_implementors();
+        Ok(site)
+    }
+
+    fn link_implementors(&mut self) {
+        let mut implementors = vec![];
+        for (idx, module) in self.modules.iter().enumerate() {
+            let linker = Linker::new(self, idx);
+            for class in module
+                .items
+                .iter()
+                .filter(|item| item.kind == ItemKind::Class)
+            {
+                let traits = class.impls.iter().map(|(t, _)| t).chain(class.bases.iter());
+                for t in traits {
+                    if let Some((trait_mod, trait_name)) = linker.resolve(t) {
+                        implementors.push((trait_mod, trait_name, (idx, class.name.clone())));
+                    }
+                }
+            }
+        }
+        for (trait_mod, trait_name, class) in implementors {
+            let module = &mut self.modules[trait_mod];
+            if let Some(item) = module
+                .items
+                .iter_mut()
+                .find(|item| item.kind == ItemKind::Trait && item.name == trait_name)
+            {
+                if !item.implementors.contains(&class) {
+                    item.implementors.push(class);
+                }
+            }
+        }
+    }
+
+    /// Returns (the file name, the content) of each page.
+    pub fn render(&self, format: DocFormat) -> Vec<(String, String)> {
+        let mut pages = vec![];
+        for idx in 0..self.modules.len() {
+            let mut renderer = Renderer::new(format);
+            render_module(&mut renderer, self, idx);
+            let title = format!("module {}", self.modules[idx].name);
+            pages.push((self.modules[idx].page(format), renderer.finish(&title)));
+        }
+        let mut renderer = Renderer::new(format);
+        renderer.heading(1, None, "API documentation");
+        let links = self
+            .modules
+            .iter()
+            .map(|module| {
+                let link = renderer.link(&module.name, &module.page(format));
+                match module.doc.iter().find(|line| !line.trim().is_empty()) {
+                    Some(summary) => format!("{link}: {}", renderer.escape(summary)),
+                    None => link,
+                }
+            })
+            .collect::<Vec<_>>();
+        renderer.list(&links);
+        pages.push((
+            format!("index.{}", format.extension()),
+            renderer.finish("API documentation"),
+        ));
+        pages
+    }
+}
+
+/// Resolves type names in the types of a module to the documented types.
+struct Linker<'a> {
+    site: &'a DocSite,
+    current: usize,
+}
+
+const LOCAL: char = '\u{1}';
+
+impl<'a> Linker<'a> {
+    const fn new(site: &'a DocSite, current: usize) -> Self {
+        Self { site, current }
+    }
+
+    /// `<module>.Point` => the current module, `../geo.Point` => `geo.er` relative to the current module.
+    fn resolve(&self, name: &str) -> Option<(usize, Str)> {
+        let name = name.replace("<module>.", &LOCAL.to_string());
+        if let Some(local) = name.strip_prefix(LOCAL) {
+            let module = &self.site.modules[self.current];
+            return module
+                .get_type(local)
+                .map(|item| (self.current, item.name.clone()));
+        }
+        let (path, local) = name.rsplit_once('.')?;
+        let dir = self.site.modules[self.current].path.parent()?;
+        let candidates = [
+            dir.join(format!("{path}.er")),
+            dir.join(path).join("__init__.er"),
+        ];
+        let target = candidates
+            .iter()
+            .find_map(|path| path.canonicalize().ok())?;
+        self.site
+            .modules
+            .iter()
+            .position(|module| module.path.canonicalize().ok().as_ref() == Some(&target))
+            .and_then(|idx| {
+                let item = self.site.modules[idx].get_type(local)?;
+                Some((idx, item.name.clone()))
+            })
+    }
+
+    /// Renders a type with links to the documented types.
+    fn render_type(&self, r: &Renderer, t: &str) -> String {
+        let t = t.replace("<module>.", &LOCAL.to_string());
+        let is_name_char = |c: char| c.is_alphanumeric() || "_./!'".contains(c) || c == LOCAL;
+        let mut out = String::new();
+        let mut chars = t.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if !is_name_char(c) {
+                out.push_str(&r.escape(&c.to_string()));
+                continue;
+            }
+            let mut end = start + c.len_utf8();
+            while let Some((i, c)) = chars.peek().copied() {
+                if !is_name_char(c) {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let token = &t[start..end];
+            let resolved = if token.starts_with(LOCAL) {
+                self.resolve(&token.replacen(LOCAL, "<module>.", 1))
+            } else if token.contains('.') {
+                self.resolve(token)
+            } else {
+                self.resolve(&format!("<module>.{token}"))
+            };
+            let text = match token.rsplit_once('.') {
+                _ if token.starts_with(LOCAL) => token.trim_start_matches(LOCAL).to_string(),
+                Some((path, name)) if resolved.is_some() => {
+                    let stem = Path::new(path)
+                        .file_stem()
+                        .map_or(path.to_string(), |stem| stem.to_string_lossy().to_string());
+                    format!("{stem}.{name}")
+                }
+                _ => token.to_string(),
+            };
+            match resolved {
+                Some((idx, name)) => {
+                    let href = self.href(r.format, idx, &name);
+                    out.push_str(&r.link(&text, &href));
+                }
+                None => out.push_str(&r.escape(&text)),
+            }
+        }
+        out
+    }
+
+    fn href(&self, format: DocFormat, module: usize, anchor: &str) -> String {
+        if module == self.current {
+            format!("#{anchor}")
+        } else {
+            format!("{}#{anchor}", self.site.modules[module].page(format))
+        }
+    }
+}
+
+fn render_module(r: &mut Renderer, site: &DocSite, idx: usize) {
+    let module = &site.modules[idx];
+    let linker = Linker::new(site, idx);
+    r.heading(1, None, &format!("module {}", r.escape(&module.name)));
+    r.doc(&module.doc);
+    let mut section = None;
+    for item in module.items.iter() {
+        if section != Some(item.kind) {
+            r.heading(2, None, item.kind.section());
+            section = Some(item.kind);
+        }
+        r.heading(3, Some(&item.name), &r.escape(&item.name));
+        if !item.kind.is_type() {
+            r.signature(&format!(
+                "{}: {}",
+                r.bold(&item.name),
+                linker.render_type(r, &item.t)
+            ));
+        }
+        r.doc(&item.doc);
+        if !item.bases.is_empty() {
+            let bases = item
+                .bases
+                .iter()
+                .map(|t| linker.render_type(r, t))
+                .collect::<Vec<_>>();
+            r.paragraph(&format!("Bases: {}", bases.join(", ")));
+        }
+        if item.kind == ItemKind::Trait {
+            render_members(r, &linker, &item.name, "Required attributes", &item.members);
+        } else {
+            let (methods, attrs) = item
+                .members
+                .iter()
+                .cloned()
+                .partition::<Vec<_>, _>(|member| member.is_subr);
+            render_members(r, &linker, &item.name, "Attributes", &attrs);
+            render_members(r, &linker, &item.name, "Methods", &methods);
+        }
+        for (t, methods) in item.impls.iter() {
+            let title = format!("Implementation of {}", linker.render_type(r, t));
+            render_members(r, &linker, &item.name, &title, methods);
+        }
+        if !item.implementors.is_empty() {
+            r.heading(4, None, "Implementors");
+            let links = item
+                .implementors
+                .iter()
+                .map(|(module, class)| {
+                    let text = if *module == idx {
+                        class.to_string()
+                    } else {
+                        format!("{}.{class}", site.modules[*module].name)
+                    };
+                    r.link(&text, &linker.href(r.format, *module, class))
+                })
+                .collect::<Vec<_>>();
+            r.list(&links);
+        }
+    }
+}
+
+fn render_members(
+    r: &mut Renderer,
+    linker: &Linker,
+    owner: &str,
+    title: &str,
+    members: &[ItemDoc],
+) {
+    if members.is_empty() {
+        return;
+    }
+    r.heading(4, None, title);
+    for member in members {
+        let anchor = format!("{owner}.{}", member.name);
+        r.heading(5, Some(&anchor), &r.escape(&member.name));
+        r.signature(&format!(
+            "{}: {}",
+            r.bold(&member.name),
+            linker.render_type(r, &member.t)
+        ));
+        r.doc(&member.doc);
+    }
+}
+
+/// Renders Markdown or HTML.
+struct Renderer {
+    format: DocFormat,
+    out: String,
+}
+
+impl Renderer {
+    const fn new(format: DocFormat) -> Self {
+        Self {
+            format,
+            out: String::new(),
+        }
+    }
+
+    fn escape(&self, text: &str) -> String {
+        let mut escaped = String::new();
+        for c in text.chars() {
+            match (self.format, c) {
+                (DocFormat::Markdown, '\\' | '*' | '_' | '[' | ']' | '`' | '<' | '|') => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                (DocFormat::Html, '<') => escaped.push_str("&lt;"),
+                (DocFormat::Html, '>') => escaped.push_str("&gt;"),
+                (DocFormat::Html, '&') => escaped.push_str("&amp;"),
+                (DocFormat::Html, '"') => escaped.push_str("&quot;"),
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    fn link(&self, text: &str, href: &str) -> String {
+        match self.format {
+            DocFormat::Markdown => format!("[{text}]({href})"),
+            DocFormat::Html => format!("<a href=\"{href}\">{text}</a>"),
+        }
+    }
+
+    fn bold(&self, text: &str) -> String {
+        let text = self.escape(text);
+        match self.format {
+            DocFormat::Markdown => format!("**{text}**"),
+            DocFormat::Html => format!("<b>{text}</b>"),
+        }
+    }
+
+    /// `text` must be escaped
+    fn heading(&mut self, level: usize, anchor: Option<&str>, text: &str) {
+        match self.format {
+            DocFormat::Markdown => {
+                let anchor = anchor.map_or(String::new(), |id| format!("<a id=\"{id}\"></a>"));
+                self.out
+                    .push_str(&format!("{} {anchor}{text}\n\n", "#".repeat(level)));
+            }
+            DocFormat::Html => {
+                let id = anchor.map_or(String::new(), |id| format!(" id=\"{id}\""));
+                self.out
+                    .push_str(&format!("<h{level}{id}>{text}</h{level}>\n"));
+            }
+        }
+    }
+
+    /// `text` must be escaped
+    fn paragraph(&mut self, text: &str) {
+        match self.format {
+            DocFormat::Markdown => self.out.push_str(&format!("{text}\n\n")),
+            DocFormat::Html => self.out.push_str(&format!("<p>{text}</p>\n")),
+        }
+    }
+
+    /// `text` must be escaped
+    fn signature(&mut self, text: &str) {
+        match self.format {
+            DocFormat::Markdown => self.out.push_str(&format!("> {text}\n\n")),
+            DocFormat::Html => self
+                .out
+                .push_str(&format!("<pre class=\"signature\">{text}</pre>\n")),
+        }
+    }
+
+    /// `items` must be escaped
+    fn list(&mut self, items: &[String]) {
+        match self.format {
+            DocFormat::Markdown => {
+                for item in items {
+                    self.out.push_str(&format!("- {item}\n"));
+                }
+                self.out.push('\n');
+            }
+            DocFormat::Html => {
+                self.out.push_str("<ul>\n");
+                for item in items {
+                    self.out.push_str(&format!("<li>{item}</li>\n"));
+                }
+                self.out.push_str("</ul>\n");
+            }
+        }
+    }
+
+    /// Markdown doc comments are written as is (Markdown) or converted into paragraphs and code blocks (HTML).
+    fn doc(&mut self, lines: &[String]) {
+        if lines.is_empty() {
+            return;
+        }
+        if self.format == DocFormat::Markdown {
+            for line in lines {
+                self.out.push_str(line);
+                self.out.push('\n');
+            }
+            self.out.push('\n');
+            return;
+        }
+        let mut paragraph = vec![];
+        let mut code: Option<(String, Vec<&str>)> = None;
+        for line in lines {
+            if let Some(lang) = line.trim_start().strip_prefix("```") {
+                if let Some((lang, code)) = code.take() {
+                    let code = self.escape(&code.join("\n"));
+                    self.out.push_str(&format!(
+                        "<pre><code class=\"language-{lang}\">{code}</code></pre>\n"
+                    ));
+                } else {
+                    self.flush_paragraph(&mut paragraph);
+                    code = Some((lang.trim().to_string(), vec![]));
+                }
+            } else if let Some((_, code)) = code.as_mut() {
+                code.push(line);
+            } else if line.trim().is_empty() {
+                self.flush_paragraph(&mut paragraph);
+            } else {
+                paragraph.push(line.trim());
+            }
+        }
+        self.flush_paragraph(&mut paragraph);
+    }
+
+    fn flush_paragraph(&mut self, paragraph: &mut Vec<&str>) {
+        if paragraph.is_empty() {
+            return;
+        }
+        // `code` => <code>code</code>
+        let text = self.escape(&paragraph.join(" "));
+        let mut html = String::new();
+        for (i, part) in text.split('`').enumerate() {
+            if i % 2 == 1 {
+                html.push_str(&format!("<code>{part}</code>"));
+            } else {
+                html.push_str(part);
+            }
+        }
+        self.paragraph(&html);
+        paragraph.clear();
+    }
+
+    fn finish(self, title: &str) -> String {
+        let title = self.escape(title);
+        match self.format {
+            DocFormat::Markdown => self.out.trim_end().to_string() + "\n",
+            DocFormat::Html => format!(
+                "\
+<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 60em; margin: auto; padding: 1em; }}
+pre {{ background: #f4f4f4; padding: 0.5em; overflow-x: auto; }}
+pre.signature {{ border-left: 3px solid #888; }}
+</style>
+</head>
+<body>
+{}</body>
+</html>
+",
+                self.out
+            ),
+        }
+    }
+}
+
+/// `erg doc [PATH] [--html] [--output DIR]`
+///
+/// If `PATH` (default: the current directory) is a package root, `src/` is documented.
+/// The documents are written into `DIR` (default: `build/doc` under `PATH`).
+#[derive(Debug, Default)]
+pub struct DocGenerator {
+    cfg: ErgConfig,
+}
+
+impl Runnable for DocGenerator {
+    type Err = CompileError;
+    type Errs = CompileErrors;
+    const NAME: &'static str = "Erg document generator";
+
+    fn new(mut cfg: ErgConfig) -> Self {
+        if cfg.input.is_repl() {
+            cfg.input = Input::file(PathBuf::from("."));
+        }
+        Self { cfg }
+    }
+
+    #[inline]
+    fn cfg(&self) -> &ErgConfig {
+        &self.cfg
+    }
+    #[inline]
+    fn cfg_mut(&mut self) -> &mut ErgConfig {
+        &mut self.cfg
+    }
+
+    #[inline]
+    fn finish(&mut self) {}
+
+    #[inline]
+    fn initialize(&mut self) {}
+
+    #[inline]
+    fn clear(&mut self) {}
+
+    fn exec(&mut self) -> Result<ExitStatus, Self::Errs> {
+        let mut format = DocFormat::Markdown;
+        let mut target = self.input().path().to_path_buf();
+        let mut output = None;
+        let mut args = self.cfg.runtime_args.clone().into_iter();
+        while let Some(arg) = args.next() {
+            match arg {
+                "--html" => format = DocFormat::Html,
+                "--markdown" | "--md" => format = DocFormat::Markdown,
+                "-o" | "--output" => {
+                    let Some(dir) = args.next() else {
+                        eprintln!("the output directory is not specified\n\n{USAGE}");
+                        return Ok(ExitStatus::ERR1);
+                    };
+                    output = Some(PathBuf::from(dir));
+                }
+                other if other.starts_with('-') => {
+                    eprintln!("invalid option: {other}\n\n{USAGE}");
+                    return Ok(ExitStatus::ERR1);
+                }
+                path => target = PathBuf::from(path),
+            }
+        }
+        let target = normalize_path(target);
+        let (root, files) = if target.is_dir() {
+            let is_package = target.join(PackageManifest::FILENAME).exists();
+            let root = if is_package {
+                target.join("src")
+            } else {
+                target.clone()
+            };
+            let files = collect_files(
+                &root,
+                |dir| dir.file_name().is_some_and(|name| name != "build"),
+                is_source_file,
+            );
+            (root, files)
+        } else {
+            let dir = target.parent().unwrap_or(Path::new(".")).to_path_buf();
+            (dir, vec![target.clone()])
+        };
+        let output = output.unwrap_or_else(|| {
+            let base = if target.is_dir() {
+                target.clone()
+            } else {
+                root.clone()
+            };
+            base.join("build").join("doc")
+        });
+        let mut cfg = self.cfg.copy();
+        if target.join(PackageManifest::FILENAME).exists() {
+            let mut resolver = DependencyResolver::new(cfg.copy());
+            if let Some(lockfile) = Lockfile::load(&cfg, &target)? {
+                resolver = resolver.with_lockfile(lockfile);
+            }
+            cfg.packages = resolver.resolve(&target)?.to_config_packages();
+        }
+        let site = DocSite::build(&cfg, &root, &files)?;
+        let io_error = |path: &Path, err| {
+            CompileErrors::from(CompileError::io_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                path,
+                &err,
+            ))
+        };
+        fs::create_dir_all(&output).map_err(|err| io_error(&output, err))?;
+        for (page, content) in site.render(format) {
+            let path = output.join(page);
+            fs::write(&path, content).map_err(|err| io_error(&path, err))?;
+        }
+        println!(
+            "documented {} module(s) into {}",
+            site.modules.len(),
+            output.display()
+        );
+        Ok(ExitStatus::OK)
+    }
+
+    fn eval(&mut self, _src: String) -> Result<String, Self::Errs> {
+        Ok("`erg doc` cannot be used in the REPL".to_string())
+    }
+}
@@ -1,6 +1,7 @@
 use erg_common::error::{ErrorCore, ErrorKind::*, Location, SubMessage};
 use erg_common::io::Input;
-use erg_common::switch_lang;
+use erg_common::style::StyledString;
+use erg_common::{switch_lang, Str};
 
 use crate::error::*;
 
@@ -47,4 +48,106 @@ impl EvalError {
             caused_by,
         )
     }
+
+    pub fn const_recursion_limit_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+        limit: usize,
+    ) -> Self {
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("コンパイル時関数{name}の呼び出しが再帰の上限({limit})を超えました"),
+                    "simplified_chinese" => format!("编译时函数{name}的调用超过了递归上限({limit})"),
+                    "traditional_chinese" => format!("編譯時函數{name}的調用超過了遞歸上限({limit})"),
+                    "english" => format!("the call of the compile-time function {name} exceeded the recursion limit ({limit})"),
+                ),
+                errno,
+                NotConstExpr,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn const_step_limit_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+        limit: usize,
+    ) -> Self {
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("コンパイル時関数{name}の評価が終わりません(呼び出し回数が{limit}回を超えました)"),
+                    "simplified_chinese" => format!("编译时函数{name}的求值未能结束(调用次数超过了{limit}次)"),
+                    "traditional_chinese" => format!("編譯時函數{name}的求值未能結束(調用次數超過了{limit}次)"),
+                    "english" => format!("the evaluation of the compile-time function {name} did not finish (more than {limit} calls)"),
+                ),
+                errno,
+                NotConstExpr,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    /// `chain`: the names and locations of the definitions in the cycle, in the order of dependencies
+    pub fn cyclic_const_def_error(
+        input: Input,
+        errno: usize,
+        caused_by: String,
+        chain: &[(Str, Location)],
+    ) -> Self {
+        let mut names = chain
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        if let Some(first) = names.first().cloned() {
+            names.push(first);
+        }
+        let cycle = names.join(" -> ");
+        let sub_msgs = chain
+            .iter()
+            .enumerate()
+            .map(|(i, (name, loc))| {
+                let depends_on = &chain[(i + 1) % chain.len()].0;
+                let name = StyledString::new(&name[..], Some(ERR), Some(ATTR));
+                let dep = StyledString::new(&depends_on[..], Some(ERR), Some(ATTR));
+                let msg = switch_lang!(
+                    "japanese" => format!("{name}は{dep}に依存しています"),
+                    "simplified_chinese" => format!("{name}依赖于{dep}"),
+                    "traditional_chinese" => format!("{name}依賴於{dep}"),
+                    "english" => format!("{name} depends on {dep}"),
+                );
+                SubMessage::ambiguous_new(*loc, vec![msg], None)
+            })
+            .collect();
+        let loc = chain.first().map_or(Location::Unknown, |(_, loc)| *loc);
+        Self::new(
+            ErrorCore::new(
+                sub_msgs,
+                switch_lang!(
+                    "japanese" => format!("定数の定義が循環しています: {cycle}"),
+                    "simplified_chinese" => format!("常量的定义是循环的: {cycle}"),
+                    "traditional_chinese" => format!("常量的定義是循環的: {cycle}"),
+                    "english" => format!("cyclic constant definition: {cycle}"),
+                ),
+                errno,
+                NameError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
 }
@@ -812,6 +812,108 @@ impl LowerError {
         Self::file_error(input, errno, desc, loc, caused_by, None)
     }
 
+    pub fn manifest_field_not_found_error(
+        input: Input,
+        errno: usize,
+        caused_by: String,
+        field: &str,
+    ) -> Self {
+        let field = StyledStr::new(field, Some(ERR), Some(ATTR));
+        let desc = switch_lang!(
+            "japanese" => format!("package.erに{field}が定義されていません"),
+            "simplified_chinese" => format!("package.er中没有定义{field}"),
+            "traditional_chinese" => format!("package.er中沒有定義{field}"),
+            "english" => format!("{field} is not defined in package.er"),
+        );
+        let loc = Location::Unknown;
+        Self::new(
+            ErrorCore::new(vec![SubMessage::only_loc(loc)], desc, errno, NameError, loc),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn invalid_manifest_field_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        field: &str,
+        expect: &str,
+    ) -> Self {
+        let file = input.filename();
+        let field = StyledStr::new(field, Some(ERR), Some(ATTR));
+        let desc = switch_lang!(
+            "japanese" => format!("{file}の{field}は{expect}でなくてはなりません"),
+            "simplified_chinese" => format!("{file}的{field}必须是{expect}"),
+            "traditional_chinese" => format!("{file}的{field}必須是{expect}"),
+            "english" => format!("{field} in {file} must be {expect}"),
+        );
+        Self::new(
+            ErrorCore::new(vec![SubMessage::only_loc(loc)], desc, errno, TypeError, loc),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn package_not_found_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+        req: &str,
+    ) -> Self {
+        let name = StyledStr::new(name, Some(ERR), Some(ATTR));
+        let desc = switch_lang!(
+            "japanese" => format!("{req}を満たすパッケージ{name}が見つかりません"),
+            "simplified_chinese" => format!("找不到满足{req}的包{name}"),
+            "traditional_chinese" => format!("找不到滿足{req}的包{name}"),
+            "english" => format!("no package {name} matching {req} is found"),
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                desc,
+                errno,
+                ImportError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn package_mismatch_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+        req: &str,
+        found: &str,
+    ) -> Self {
+        let name = StyledStr::new(name, Some(ERR), Some(ATTR));
+        let found = StyledStr::new(found, Some(ERR), Some(ATTR));
+        let desc = switch_lang!(
+            "japanese" => format!("パッケージ{name} ({req})が必要ですが、{found}が見つかりました"),
+            "simplified_chinese" => format!("需要包{name} ({req})，但找到的是{found}"),
+            "traditional_chinese" => format!("需要包{name} ({req})，但找到的是{found}"),
+            "english" => format!("package {name} ({req}) is required, but {found} is found"),
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                desc,
+                errno,
+                ImportError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     pub fn import_error(
         input: Input,
         errno: usize,
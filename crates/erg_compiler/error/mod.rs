@@ -3,6 +3,7 @@ pub mod lower;
 pub mod tycheck;
 
 use std::fmt;
+use std::path::Path;
 
 use erg_common::error::{
     ErrorCore, ErrorDisplay, ErrorKind::*, Location, MultiErrorDisplay, SubMessage,
@@ -318,6 +319,27 @@ caused from: {fn_name}"),
         )
     }
 
+    /// A failure of a file system operation on `path` (e.g. writing the build artifacts)
+    pub fn io_error(input: Input, errno: usize, path: &Path, err: &std::io::Error) -> Self {
+        let path = path.display();
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(Location::Unknown)],
+                switch_lang!(
+                    "japanese" => format!("{path}の操作に失敗しました: {err}"),
+                    "simplified_chinese" => format!("{path}的操作失败: {err}"),
+                    "traditional_chinese" => format!("{path}的操作失敗: {err}"),
+                    "english" => format!("failed to operate on {path}: {err}"),
+                ),
+                errno,
+                IoError,
+                Location::Unknown,
+            ),
+            input,
+            "".to_owned(),
+        )
+    }
+
     pub fn system_exit() -> Self {
         Self::new(
             ErrorCore::new(
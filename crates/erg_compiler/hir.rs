@@ -22,12 +22,12 @@ use erg_parser::token::{Token, TokenKind, DOT};
 
 use crate::ty::constructors::{dict_t, set_t, tuple_t};
 use crate::ty::typaram::TyParam;
-use crate::ty::value::{GenTypeObj, ValueObj};
+use crate::ty::value::{GenTypeObj, TypeObj, ValueObj};
 use crate::ty::{Field, HasType, Type, VisibilityModifier};
 
 use crate::context::eval::type_from_token_kind;
 use crate::error::readable_name;
-use crate::varinfo::VarInfo;
+use crate::varinfo::{VarInfo, VarKind};
 use crate::{impl_t, impl_t_for_enum};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -444,6 +444,10 @@ impl From<Identifier> for Expr {
     }
 }
 
+/// `py_name` of the `base` attribute of newtypes (`UserId = Newtype Nat`).
+/// Newtypes have no runtime representation, so `id.base` is compiled into `id`.
+pub const NEWTYPE_BASE: &str = "<newtype base>";
+
 impl Identifier {
     pub const fn new(raw: ast::Identifier, qual_name: Option<Str>, vi: VarInfo) -> Self {
         Self { raw, qual_name, vi }
@@ -484,6 +488,13 @@ impl Identifier {
         self.vi.py_name.is_some()
     }
 
+    pub fn is_newtype_base(&self) -> bool {
+        self.vi
+            .py_name
+            .as_ref()
+            .is_some_and(|name| &name[..] == NEWTYPE_BASE)
+    }
+
     pub fn is_const(&self) -> bool {
         self.raw.is_const()
     }
@@ -1446,6 +1457,23 @@ impl Call {
         })
     }
 
+    /// `UserId.new(x)`, `UserId(x)` (`UserId = Newtype Nat`)
+    /// Newtype constructors are erased at runtime, so these calls are compiled into `x`.
+    pub fn is_newtype_ctor_call(&self) -> bool {
+        let is_newtype = matches!(
+            self.obj.ref_t().singleton_value(),
+            Some(TyParam::Value(ValueObj::Type(TypeObj::Generated(gen)))) if gen.is_newtype()
+        );
+        let is_ctor = match self.attr_name.as_ref() {
+            Some(ident) => {
+                matches!(&ident.inspect()[..], "new" | "__new__")
+                    && matches!(ident.vi.kind, VarKind::Auto | VarKind::FixedAuto)
+            }
+            None => true,
+        };
+        is_newtype && is_ctor && self.args.pos_args.len() == 1 && self.args.len() == 1
+    }
+
     pub fn return_t(&self) -> Option<&Type> {
         if let Some(attr) = self.attr_name.as_ref() {
             attr.ref_t().return_t()
@@ -2126,7 +2154,7 @@ impl Def {
     pub fn def_kind(&self) -> DefKind {
         match self.body.block.first().unwrap() {
             Expr::Call(call) => match call.obj.show_acc().as_ref().map(|n| &n[..]) {
-                Some("Class") => DefKind::Class,
+                Some("Class" | "Newtype") => DefKind::Class,
                 Some("Inherit") => DefKind::Inherit,
                 Some("Trait") => DefKind::Trait,
                 Some("Subsume") => DefKind::Subsume,
@@ -6,12 +6,14 @@ pub extern crate erg_parser;
 
 pub mod artifact;
 pub mod build_hir;
+pub mod compat;
 mod compile;
 pub use compile::*;
 mod codegen;
 pub mod context;
 pub mod declare;
 pub mod desugar_hir;
+pub mod docgen;
 pub mod effectcheck;
 pub mod error;
 pub mod hir;
@@ -22,9 +24,12 @@ pub mod lower;
 pub mod module;
 pub mod optimize;
 pub mod ownercheck;
+pub mod package;
+pub mod project;
 pub mod transpile;
 pub mod ty;
 pub mod varinfo;
+pub mod watch;
 
 pub use build_hir::HIRBuilder;
 pub use erg_parser::build_ast::ASTBuilder;
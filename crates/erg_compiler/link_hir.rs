@@ -6,7 +6,7 @@ use std::rc::Rc;
 use erg_common::config::ErgConfig;
 use erg_common::dict::Dict as Dic;
 use erg_common::fresh::SharedFreshNameGenerator;
-use erg_common::pathutil::squash;
+use erg_common::pathutil::{squash, NormalizedPathBuf};
 use erg_common::traits::{Locational, Stream};
 use erg_common::Str;
 use erg_common::{enum_unwrap, log};
@@ -24,6 +24,11 @@ use crate::module::SharedModuleCache;
 pub struct Mod {
     variable: Expr,
     definition: Expr,
+    /// `true` while the module body is being linked (i.e. the module is not executed yet at that point)
+    linking: bool,
+    /// bindings of modules in an import cycle (attribute name, binding),
+    /// executed after the attribute is defined in this module
+    deferred: Vec<(Str, Expr)>,
 }
 
 impl Mod {
@@ -31,6 +36,8 @@ impl Mod {
         Self {
             variable,
             definition,
+            linking: true,
+            deferred: vec![],
         }
     }
 }
@@ -42,6 +49,9 @@ pub struct HIRLinker<'a> {
     mod_cache: &'a SharedModuleCache,
     removed_mods: Rc<RefCell<Dic<PathBuf, Mod>>>,
     fresh_gen: SharedFreshNameGenerator,
+    /// (the root directory, the Python package name)
+    /// Modules under the root are not linked but imported as `{package}.{path}` (used by `ProjectBuilder`)
+    package: Option<(PathBuf, Str)>,
 }
 
 impl<'a> HIRLinker<'a> {
@@ -51,23 +61,40 @@ impl<'a> HIRLinker<'a> {
             mod_cache,
             removed_mods: Rc::new(RefCell::new(Dic::new())),
             fresh_gen: SharedFreshNameGenerator::new("hir_linker"),
+            package: None,
         }
     }
 
+    /// Erg modules under `root` are compiled separately and imported as the submodules of the Python package `name`.
+    pub fn with_package(mut self, root: PathBuf, name: Str) -> Self {
+        self.package = Some((NormalizedPathBuf::new(root).to_path_buf(), name));
+        self
+    }
+
     fn inherit(&self, cfg: &'a ErgConfig) -> Self {
         Self {
             cfg,
             mod_cache: self.mod_cache,
             removed_mods: self.removed_mods.clone(),
             fresh_gen: self.fresh_gen.clone(),
+            package: self.package.clone(),
         }
     }
 
     pub fn link(&self, mut main: HIR) -> HIR {
         log!(info "the linking process has started.");
+        // the entry module can also be imported from modules in an import cycle
+        let entry = NormalizedPathBuf::from(self.cfg.input.path()).to_path_buf();
+        self.removed_mods.borrow_mut().insert(
+            entry.clone(),
+            Mod::new(Self::self_module(), Expr::Code(Block::empty())),
+        );
         for chunk in main.module.iter_mut() {
             self.replace_import(chunk);
         }
+        if let Some(entry) = self.removed_mods.borrow_mut().remove(&entry) {
+            Self::insert_deferred(&mut main.module, entry.deferred);
+        }
         // declare all modules first (due to cyclic modules)
         for (i, module) in self.removed_mods.borrow_mut().values_mut().enumerate() {
             main.module.insert(i, take(&mut module.definition));
@@ -377,7 +404,11 @@ impl<'a> HIRLinker<'a> {
             unreachable!()
         };
         let path = Path::new(&path[..]);
-        let path = self.cfg.input.resolve_real_path(path).unwrap();
+        let path = self
+            .cfg
+            .input
+            .resolve_real_path(path, &self.cfg.packages, &self.cfg.search_paths)
+            .unwrap();
         // # module.er
         // self = import "module"
         // ↓
@@ -388,8 +419,12 @@ impl<'a> HIRLinker<'a> {
             *expr = Self::self_module();
             return;
         }
-        // In the case of REPL, entries cannot be used up
-        let hir_cfg = if self.cfg.input.is_repl() {
+        if let Some(mod_name) = self.package_module_name(&path) {
+            *expr = Self::import_module(mod_name, line);
+            return;
+        }
+        // In the case of REPL (or when the modules are compiled separately), entries cannot be used up
+        let hir_cfg = if self.cfg.input.is_repl() || self.package.is_some() {
             self.mod_cache
                 .get(path.as_path())
                 .and_then(|entry| entry.hir.clone().map(|hir| (hir, entry.cfg().clone())))
@@ -417,9 +452,14 @@ impl<'a> HIRLinker<'a> {
             ));
             self.removed_mods
                 .borrow_mut()
-                .insert(path, Mod::new(mod_var.clone(), mod_def));
+                .insert(path.clone(), Mod::new(mod_var.clone(), mod_def));
             let linker = self.inherit(&cfg);
-            let hir = linker.link_child(hir);
+            let mut hir = self.defer_cyclic_bindings(hir, &mod_var);
+            hir = linker.link_child(hir);
+            if let Some(module) = self.removed_mods.borrow_mut().get_mut(&path) {
+                module.linking = false;
+                Self::insert_deferred(&mut hir.module, take(&mut module.deferred));
+            }
             let code = Expr::Code(Block::new(Vec::from(hir.module)));
             let __dict__ = Identifier::public("__dict__");
             let m_dict = mod_var.clone().attr_expr(__dict__);
@@ -441,6 +481,135 @@ impl<'a> HIRLinker<'a> {
         }
     }
 
+    /// `root/foo/bar.er` => `{package}.foo.bar`, `root/foo/__init__.er` => `{package}.foo`
+    fn package_module_name(&self, path: &Path) -> Option<String> {
+        let (root, package) = self.package.as_ref()?;
+        let path = NormalizedPathBuf::from(path).to_path_buf();
+        let rel = path.strip_prefix(root).ok()?.with_extension("");
+        let mut comps = vec![package.to_string()];
+        comps.extend(
+            rel.components()
+                .map(|comp| comp.as_os_str().to_string_lossy().to_string()),
+        );
+        if comps.last().is_some_and(|last| last == "__init__") {
+            comps.pop();
+        }
+        Some(comps.join("."))
+    }
+
+    /// ```python
+    /// __import__("importlib").import_module("{mod_name}")
+    /// ```
+    fn import_module(mod_name: String, line: u32) -> Expr {
+        let __import__ = Expr::from(Identifier::public_with_line(
+            DOT,
+            Str::ever("__import__"),
+            line,
+        ));
+        let str_lit = |s: String| {
+            let token = Token::new(TokenKind::StrLit, format!("\"{s}\""), line, 0);
+            Expr::Lit(Literal::try_from(token).unwrap())
+        };
+        let importlib = __import__.call1(str_lit("importlib".to_string()));
+        Expr::Call(Call::new(
+            importlib,
+            Some(Identifier::public("import_module")),
+            Args::single(PosArg::new(str_lit(mod_name))),
+        ))
+    }
+
+    /// If a module imports a module that is not executed yet (import cycle),
+    /// attribute bindings from it are deferred until the attribute is defined in the imported module.
+    /// ```erg
+    /// # c.er
+    /// d = import "d"
+    /// .f(x) = d.g(x)
+    /// ```
+    /// ```erg
+    /// # d.er
+    /// {f;} = import "c"
+    /// .g(x) = if x <= 0, do 0, do f(x - 1)
+    /// ```
+    /// ↓
+    /// ```python
+    /// # c.er (pseudo code)
+    /// d = ... # exec d.er:
+    ///     _v = _c
+    ///     # f = _v.f (moved)
+    ///     g = lambda x: ...
+    /// f = lambda x: d.g(x)
+    /// exec("f = _v.f", _d.__dict__)
+    /// ```
+    fn defer_cyclic_bindings(&self, mut hir: HIR, mod_var: &Expr) -> HIR {
+        let mut removed_mods = self.removed_mods.borrow_mut();
+        // local name -> the path of the module (not executed yet)
+        let mut cyclic_mods = Dic::new();
+        let mut module = Vec::with_capacity(hir.module.len());
+        for chunk in take(&mut hir.module).into_iter() {
+            let Expr::Def(def) = &chunk else {
+                module.push(chunk);
+                continue;
+            };
+            match def.body.block.first() {
+                Some(Expr::Accessor(Accessor::Attr(attr))) if def.body.block.len() == 1 => {
+                    if let Expr::Accessor(Accessor::Ident(obj)) = attr.obj.as_ref() {
+                        if let Some(cyclic) = cyclic_mods
+                            .get(obj.inspect())
+                            .and_then(|path| removed_mods.get_mut(path))
+                        {
+                            let attr_name = attr.ident.inspect().clone();
+                            let line = chunk.ln_begin().unwrap_or(0);
+                            let code = Expr::Code(Block::new(vec![chunk]));
+                            let m_dict = mod_var.clone().attr_expr(Identifier::public("__dict__"));
+                            let exec =
+                                Expr::Accessor(Accessor::public_with_line(Str::ever("exec"), line));
+                            cyclic.deferred.push((attr_name, exec.call2(code, m_dict)));
+                            continue;
+                        }
+                    }
+                }
+                Some(body @ Expr::Call(call))
+                    if def.body.block.len() == 1
+                        && call.additional_operation() == Some(OperationKind::Import) =>
+                {
+                    if let Some(path) = self.erg_import_path(body) {
+                        if removed_mods.get(&path).is_some_and(|module| module.linking) {
+                            cyclic_mods.insert(def.sig.inspect().clone(), path);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            module.push(chunk);
+        }
+        hir.module = Module::new(module);
+        hir
+    }
+
+    fn erg_import_path(&self, expr: &Expr) -> Option<PathBuf> {
+        let TyParam::Value(ValueObj::Str(path)) = expr.ref_t().typarams().into_iter().next()?
+        else {
+            return None;
+        };
+        self.cfg.input.resolve_real_path(
+            Path::new(&path[..]),
+            &self.cfg.packages,
+            &self.cfg.search_paths,
+        )
+    }
+
+    fn insert_deferred(module: &mut Module, deferred: Vec<(Str, Expr)>) {
+        for (attr_name, binding) in deferred.into_iter() {
+            let pos = module.iter().rposition(
+                |chunk| matches!(chunk, Expr::Def(def) if def.sig.inspect() == &attr_name),
+            );
+            match pos {
+                Some(pos) => module.insert(pos + 1, binding),
+                None => module.push(binding),
+            }
+        }
+    }
+
     /// ```erg
     /// x = pyimport "x" # called from dir "a"
     /// ```
@@ -456,7 +625,11 @@ impl<'a> HIRLinker<'a> {
         let mod_path = self
             .cfg
             .input
-            .resolve_decl_path(Path::new(&mod_name_str[..]))
+            .resolve_decl_path(
+                Path::new(&mod_name_str[..]),
+                &self.cfg.packages,
+                &self.cfg.search_paths,
+            )
             .unwrap();
         if !mod_path
             .canonicalize()
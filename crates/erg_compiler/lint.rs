@@ -33,7 +33,7 @@ impl ASTLowerer {
             .context
             .sub_unify(found, expect, loc, Some(name))
             .map_err(|_| {
-                LowerError::type_mismatch_error(
+                let err = LowerError::type_mismatch_error(
                     self.cfg().input.clone(),
                     line!() as usize,
                     loc.loc(),
@@ -46,7 +46,10 @@ impl ASTLowerer {
                     self.module
                         .context
                         .get_simple_type_mismatch_hint(expect, found),
-                )
+                );
+                // e.g. ?T(:> Int) ==> Int, so that the same mismatch is not reported again when resolving
+                found.destructive_coerce();
+                err
             })
     }
 
@@ -178,7 +181,12 @@ impl ASTLowerer {
             }
             let name_is_auto = &value.name[..] == "_"
                 || !Lexer::is_valid_start_symbol_ch(value.name.chars().next().unwrap_or(' '));
-            if value.referrers.is_empty() && value.vi.vis.is_private() && !name_is_auto {
+            // `@Test` subroutines are called by the test runner
+            if value.referrers.is_empty()
+                && value.vi.vis.is_private()
+                && !name_is_auto
+                && !value.vi.is_test()
+            {
                 let input = referee
                     .module
                     .as_ref()
@@ -26,7 +26,8 @@ use crate::artifact::{CompleteArtifact, IncompleteArtifact};
 use crate::context::instantiate::TyVarCache;
 use crate::module::SharedCompilerResource;
 use crate::ty::constructors::{
-    array_t, free_var, func, guard, mono, poly, proc, refinement, set_t, singleton, ty_tp, v_enum,
+    array_t, free_var, func, guard, instanceof, mono, mono_q, poly, proc, refinement, set_t,
+    singleton, ty_tp, v_enum,
 };
 use crate::ty::free::Constraint;
 use crate::ty::typaram::TyParam;
@@ -675,11 +676,15 @@ impl ASTLowerer {
         }
     }
 
+    /// `match` and `match!` are special forms (but `re.match` is not)
+    fn is_match_special_form(ident: &ast::Identifier) -> bool {
+        ident.vis.is_private()
+            && ControlKind::try_from(&ident.inspect()[..]) == Ok(ControlKind::Match)
+    }
+
     fn lower_ident(&mut self, ident: ast::Identifier) -> LowerResult<hir::Identifier> {
         // `match` is a special form, typing is magic
-        let (vi, __name__) = if ident.vis.is_private()
-            && (&ident.inspect()[..] == "match" || &ident.inspect()[..] == "match!")
-        {
+        let (vi, __name__) = if Self::is_match_special_form(&ident) {
             (
                 VarInfo {
                     t: mono("GenericCallable"),
@@ -957,7 +962,16 @@ impl ASTLowerer {
         } else {
             None
         };
-        let hir_args = self.lower_args(call.args, &mut errs);
+        let type_app = self.module.context.eval_poly_type_app(&call);
+        let is_match = call.attr_name.is_none()
+            && matches!(
+                call.obj.as_ref(),
+                ast::Expr::Accessor(ast::Accessor::Ident(ident)) if Self::is_match_special_form(ident)
+            );
+        let mut hir_args = self.lower_args(call.args, &mut errs);
+        if is_match {
+            self.refine_gadt_arms(&mut hir_args);
+        }
         let mut obj = match self.lower_expr(*call.obj) {
             Ok(obj) => obj,
             Err(es) => {
@@ -984,6 +998,12 @@ impl ASTLowerer {
         if let Err(es) = self.module.context.propagate(&mut vi.t, &obj) {
             errs.extend(es);
         }
+        // `Box(Int)`: {Box(?T)} => {Box(Int)}
+        if let Some(t) = type_app {
+            if let Some(ret_t) = vi.t.mut_return_t() {
+                *ret_t = v_enum(set! { ValueObj::Type(t) });
+            }
+        }
         if let Some(guard) = guard {
             debug_assert!(
                 self.module
@@ -1020,6 +1040,41 @@ impl ASTLowerer {
         Ok(call)
     }
 
+    /// In a `match` arm, the type variables of the target are refined by the pattern type.
+    /// If the arm returns a value of the refined type, the return type of the arm is the type variable.
+    /// ```erg
+    /// eval|T|(e: Expr(T)): T = match e:
+    ///     (i: IntLit) -> i.v # IntLit <: Expr(Int), T == Int
+    ///     (s: StrLit) -> s.v # StrLit <: Expr(Str), T == Str
+    /// ```
+    fn refine_gadt_arms(&self, args: &mut hir::Args) {
+        let Some(target_t) = args.pos_args.first().map(|arg| arg.expr.t()) else {
+            return;
+        };
+        for arg in args.pos_args.iter_mut().skip(1) {
+            let hir::Expr::Lambda(lambda) = &mut arg.expr else {
+                continue;
+            };
+            let Some(pat_t) = lambda
+                .params
+                .non_defaults
+                .first()
+                .map(|param| param.vi.t.clone())
+            else {
+                continue;
+            };
+            for (var, refined) in self.module.context.get_gadt_refinements(&target_t, &pat_t) {
+                let Some(return_t) = lambda.t.mut_return_t() else {
+                    continue;
+                };
+                if self.module.context.subtype_of(return_t, &refined) {
+                    *return_t = var;
+                    break;
+                }
+            }
+        }
+    }
+
     /// importing is done in [preregister](https://github.com/erg-lang/erg/blob/ffd33015d540ff5a0b853b28c01370e46e0fcc52/crates/erg_compiler/context/register.rs#L819)
     fn exec_additional_op(&mut self, call: &mut hir::Call) -> LowerResult<()> {
         match call.additional_operation() {
@@ -1427,6 +1482,10 @@ impl ASTLowerer {
             .context
             .instantiate_vis_modifier(def.sig.vis())?;
         let res = match def.sig {
+            ast::Signature::Subr(sig) if def.body.def_kind().is_class() => {
+                self.module.context.grow(&name, kind, vis, None);
+                self.lower_poly_type_def(sig, def.body)
+            }
             ast::Signature::Subr(sig) => {
                 let tv_cache = self
                     .module
@@ -1527,6 +1586,32 @@ impl ASTLowerer {
         }
     }
 
+    /// `Box T = Class { .v = T }` is lowered as `Box = Class { .v = T }` (`T` is a type variable).
+    /// The type of `Box` has already been registered by `register_poly_type_def`.
+    fn lower_poly_type_def(
+        &mut self,
+        sig: ast::SubrSignature,
+        body: ast::DefBody,
+    ) -> LowerResult<hir::Def> {
+        log!(info "entered {}({sig})", fn_name!());
+        for param in sig.params.non_defaults.iter() {
+            if let Some(name) = param.name() {
+                let ident = ast::Identifier::new(VisModifierSpec::Private, name.clone());
+                let t = mono_q(name.inspect(), instanceof(Type::Type));
+                self.module
+                    .context
+                    .register_type_alias(&ident, t, Type::Type)?;
+                if let Some(vi) = self.module.context.decls.remove(name) {
+                    // type parameters may be phantom (e.g. `Expr T = Inheritable Class()`)
+                    self.inc_ref(name.inspect(), &vi, name);
+                    self.module.context.locals.insert(name.clone(), vi);
+                }
+            }
+        }
+        let sig = ast::VarSignature::new(ast::VarPattern::Ident(sig.ident), None);
+        self.lower_var_def(sig, body)
+    }
+
     // NOTE: Note that this is in the inner scope while being called.
     fn lower_subr_def(
         &mut self,
@@ -1747,7 +1832,15 @@ impl ASTLowerer {
             }
             self.check_collision_and_push(class, impl_trait.map(|(t, _)| t));
         }
-        let class = self.module.context.gen_type(&hir_def.sig.ident().raw);
+        // polymorphic classes (e.g. `Box T = Class { .v = T }`) are registered as `Box(T)`
+        let class = match self
+            .module
+            .context
+            .rec_get_const_obj(hir_def.sig.ident().inspect())
+        {
+            Some(ValueObj::Type(t)) if !t.typ().is_monomorphic() => t.typ().clone(),
+            _ => self.module.context.gen_type(&hir_def.sig.ident().raw),
+        };
         let Some((_, class_ctx)) = self.module.context.get_nominal_type_ctx(&class) else {
             return Err(LowerErrors::from(LowerError::type_not_found(
                 self.cfg.input.clone(),
@@ -2282,7 +2375,7 @@ impl ASTLowerer {
                     Self::get_require_or_sup_or_base(call.args.remove_left_or_key("Class").unwrap())
                 }
                 Some("Structural") => call.args.remove_left_or_key("Type"),
-                Some("Patch") => call.args.remove_left_or_key("Base"),
+                Some("Patch" | "Newtype") => call.args.remove_left_or_key("Base"),
                 _ => todo!(),
             },
             other => todo!("{other}"),
@@ -2516,6 +2609,56 @@ impl ASTLowerer {
         self.warn_unused_local_vars(mode);
     }
 
+    /// The first phase of analyzing mutually recursive modules.
+    /// Registers constants, classes and signatures of the top-level definitions. Imports are not followed.
+    pub(crate) fn declare_toplevel(&mut self, ast: AST) {
+        log!(info "the declaring process of top-level names has started.");
+        let path = self.cfg.input.path();
+        self.module.context.shared().graph.add_node_if_none(path);
+        let Ok(ast) = ASTLinker::new(self.cfg.clone()).link(ast, "exec") else {
+            return;
+        };
+        let decls = ast
+            .module
+            .into_iter()
+            .filter(|chunk| !matches!(chunk, ast::Expr::Def(def) if def.def_kind().is_import()))
+            .collect::<Vec<_>>();
+        let block = ast::Block::new(decls);
+        let _ = self.module.context.preregister_const(&block);
+        let _ = self.module.context.register_const(&block);
+        // variables without type specifications are typed by their values if they can be evaluated at compile time
+        for chunk in block.iter() {
+            let ast::Expr::Def(def) = chunk else {
+                continue;
+            };
+            let ast::Signature::Var(sig) = &def.sig else {
+                continue;
+            };
+            let (None, Some(name)) = (&sig.t_spec, sig.inspect()) else {
+                continue;
+            };
+            let Ok(obj) = self.module.context.eval_const_block(&def.body.block) else {
+                continue;
+            };
+            if let Some(vi) = self.module.context.future_defined_locals.get(&name[..]) {
+                if vi.t.is_unbound_var() {
+                    vi.t.destructive_link(&obj.class());
+                }
+            }
+        }
+        // other modules can refer to them before the module is lowered
+        // (variables of unknown types are not declared, e.g. `.x = b.f()` where `b` refers to `x`)
+        let declared = std::mem::take(&mut self.module.context.future_defined_locals);
+        self.module.context.decls.extend(
+            declared
+                .into_iter()
+                .filter(|(_, vi)| !vi.t.is_unbound_var()),
+        );
+        self.module.context.clear_invalid_vars();
+        self.warns.clear();
+        log!(info "the declaring process of top-level names has completed.");
+    }
+
     pub fn lower(&mut self, ast: AST, mode: &str) -> Result<CompleteArtifact, IncompleteArtifact> {
         log!(info "the AST lowering process has started.");
         log!(info "the type-checking process has started.");
@@ -2549,6 +2692,11 @@ impl ASTLowerer {
             self.errs.extend(errs);
         }
         for chunk in ast.module.into_iter() {
+            if self.module.context.shared().cancel_token.is_cancelled() {
+                log!(info "the AST lowering process has been cancelled.");
+                let hir = HIR::new(ast.name, module);
+                return Err(self.return_incomplete_artifact(hir));
+            }
             match self.lower_chunk(chunk) {
                 Ok(chunk) => {
                     module.push(chunk);
@@ -253,6 +253,20 @@ impl SharedModuleCache {
         self.0.borrow_mut().register(path.into(), hir, ctx);
     }
 
+    /// Register the module only if it has not been registered yet (by another thread).
+    pub fn register_if_absent<P: Into<NormalizedPathBuf>>(
+        &self,
+        path: P,
+        hir: Option<HIR>,
+        ctx: ModuleContext,
+    ) {
+        let path = path.into();
+        let mut cache = self.0.borrow_mut();
+        if cache.get(&path).is_none() {
+            cache.register(path, hir, ctx);
+        }
+    }
+
     pub fn remove<Q: Eq + Hash + ?Sized>(&self, path: &Q) -> Option<ModuleEntry>
     where
         NormalizedPathBuf: Borrow<Q>,
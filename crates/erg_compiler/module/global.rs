@@ -1,6 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use erg_common::config::ErgConfig;
+use erg_common::shared::CancellationToken;
 
 use crate::context::Context;
 
@@ -24,6 +25,8 @@ pub struct SharedCompilerResource {
     pub promises: SharedPromises,
     pub errors: SharedCompileErrors,
     pub warns: SharedCompileWarnings,
+    /// The checking process is aborted when this is cancelled (e.g. by the language server when the file has been edited).
+    pub cancel_token: CancellationToken,
 }
 
 impl SharedCompilerResource {
@@ -43,6 +46,7 @@ impl SharedCompilerResource {
             ),
             errors: SharedCompileErrors::new(),
             warns: SharedCompileWarnings::new(),
+            cancel_token: CancellationToken::new(),
         };
         Context::init_builtins(cfg, self_.clone());
         self_
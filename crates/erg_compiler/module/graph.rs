@@ -19,12 +19,16 @@ impl IncRefError {
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct ModuleGraph(Graph<NormalizedPathBuf, ()>);
+pub struct ModuleGraph {
+    graph: Graph<NormalizedPathBuf, ()>,
+    /// the modules in an import cycle (the edges which make cycles are not added to `graph`)
+    cyclic: Set<NormalizedPathBuf>,
+}
 
 impl fmt::Display for ModuleGraph {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "ModuleGraph {{")?;
-        for node in self.0.iter() {
+        for node in self.graph.iter() {
             writeln!(f, "{} depends on {{", node.id.display())?;
             for dep in node.depends_on.iter() {
                 writeln!(f, "{}, ", dep.display())?;
@@ -40,18 +44,21 @@ impl IntoIterator for ModuleGraph {
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.graph.into_iter()
     }
 }
 
 impl ModuleGraph {
     pub fn new() -> Self {
-        Self(Graph::new())
+        Self {
+            graph: Graph::new(),
+            cyclic: set! {},
+        }
     }
 
     pub fn get_node(&self, path: &Path) -> Option<&Node<NormalizedPathBuf, ()>> {
         let path = NormalizedPathBuf::new(path.to_path_buf());
-        self.0.iter().find(|n| n.id == path)
+        self.graph.iter().find(|n| n.id == path)
     }
 
     /// if `path` depends on `target`, returns `true`, else `false`.
@@ -59,7 +66,7 @@ impl ModuleGraph {
     pub fn depends_on(&self, path: &Path, target: &Path) -> bool {
         let path = NormalizedPathBuf::new(path.to_path_buf());
         let target = NormalizedPathBuf::new(target.to_path_buf());
-        self.0
+        self.graph
             .iter()
             .find(|n| n.id == path)
             .map(|n| n.depends_on.contains(&target))
@@ -68,7 +75,7 @@ impl ModuleGraph {
 
     pub fn children(&self, path: &Path) -> Set<NormalizedPathBuf> {
         let path = NormalizedPathBuf::new(path.to_path_buf());
-        self.0
+        self.graph
             .iter()
             .filter(|n| n.depends_on.contains(&path))
             .map(|n| n.id.clone())
@@ -77,7 +84,10 @@ impl ModuleGraph {
 
     fn parents(&self, path: &Path) -> Option<&Set<NormalizedPathBuf>> {
         let path = NormalizedPathBuf::new(path.to_path_buf());
-        self.0.iter().find(|n| n.id == path).map(|n| &n.depends_on)
+        self.graph
+            .iter()
+            .find(|n| n.id == path)
+            .map(|n| &n.depends_on)
     }
 
     /// ```erg
@@ -98,9 +108,9 @@ impl ModuleGraph {
 
     pub fn add_node_if_none(&mut self, path: &Path) {
         let path = NormalizedPathBuf::new(path.to_path_buf());
-        if self.0.iter().all(|n| n.id != path) {
+        if self.graph.iter().all(|n| n.id != path) {
             let node = Node::new(path, (), set! {});
-            self.0.push(node);
+            self.graph.push(node);
         }
     }
 
@@ -109,9 +119,10 @@ impl ModuleGraph {
         let referrer = NormalizedPathBuf::new(referrer.to_path_buf());
         let depends_on = NormalizedPathBuf::new(depends_on);
         if self.ancestors(&depends_on).contains(&referrer) && referrer != depends_on {
+            self.register_cycle(&referrer, depends_on);
             return Err(IncRefError::CycleDetected);
         }
-        if let Some(node) = self.0.iter_mut().find(|n| n.id == referrer) {
+        if let Some(node) = self.graph.iter_mut().find(|n| n.id == referrer) {
             if referrer == depends_on {
                 return Ok(());
             }
@@ -122,13 +133,32 @@ impl ModuleGraph {
         Ok(())
     }
 
+    /// `referrer` -> `depends_on` -> ... -> `referrer`
+    fn register_cycle(&mut self, referrer: &NormalizedPathBuf, depends_on: NormalizedPathBuf) {
+        let on_cycle = self
+            .ancestors(&depends_on)
+            .into_iter()
+            .filter(|path| path == referrer || self.ancestors(path).contains(referrer))
+            .collect::<Vec<_>>();
+        self.cyclic.extend(on_cycle);
+        self.cyclic.insert(referrer.clone());
+        self.cyclic.insert(depends_on);
+    }
+
+    /// Is `path` a part of an import cycle?
+    pub fn is_cyclic(&self, path: &Path) -> bool {
+        let path = NormalizedPathBuf::new(path.to_path_buf());
+        self.cyclic.contains(&path)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &Node<NormalizedPathBuf, ()>> {
-        self.0.iter()
+        self.graph.iter()
     }
 
     #[allow(clippy::result_unit_err)]
     pub fn sorted(self) -> Result<Self, TopoSortError> {
-        tsort(self.0).map(Self)
+        let cyclic = self.cyclic;
+        tsort(self.graph).map(|graph| Self { graph, cyclic })
     }
 
     #[allow(clippy::result_unit_err)]
@@ -140,13 +170,14 @@ impl ModuleGraph {
     /// Do not erase relationships with modules that depend on `path`
     pub fn remove(&mut self, path: &Path) {
         let path = NormalizedPathBuf::new(path.to_path_buf());
-        self.0.retain(|n| n.id != path);
+        self.graph.retain(|n| n.id != path);
+        self.cyclic.remove(&path);
     }
 
     pub fn rename_path(&mut self, old: &Path, new: PathBuf) {
         let old = NormalizedPathBuf::new(old.to_path_buf());
         let new = NormalizedPathBuf::new(new);
-        for node in self.0.iter_mut() {
+        for node in self.graph.iter_mut() {
             if node.id == old {
                 node.id = new.clone();
             }
@@ -155,10 +186,14 @@ impl ModuleGraph {
             }
             node.depends_on.retain(|p| *p != old);
         }
+        if self.cyclic.remove(&old) {
+            self.cyclic.insert(new);
+        }
     }
 
     pub fn initialize(&mut self) {
-        self.0.clear();
+        self.graph.clear();
+        self.cyclic = set! {};
     }
 }
 
@@ -218,6 +253,10 @@ impl SharedModuleGraph {
         self.0.borrow_mut().inc_ref(referrer, depends_on)
     }
 
+    pub fn is_cyclic(&self, path: &Path) -> bool {
+        self.0.borrow().is_cyclic(path)
+    }
+
     pub fn ref_inner(&self) -> RwLockReadGuard<ModuleGraph> {
         self.0.borrow()
     }
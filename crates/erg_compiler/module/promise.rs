@@ -103,6 +103,14 @@ impl SharedPromises {
             .insert(path, Promise::running(handle));
     }
 
+    /// Registers a module built outside of the import process (e.g. by the language server),
+    /// so that the importers use the cached module instead of building it again.
+    pub fn mark_as_finished<P: Into<NormalizedPathBuf>>(&self, path: P) {
+        self.promises
+            .borrow_mut()
+            .insert(path.into(), Promise::Finished);
+    }
+
     pub fn remove(&self, path: &Path) {
         self.promises.borrow_mut().remove(path);
     }
@@ -130,8 +138,16 @@ impl SharedPromises {
     }
 
     fn join_checked(&self, path: &Path, promise: Promise) -> std::thread::Result<()> {
-        let Promise::Running { handle, parent } = promise else {
-            return Ok(());
+        let (handle, parent) = match promise {
+            Promise::Running { handle, parent } => (handle, parent),
+            Promise::Finished => {
+                // `take` replaced it with `Joining`, so restore it (otherwise other threads will wait forever)
+                *self.promises.borrow_mut().get_mut(path).unwrap() = Promise::Finished;
+                return Ok(());
+            }
+            Promise::Joining => {
+                return Ok(());
+            }
         };
         if self.graph.ancestors(path).contains(&self.path) || handle.thread().id() == current().id()
         {
@@ -12,11 +12,33 @@ use crate::module::SharedCompilerResource;
 pub struct HIROptimizer {
     cfg: ErgConfig,
     shared: SharedCompilerResource,
+    /// the public definitions may be referenced by other modules
+    /// (the module is a part of an import cycle or a package)
+    keep_public: bool,
 }
 
 impl HIROptimizer {
     pub fn optimize(cfg: ErgConfig, shared: SharedCompilerResource, hir: HIR) -> HIR {
-        let mut optimizer = HIROptimizer { cfg, shared };
+        Self::optimize_with(cfg, shared, hir, false)
+    }
+
+    /// Optimizes a module of a package, which can be imported by any Python code.
+    pub fn optimize_library(cfg: ErgConfig, shared: SharedCompilerResource, hir: HIR) -> HIR {
+        Self::optimize_with(cfg, shared, hir, true)
+    }
+
+    fn optimize_with(
+        cfg: ErgConfig,
+        shared: SharedCompilerResource,
+        hir: HIR,
+        is_library: bool,
+    ) -> HIR {
+        let keep_public = is_library || shared.graph.is_cyclic(cfg.input.path());
+        let mut optimizer = HIROptimizer {
+            cfg,
+            shared,
+            keep_public,
+        };
         if optimizer.cfg.opt_level == 0 || optimizer.cfg.input.is_repl() {
             return hir;
         }
@@ -37,7 +59,12 @@ impl HIROptimizer {
     fn eliminate_unused_def(&mut self, expr: &mut Expr) {
         match expr {
             Expr::Def(def) => {
-                if def.sig.ident().is_discarded() {
+                // public definitions may be referenced by modules in an import cycle or the users of the package
+                // `@Test` subroutines are called by the test runner
+                if def.sig.ident().is_discarded()
+                    || (def.sig.vis().is_public() && self.keep_public)
+                    || def.sig.ident().vi.is_test()
+                {
                     return;
                 }
                 if self
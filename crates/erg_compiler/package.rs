@@ -0,0 +1,1127 @@
+//! defines `PackageManifest`, the typed contents of `package.er`, and `DependencyResolver`.
+//!
+//! `package.er` is an ordinary Erg module, which is type-checked by the compiler and then evaluated.
+//! `package.lock.er` is evaluated in the same way.
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use erg_common::config::{ErgConfig, Package};
+use erg_common::env::erg_pkgs_path;
+use erg_common::error::Location;
+use erg_common::levenshtein::get_similar_name;
+use erg_common::traits::{Locational, Runnable, Stream};
+use erg_common::{dict, normalize_path, Str};
+
+use crate::build_hir::HIRBuilder;
+use crate::error::{CompileError, CompileErrors};
+use crate::hir::{Accessor, Array, Expr, HIR};
+use crate::ty::value::ValueObj;
+use crate::ty::Field;
+
+/// `pack` can be used in `package.er` without importing.
+const MANIFEST_PRELUDE: &str = "pack name: Str, version := \"*\", path := \"\" = \
+    { .name = name; .version = version; .path = path }";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PackageType {
+    /// an executable package (`src/main.er`)
+    #[default]
+    App,
+    /// a library package (`src/lib.er`)
+    Lib,
+    /// both of them
+    Hybrid,
+}
+
+impl FromStr for PackageType {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "app" => Ok(Self::App),
+            "lib" => Ok(Self::Lib),
+            "hybrid" => Ok(Self::Hybrid),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for PackageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::App => write!(f, "app"),
+            Self::Lib => write!(f, "lib"),
+            Self::Hybrid => write!(f, "hybrid"),
+        }
+    }
+}
+
+impl PackageType {
+    pub const fn is_app(&self) -> bool {
+        matches!(self, Self::App | Self::Hybrid)
+    }
+
+    pub const fn is_lib(&self) -> bool {
+        matches!(self, Self::Lib | Self::Hybrid)
+    }
+}
+
+/// `major.minor.patch(-pre)(+build)`
+///
+/// The build metadata is ignored in comparisons (`1.0.0+a == 1.0.0+b`).
+#[derive(Debug, Clone, Default)]
+pub struct SemVer {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Option<String>,
+    pub build: Option<String>,
+}
+
+impl PartialEq for SemVer {
+    fn eq(&self, other: &Self) -> bool {
+        (self.major, self.minor, self.patch, &self.pre)
+            == (other.major, other.minor, other.patch, &other.pre)
+    }
+}
+
+impl Eq for SemVer {}
+
+impl Hash for SemVer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.major, self.minor, self.patch, &self.pre).hash(state);
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// a pre-release version is lower than the normal version (`1.0.0-alpha < 1.0.0`)
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(l), Some(r)) => cmp_pre_release(l, r),
+            })
+    }
+}
+
+/// Compares the dot-separated identifiers of pre-release versions one by one
+/// (`1.0.0-alpha < 1.0.0-alpha.1 < 1.0.0-beta < 1.0.0-rc.2 < 1.0.0-rc.10`).
+/// Numeric identifiers are compared numerically and have lower precedence than alphanumeric ones.
+fn cmp_pre_release(l: &str, r: &str) -> Ordering {
+    let is_numeric = |id: &str| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit());
+    let mut l_ids = l.split('.');
+    let mut r_ids = r.split('.');
+    loop {
+        let ord = match (l_ids.next(), r_ids.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) => match (is_numeric(l), is_numeric(r)) {
+                // numeric identifiers have no leading zeros, so the longer one is larger
+                (true, true) => l.len().cmp(&r.len()).then_with(|| l.cmp(r)),
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => l.cmp(r),
+            },
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+impl fmt::Display for SemVer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{pre}")?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{build}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SemVer {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let (s, build) = match s.split_once('+') {
+            Some((s, build)) if !build.is_empty() => (s, Some(build.to_string())),
+            Some(_) => return Err(()),
+            None => (s, None),
+        };
+        let (ver, pre) = match s.split_once('-') {
+            Some((ver, pre)) if !pre.is_empty() => (ver, Some(pre.to_string())),
+            Some(_) => return Err(()),
+            None => (s, None),
+        };
+        let mut nums = ver.split('.').map(|n| n.parse::<u64>().map_err(|_| ()));
+        let (Some(major), Some(minor), Some(patch), None) =
+            (nums.next(), nums.next(), nums.next(), nums.next())
+        else {
+            return Err(());
+        };
+        Ok(Self {
+            major: major?,
+            minor: minor?,
+            patch: patch?,
+            pre,
+            build,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum CmpOp {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    /// `^1.2.3` (`>=1.2.3, <2.0.0`)
+    Caret,
+    /// `~1.2.3` (`>=1.2.3, <1.3.0`)
+    Tilde,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Comparator {
+    /// `*`, `1.*.*`, `1.2.*`
+    Wildcard {
+        major: Option<u64>,
+        minor: Option<u64>,
+    },
+    Op(CmpOp, SemVer),
+}
+
+impl FromStr for Comparator {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let s = s.trim();
+        if s.contains('*') {
+            let mut nums = s.split('.');
+            let mut next = || match nums.next() {
+                None | Some("*") => Ok(None),
+                Some(n) => n.parse::<u64>().map(Some).map_err(|_| ()),
+            };
+            let (major, minor, patch) = (next()?, next()?, next()?);
+            // `1.*.3` is not allowed
+            if patch.is_some() || (major.is_none() && minor.is_some()) || nums.next().is_some() {
+                return Err(());
+            }
+            return Ok(Self::Wildcard { major, minor });
+        }
+        let (op, ver) = if let Some(ver) = s.strip_prefix(">=") {
+            (CmpOp::Ge, ver)
+        } else if let Some(ver) = s.strip_prefix("<=") {
+            (CmpOp::Le, ver)
+        } else if let Some(ver) = s.strip_prefix('>') {
+            (CmpOp::Gt, ver)
+        } else if let Some(ver) = s.strip_prefix('<') {
+            (CmpOp::Lt, ver)
+        } else if let Some(ver) = s.strip_prefix('^') {
+            (CmpOp::Caret, ver)
+        } else if let Some(ver) = s.strip_prefix('~') {
+            (CmpOp::Tilde, ver)
+        } else {
+            (CmpOp::Eq, s.strip_prefix('=').unwrap_or(s))
+        };
+        Ok(Self::Op(op, ver.trim().parse()?))
+    }
+}
+
+impl Comparator {
+    fn matches(&self, ver: &SemVer) -> bool {
+        match self {
+            Self::Wildcard { major, minor } => {
+                !matches!(major, Some(major) if *major != ver.major)
+                    && !matches!(minor, Some(minor) if *minor != ver.minor)
+            }
+            Self::Op(op, req) => {
+                let upper = |major, minor, patch| SemVer {
+                    major,
+                    minor,
+                    patch,
+                    pre: Some("0".to_string()),
+                    build: None,
+                };
+                match op {
+                    CmpOp::Eq => ver == req,
+                    CmpOp::Gt => ver > req,
+                    CmpOp::Ge => ver >= req,
+                    CmpOp::Lt => ver < req,
+                    CmpOp::Le => ver <= req,
+                    CmpOp::Caret => {
+                        let bound = if req.major > 0 {
+                            upper(req.major + 1, 0, 0)
+                        } else if req.minor > 0 {
+                            upper(0, req.minor + 1, 0)
+                        } else {
+                            upper(0, 0, req.patch + 1)
+                        };
+                        ver >= req && ver < &bound
+                    }
+                    CmpOp::Tilde => ver >= req && ver < &upper(req.major, req.minor + 1, 0),
+                }
+            }
+        }
+    }
+
+    /// Does this comparator explicitly mention the pre-release of `ver`?
+    fn allows_pre(&self, ver: &SemVer) -> bool {
+        match self {
+            Self::Wildcard { .. } => false,
+            Self::Op(_, req) => {
+                req.pre.is_some()
+                    && (req.major, req.minor, req.patch) == (ver.major, ver.minor, ver.patch)
+            }
+        }
+    }
+}
+
+/// A version requirement of a dependency.
+///
+/// * `*`, `1.*.*`, `1.2.*`: wildcards
+/// * `1.2.3`, `=1.2.3`: exactly the version
+/// * `>1.2.3`, `>=1.2.3`, `<1.2.3`, `<=1.2.3`: comparisons
+/// * `^1.2.3`: compatible versions (`>=1.2.3, <2.0.0`; `>=0.2.3, <0.3.0` for `0.*.*`)
+/// * `~1.2.3`: patch updates (`>=1.2.3, <1.3.0`)
+///
+/// Comparators separated by `,` must all be satisfied.
+/// A pre-release version matches only if a comparator has a pre-release of the same `x.y.z`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionReq {
+    src: Str,
+    comparators: Vec<Comparator>,
+}
+
+impl Default for VersionReq {
+    fn default() -> Self {
+        Self {
+            src: Str::ever("*"),
+            comparators: vec![Comparator::Wildcard {
+                major: None,
+                minor: None,
+            }],
+        }
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.src)
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let comparators = s
+            .split(',')
+            .map(|cmp| cmp.parse())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            src: Str::rc(s.trim()),
+            comparators,
+        })
+    }
+}
+
+impl VersionReq {
+    pub fn matches(&self, ver: &SemVer) -> bool {
+        (ver.pre.is_none() || self.comparators.iter().any(|cmp| cmp.allows_pre(ver)))
+            && self.comparators.iter().all(|cmp| cmp.matches(ver))
+    }
+}
+
+/// `as_name = pack(name, version, path)` in `dependencies`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Dependency {
+    /// the name used in the package (may differ from `name` when the package is renamed)
+    pub as_name: Str,
+    pub name: Str,
+    /// the version requirement (e.g. `1.*.*`, `*`)
+    pub version: Str,
+    /// the package root relative to the depending package (a path dependency)
+    pub path: Option<PathBuf>,
+    pub loc: Location,
+}
+
+impl Dependency {
+    pub fn version_req(&self) -> VersionReq {
+        self.version.parse().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageManifest {
+    pub name: Str,
+    pub author: Str,
+    pub version: SemVer,
+    pub description: Str,
+    pub categories: Vec<Str>,
+    pub package_type: PackageType,
+    pub license: Str,
+    /// a script executed before build
+    pub pre_build: Option<PathBuf>,
+    /// a script executed after build
+    pub post_build: Option<PathBuf>,
+    pub dependencies: Vec<Dependency>,
+    pub deprecated: bool,
+    /// alternative packages (when the package is deprecated)
+    pub successors: Vec<Str>,
+}
+
+impl PackageManifest {
+    pub const FILENAME: &'static str = "package.er";
+
+    /// Type-checks and evaluates `<root>/package.er`.
+    pub fn load(cfg: &ErgConfig, root: &Path) -> Result<Self, CompileErrors> {
+        let path = root.join(Self::FILENAME);
+        ManifestEvaluator::load(cfg, &path, Some(MANIFEST_PRELUDE))?.into_manifest()
+    }
+
+    /// The dependency named `as_name` in the package.
+    pub fn get_dependency(&self, as_name: &str) -> Option<&Dependency> {
+        self.dependencies
+            .iter()
+            .find(|dep| &dep.as_name[..] == as_name)
+    }
+}
+
+fn is_pack(expr: &Expr) -> bool {
+    matches!(expr, Expr::Accessor(Accessor::Ident(ident)) if &ident.inspect()[..] == "pack")
+}
+
+struct ManifestEvaluator {
+    cfg: ErgConfig,
+    /// the values of the top-level variables
+    binds: Vec<(Str, ValueObj, Location)>,
+    errors: CompileErrors,
+}
+
+impl ManifestEvaluator {
+    fn new(cfg: ErgConfig) -> Self {
+        Self {
+            cfg,
+            binds: vec![],
+            errors: CompileErrors::empty(),
+        }
+    }
+
+    /// Type-checks `path` (after `prelude`) and evaluates its top-level definitions.
+    fn load(cfg: &ErgConfig, path: &Path, prelude: Option<&str>) -> Result<Self, CompileErrors> {
+        let mut cfg = cfg.inherit(path.to_path_buf());
+        if !path.exists() {
+            let desc = format!("{} not found", path.display());
+            return Err(CompileErrors::from(CompileError::file_error(
+                cfg.input.clone(),
+                line!() as usize,
+                desc,
+                Location::Unknown,
+                "".into(),
+                None,
+            )));
+        }
+        let mut builder = HIRBuilder::new(cfg.copy());
+        if let Some(prelude) = prelude {
+            builder
+                .build(prelude.into(), "exec")
+                .map_err(|arti| arti.errors)?;
+        }
+        let src = cfg.input.read();
+        let hir = builder
+            .build(src, "exec")
+            .map_err(|arti| arti.errors)?
+            .object;
+        let mut evaluator = Self::new(cfg);
+        evaluator.eval(hir);
+        Ok(evaluator)
+    }
+
+    fn eval(&mut self, hir: HIR) {
+        for chunk in hir.module.into_iter() {
+            let Expr::Def(mut def) = chunk else {
+                continue;
+            };
+            let name = def.sig.inspect().clone();
+            let loc = def.loc();
+            let errs_len = self.errors.len();
+            if let Some(val) = self.expr_into_value(def.body.block.remove(0)) {
+                self.binds.push((name, val, loc));
+            } else if self.errors.len() == errs_len {
+                self.errors.push(CompileError::not_const_expr(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    loc,
+                    "".into(),
+                ));
+            }
+        }
+    }
+
+    fn into_manifest(mut self) -> Result<PackageManifest, CompileErrors> {
+        let manifest = PackageManifest {
+            name: self.get_str("name", true),
+            author: self.get_str("author", false),
+            version: self.get_version(),
+            description: self.get_str("description", false),
+            categories: self.get_str_array("categories"),
+            package_type: self.get_package_type(),
+            license: self.get_str("license", false),
+            pre_build: self.get_path("pre_build"),
+            post_build: self.get_path("post_build"),
+            dependencies: self.get_dependencies(),
+            deprecated: self.get_bool("deprecated"),
+            successors: self.get_str_array("successors"),
+        };
+        if self.errors.is_empty() {
+            Ok(manifest)
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn expr_into_value(&mut self, expr: Expr) -> Option<ValueObj> {
+        match expr {
+            Expr::Lit(lit) => Some(lit.value),
+            Expr::Array(Array::Normal(arr)) => {
+                let mut vals = vec![];
+                for elem in arr.elems.pos_args {
+                    vals.push(self.expr_into_value(elem.expr)?);
+                }
+                Some(ValueObj::Array(vals.into()))
+            }
+            Expr::Record(rec) => {
+                let mut attrs = dict! {};
+                for mut attr in rec.attrs {
+                    let field = Field::public(attr.sig.ident().inspect().clone());
+                    let val = self.expr_into_value(attr.body.block.remove(0))?;
+                    attrs.insert(field, val);
+                }
+                Some(ValueObj::Record(attrs))
+            }
+            Expr::Accessor(Accessor::Ident(ident)) => {
+                let name = ident.inspect();
+                self.binds
+                    .iter()
+                    .rfind(|(bind, ..)| bind == name)
+                    .map(|(_, val, _)| val.clone())
+            }
+            // pack(name, version := "*", path := "")
+            Expr::Call(mut call) if is_pack(&call.obj) && call.attr_name.is_none() => {
+                const PARAMS: [&str; 3] = ["name", "version", "path"];
+                let mut name = None;
+                let mut version = ValueObj::Str(Str::ever("*"));
+                let mut path = ValueObj::Str(Str::ever(""));
+                let (pos_len, kw_len) = (call.args.pos_args.len(), call.args.kw_args.len());
+                if pos_len > PARAMS.len() {
+                    self.errors.push(CompileError::too_many_args_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        call.loc(),
+                        "pack",
+                        "".into(),
+                        PARAMS.len(),
+                        pos_len,
+                        kw_len,
+                    ));
+                    return None;
+                }
+                for (i, arg) in call.args.pos_args.into_iter().enumerate() {
+                    match i {
+                        0 => name = Some(self.expr_into_value(arg.expr)?),
+                        1 => version = self.expr_into_value(arg.expr)?,
+                        _ => path = self.expr_into_value(arg.expr)?,
+                    }
+                }
+                for arg in std::mem::take(&mut call.args.kw_args) {
+                    match &arg.keyword.inspect()[..] {
+                        "name" => name = Some(self.expr_into_value(arg.expr)?),
+                        "version" => version = self.expr_into_value(arg.expr)?,
+                        "path" => path = self.expr_into_value(arg.expr)?,
+                        other => {
+                            self.errors.push(CompileError::unexpected_kw_arg_error(
+                                self.cfg.input.clone(),
+                                line!() as usize,
+                                arg.loc(),
+                                "pack",
+                                "".into(),
+                                other,
+                                get_similar_name(PARAMS.into_iter(), other),
+                            ));
+                            return None;
+                        }
+                    }
+                }
+                Some(ValueObj::Record(dict! {
+                    Field::public(Str::ever("name")) => name?,
+                    Field::public(Str::ever("version")) => version,
+                    Field::public(Str::ever("path")) => path,
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    fn get(&self, field: &str) -> Option<&(Str, ValueObj, Location)> {
+        self.binds.iter().rfind(|(name, ..)| &name[..] == field)
+    }
+
+    fn invalid_field(&mut self, field: &str, loc: Location, expect: &str) {
+        self.errors.push(CompileError::invalid_manifest_field_error(
+            self.cfg.input.clone(),
+            line!() as usize,
+            loc,
+            "".into(),
+            field,
+            expect,
+        ));
+    }
+
+    fn get_str(&mut self, field: &str, required: bool) -> Str {
+        match self.get(field).cloned() {
+            Some((_, ValueObj::Str(s), _)) => s,
+            Some((.., loc)) => {
+                self.invalid_field(field, loc, "Str");
+                Str::ever("")
+            }
+            None if required => {
+                self.errors
+                    .push(CompileError::manifest_field_not_found_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        "".into(),
+                        field,
+                    ));
+                Str::ever("")
+            }
+            None => Str::ever(""),
+        }
+    }
+
+    fn get_bool(&mut self, field: &str) -> bool {
+        match self.get(field).cloned() {
+            Some((_, ValueObj::Bool(b), _)) => b,
+            Some((.., loc)) => {
+                self.invalid_field(field, loc, "Bool");
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn get_path(&mut self, field: &str) -> Option<PathBuf> {
+        let path = self.get_str(field, false);
+        (!path.is_empty()).then(|| PathBuf::from(&path[..]))
+    }
+
+    fn get_str_array(&mut self, field: &str) -> Vec<Str> {
+        match self.get(field).cloned() {
+            Some((_, ValueObj::Array(arr), loc)) => {
+                let mut strs = vec![];
+                for elem in arr.iter() {
+                    if let ValueObj::Str(s) = elem {
+                        strs.push(s.clone());
+                    } else {
+                        self.invalid_field(field, loc, "Array(Str)");
+                        break;
+                    }
+                }
+                strs
+            }
+            Some((.., loc)) => {
+                self.invalid_field(field, loc, "Array(Str)");
+                vec![]
+            }
+            None => vec![],
+        }
+    }
+
+    fn get_version(&mut self) -> SemVer {
+        let loc = self
+            .get("version")
+            .map_or(Location::Unknown, |(.., loc)| *loc);
+        let version = self.get_str("version", true);
+        if version.is_empty() {
+            return SemVer::default();
+        }
+        version.parse().unwrap_or_else(|_| {
+            self.invalid_field("version", loc, "a semantic version (e.g. \"0.1.0\")");
+            SemVer::default()
+        })
+    }
+
+    fn get_package_type(&mut self) -> PackageType {
+        let loc = self.get("type").map_or(Location::Unknown, |(.., loc)| *loc);
+        let ty = self.get_str("type", false);
+        if ty.is_empty() {
+            return PackageType::default();
+        }
+        ty.parse().unwrap_or_else(|_| {
+            self.invalid_field("type", loc, "\"app\", \"lib\" or \"hybrid\"");
+            PackageType::default()
+        })
+    }
+
+    fn get_dependencies(&mut self) -> Vec<Dependency> {
+        let Some((_, deps, loc)) = self.get("dependencies").cloned() else {
+            return vec![];
+        };
+        let ValueObj::Record(deps) = deps else {
+            self.invalid_field("dependencies", loc, "a record of `pack(...)`");
+            return vec![];
+        };
+        let mut dependencies = vec![];
+        for (as_name, dep) in deps.iter() {
+            let ValueObj::Record(dep) = dep else {
+                self.invalid_field("dependencies", loc, "a record of `pack(...)`");
+                continue;
+            };
+            let get = |key: &str| {
+                dep.iter().find_map(|(field, val)| match val {
+                    ValueObj::Str(s) if &field.symbol[..] == key => Some(s.clone()),
+                    _ => None,
+                })
+            };
+            let (Some(name), Some(version)) = (get("name"), get("version")) else {
+                self.invalid_field("dependencies", loc, "a record of `pack(...)`");
+                continue;
+            };
+            if version.parse::<VersionReq>().is_err() {
+                self.invalid_field(
+                    "dependencies",
+                    loc,
+                    "a version requirement (e.g. \"1.*.*\", \">=1.2.0, <2.0.0\")",
+                );
+                continue;
+            }
+            let path = get("path")
+                .filter(|path| !path.is_empty())
+                .map(|path| PathBuf::from(&path[..]));
+            dependencies.push(Dependency {
+                as_name: as_name.symbol.clone(),
+                name,
+                version,
+                path,
+                loc,
+            });
+        }
+        dependencies.sort_by(|l, r| l.as_name.cmp(&r.as_name));
+        dependencies
+    }
+}
+
+/// Where a resolved package comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PackageSource {
+    /// the package being built
+    Root,
+    /// `pack(..., path := "...")`, the path is relative to the root package
+    Path(PathBuf),
+    /// the local package cache (`<cache>/<name>/<version>/`)
+    Cache,
+}
+
+impl fmt::Display for PackageSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Root => write!(f, "root"),
+            Self::Path(path) => write!(f, "path+{}", path.display()),
+            Self::Cache => write!(f, "cache"),
+        }
+    }
+}
+
+impl FromStr for PackageSource {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "root" => Ok(Self::Root),
+            "cache" => Ok(Self::Cache),
+            _ => s
+                .strip_prefix("path+")
+                .map(|path| Self::Path(PathBuf::from(path)))
+                .ok_or(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPackage {
+    pub name: Str,
+    pub version: SemVer,
+    /// the canonicalized directory containing `package.er`
+    pub root: PathBuf,
+    pub source: PackageSource,
+    /// (the name used in the package, the index of the dependency in `Resolution::packages`)
+    pub deps: Vec<(Str, usize)>,
+}
+
+/// The result of `DependencyResolver::resolve`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution {
+    /// `packages[0]` is the root package
+    pub packages: Vec<ResolvedPackage>,
+}
+
+impl Resolution {
+    pub fn root(&self) -> &ResolvedPackage {
+        &self.packages[0]
+    }
+
+    /// The packages to be set to `ErgConfig::packages` (to resolve `import "<dep>"`).
+    pub fn to_config_packages(&self) -> Vec<Package> {
+        self.packages
+            .iter()
+            .map(|pkg| Package {
+                name: pkg.name.to_string(),
+                version: pkg.version.to_string(),
+                root: pkg.root.clone(),
+                deps: pkg
+                    .deps
+                    .iter()
+                    .map(|(as_name, idx)| (as_name.to_string(), self.packages[*idx].root.clone()))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    pub fn to_lockfile(&self) -> Lockfile {
+        let packages = self
+            .packages
+            .iter()
+            .map(|pkg| {
+                let mut dependencies = pkg
+                    .deps
+                    .iter()
+                    .map(|(as_name, idx)| {
+                        let dep = &self.packages[*idx];
+                        Str::from(format!("{as_name} = {}@{}", dep.name, dep.version))
+                    })
+                    .collect::<Vec<_>>();
+                dependencies.sort_by(|l, r| l[..].cmp(&r[..]));
+                LockedPackage {
+                    name: pkg.name.clone(),
+                    version: pkg.version.clone(),
+                    source: pkg.source.clone(),
+                    dependencies,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut lockfile = Lockfile { packages };
+        lockfile.sort();
+        lockfile
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub name: Str,
+    pub version: SemVer,
+    pub source: PackageSource,
+    /// `as_name = name@version`
+    pub dependencies: Vec<Str>,
+}
+
+/// `package.lock.er`, which records the resolved versions of all (transitive) dependencies.
+///
+/// `DependencyResolver` prefers the locked versions of cached packages if they satisfy the requirements.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Lockfile {
+    pub packages: Vec<LockedPackage>,
+}
+
+impl fmt::Display for Lockfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# This file is generated by `erg pack`. Do not edit it manually."
+        )?;
+        writeln!(f, "packages = {{")?;
+        for pkg in self.packages.iter() {
+            let deps = pkg
+                .dependencies
+                .iter()
+                .map(|dep| format!("{:?}", &dep[..]))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                f,
+                "    '{}@{}' = {{source = \"{}\"; dependencies = [{deps}]}}",
+                pkg.name, pkg.version, pkg.source,
+            )?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+impl Lockfile {
+    pub const FILENAME: &'static str = "package.lock.er";
+
+    /// Loads `<root>/package.lock.er` if it exists.
+    pub fn load(cfg: &ErgConfig, root: &Path) -> Result<Option<Self>, CompileErrors> {
+        let path = root.join(Self::FILENAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        ManifestEvaluator::load(cfg, &path, None)?
+            .into_lockfile()
+            .map(Some)
+    }
+
+    /// The root package first, then the others in order of (name, version, source).
+    fn sort(&mut self) {
+        self.packages.sort_by(|l, r| {
+            (
+                l.source != PackageSource::Root,
+                &l.name[..],
+                &l.version,
+                l.source.to_string(),
+            )
+                .cmp(&(
+                    r.source != PackageSource::Root,
+                    &r.name[..],
+                    &r.version,
+                    r.source.to_string(),
+                ))
+        });
+    }
+
+    pub fn write(&self, root: &Path) -> std::io::Result<()> {
+        std::fs::write(root.join(Self::FILENAME), self.to_string())
+    }
+
+    fn locked_versions<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a SemVer> {
+        self.packages
+            .iter()
+            .filter(move |pkg| &pkg.name[..] == name && pkg.source == PackageSource::Cache)
+            .map(|pkg| &pkg.version)
+    }
+}
+
+impl ManifestEvaluator {
+    fn into_lockfile(mut self) -> Result<Lockfile, CompileErrors> {
+        let expect = "a record of `'name@version' = {source; dependencies}`";
+        let mut packages = vec![];
+        match self.get("packages").cloned() {
+            Some((_, ValueObj::Record(pkgs), loc)) => {
+                for (id, pkg) in pkgs.iter() {
+                    let get = |key: &str| match pkg {
+                        ValueObj::Record(pkg) => pkg
+                            .iter()
+                            .find(|(field, _)| &field.symbol[..] == key)
+                            .map(|(_, val)| val),
+                        _ => None,
+                    };
+                    let (
+                        Some((name, version)),
+                        Some(ValueObj::Str(source)),
+                        Some(ValueObj::Array(deps)),
+                    ) = (
+                        id.symbol.trim_matches('\'').split_once('@'),
+                        get("source"),
+                        get("dependencies"),
+                    )
+                    else {
+                        self.invalid_field("packages", loc, expect);
+                        continue;
+                    };
+                    let (Ok(version), Ok(source)) = (version.parse(), source.parse()) else {
+                        self.invalid_field("packages", loc, expect);
+                        continue;
+                    };
+                    let dependencies = deps
+                        .iter()
+                        .filter_map(|dep| match dep {
+                            ValueObj::Str(dep) => Some(dep.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    packages.push(LockedPackage {
+                        name: Str::rc(name),
+                        version,
+                        source,
+                        dependencies,
+                    });
+                }
+            }
+            Some((.., loc)) => self.invalid_field("packages", loc, expect),
+            None => {}
+        }
+        if self.errors.is_empty() {
+            let mut lockfile = Lockfile { packages };
+            lockfile.sort();
+            Ok(lockfile)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// Resolves the dependencies of a package offline.
+///
+/// * path dependencies (`pack("foo", path := "../foo")`) are resolved relative to the depending package
+/// * the other dependencies are searched in the package cache (`<cache>/<name>/<version>/package.er`,
+///   `$ERG_PATH/lib/pkgs` by default); the highest version satisfying the requirement is selected,
+///   but locked versions take precedence
+pub struct DependencyResolver {
+    cfg: ErgConfig,
+    cache_dir: PathBuf,
+    lockfile: Lockfile,
+}
+
+impl DependencyResolver {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self {
+            cfg,
+            cache_dir: erg_pkgs_path().clone(),
+            lockfile: Lockfile::default(),
+        }
+    }
+
+    pub fn with_cache_dir(mut self, cache_dir: PathBuf) -> Self {
+        self.cache_dir = cache_dir;
+        self
+    }
+
+    pub fn with_lockfile(mut self, lockfile: Lockfile) -> Self {
+        self.lockfile = lockfile;
+        self
+    }
+
+    /// Resolves the (transitive) dependencies of the package at `root`.
+    pub fn resolve(&self, root: &Path) -> Result<Resolution, CompileErrors> {
+        let root = normalize_path(root.canonicalize().unwrap_or_else(|_| root.to_path_buf()));
+        let manifest = PackageManifest::load(&self.cfg, &root)?;
+        let mut packages = vec![ResolvedPackage {
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            root: root.clone(),
+            source: PackageSource::Root,
+            deps: vec![],
+        }];
+        let mut unresolved = vec![(0, manifest)];
+        while let Some((idx, manifest)) = unresolved.pop() {
+            let input = self
+                .cfg
+                .inherit(packages[idx].root.join(PackageManifest::FILENAME))
+                .input;
+            for dep in manifest.dependencies.iter() {
+                let req = dep.version_req();
+                let (dep_root, source) = if let Some(path) = &dep.path {
+                    let Ok(dep_root) = packages[idx].root.join(path).canonicalize() else {
+                        return Err(CompileErrors::from(CompileError::package_not_found_error(
+                            input,
+                            line!() as usize,
+                            dep.loc,
+                            "".into(),
+                            &dep.name,
+                            &format!("{req} (path: {})", path.display()),
+                        )));
+                    };
+                    let dep_root = normalize_path(dep_root);
+                    let rel = relative_path(&root, &dep_root);
+                    (dep_root, PackageSource::Path(rel))
+                } else {
+                    let Some(dep_root) = self.find_in_cache(&dep.name, &req) else {
+                        return Err(CompileErrors::from(CompileError::package_not_found_error(
+                            input,
+                            line!() as usize,
+                            dep.loc,
+                            "".into(),
+                            &dep.name,
+                            &req.to_string(),
+                        )));
+                    };
+                    (dep_root, PackageSource::Cache)
+                };
+                if let Some(dep_idx) = packages.iter().position(|pkg| pkg.root == dep_root) {
+                    packages[idx].deps.push((dep.as_name.clone(), dep_idx));
+                    continue;
+                }
+                let dep_manifest = PackageManifest::load(&self.cfg, &dep_root)?;
+                if dep_manifest.name != dep.name || !req.matches(&dep_manifest.version) {
+                    return Err(CompileErrors::from(CompileError::package_mismatch_error(
+                        input,
+                        line!() as usize,
+                        dep.loc,
+                        "".into(),
+                        &dep.name,
+                        &req.to_string(),
+                        &format!("{}@{}", dep_manifest.name, dep_manifest.version),
+                    )));
+                }
+                let dep_idx = packages.len();
+                packages.push(ResolvedPackage {
+                    name: dep_manifest.name.clone(),
+                    version: dep_manifest.version.clone(),
+                    root: dep_root,
+                    source,
+                    deps: vec![],
+                });
+                packages[idx].deps.push((dep.as_name.clone(), dep_idx));
+                unresolved.push((dep_idx, dep_manifest));
+            }
+        }
+        Ok(Resolution { packages })
+    }
+
+    /// Finds `<cache>/<name>/<version>/` satisfying `req`.
+    fn find_in_cache(&self, name: &str, req: &VersionReq) -> Option<PathBuf> {
+        let entries = std::fs::read_dir(self.cache_dir.join(name)).ok()?;
+        let candidates = entries
+            .flatten()
+            .filter_map(|entry| {
+                let version = entry.file_name().to_str()?.parse::<SemVer>().ok()?;
+                let root = entry.path();
+                (req.matches(&version) && root.join(PackageManifest::FILENAME).exists())
+                    .then_some((version, root))
+            })
+            .collect::<Vec<_>>();
+        let locked = candidates
+            .iter()
+            .filter(|(ver, _)| {
+                self.lockfile
+                    .locked_versions(name)
+                    .any(|locked| locked == ver)
+            })
+            .max_by(|(l, _), (r, _)| l.cmp(r));
+        let (_, root) = locked.or_else(|| candidates.iter().max_by(|(l, _), (r, _)| l.cmp(r)))?;
+        root.canonicalize().ok().map(normalize_path)
+    }
+}
+
+/// `to` relative to `from` (both must be absolute).
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from = from.components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let mut rel = PathBuf::new();
+    for _ in common..from.len() {
+        rel.push("..");
+    }
+    for comp in to[common..].iter() {
+        rel.push(comp);
+    }
+    if rel.as_os_str().is_empty() {
+        rel.push(".");
+    }
+    rel
+}
@@ -0,0 +1,328 @@
+//! defines `ProjectBuilder`, which compiles all modules of a project into a Python package.
+//!
+//! Unlike `Compiler`, which links the imported Erg modules into the entry module,
+//! each module is compiled into its own file and imported with `importlib`.
+//! ```console
+//! src/               dist/
+//!     main.er            _erg_std_prelude.py, ... (the runtime)
+//!     foo/               app/
+//!         bar.er             __init__.py
+//!                            main.pyc
+//!                            foo/
+//!                                __init__.py
+//!                                bar.pyc
+//! ```
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use erg_common::config::{ErgConfig, ErgMode};
+use erg_common::dict::Dict;
+use erg_common::env::erg_std_path;
+use erg_common::error::MultiErrorDisplay;
+use erg_common::pathutil::{collect_files, NormalizedPathBuf};
+use erg_common::traits::{ExitStatus, Runnable, Stream};
+use erg_common::Str;
+
+use crate::artifact::CompleteArtifact;
+use crate::build_hir::HIRBuilder;
+use crate::codegen::PyCodeGenerator;
+use crate::desugar_hir::HIRDesugarer;
+use crate::error::{CompileError, CompileErrors, CompileWarnings};
+use crate::hir::HIR;
+use crate::link_hir::HIRLinker;
+use crate::module::SharedCompilerResource;
+use crate::optimize::HIROptimizer;
+use crate::package::{Lockfile, PackageManifest};
+use crate::transpile::PyScriptGenerator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OutputFormat {
+    #[default]
+    Pyc,
+    /// transpiled Python scripts
+    Py,
+}
+
+impl OutputFormat {
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Pyc => "pyc",
+            Self::Py => "py",
+        }
+    }
+}
+
+/// `foo-bar` => `foo_bar`
+fn python_package_name(name: &str) -> Str {
+    let name = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        Str::from(format!("_{name}"))
+    } else {
+        Str::from(name)
+    }
+}
+
+/// `*.er` except for `*.d.er` and the package files
+pub(crate) fn is_source_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    name.ends_with(".er")
+        && !name.ends_with(".d.er")
+        && name != PackageManifest::FILENAME
+        && name != Lockfile::FILENAME
+}
+
+/// Builds all Erg modules under `root` into `dist/{name}/`.
+///
+/// `erg compile <DIR> [--dist <DIST>]` (`erg transpile <DIR>` for `.py`)
+#[derive(Debug)]
+pub struct ProjectBuilder {
+    cfg: ErgConfig,
+    shared: SharedCompilerResource,
+    root: PathBuf,
+    /// the name of the Python package
+    name: Str,
+    dist: PathBuf,
+    format: OutputFormat,
+}
+
+impl Default for ProjectBuilder {
+    fn default() -> Self {
+        Self::new(ErgConfig::default())
+    }
+}
+
+impl Runnable for ProjectBuilder {
+    type Err = CompileError;
+    type Errs = CompileErrors;
+    const NAME: &'static str = "Erg project builder";
+
+    fn new(cfg: ErgConfig) -> Self {
+        let root = cfg.input.path().to_path_buf();
+        let name = NormalizedPathBuf::new(root.clone())
+            .file_name()
+            .map_or("main".to_string(), |name| {
+                name.to_string_lossy().to_string()
+            });
+        let dist = cfg
+            .dist_dir
+            .map_or_else(|| root.join("build"), PathBuf::from);
+        let format = if cfg.mode == ErgMode::Transpile {
+            OutputFormat::Py
+        } else {
+            OutputFormat::Pyc
+        };
+        Self::new_project(cfg, root, &name, dist).with_format(format)
+    }
+
+    #[inline]
+    fn cfg(&self) -> &ErgConfig {
+        &self.cfg
+    }
+    #[inline]
+    fn cfg_mut(&mut self) -> &mut ErgConfig {
+        &mut self.cfg
+    }
+
+    #[inline]
+    fn finish(&mut self) {}
+
+    fn initialize(&mut self) {
+        self.shared.clear_all();
+    }
+
+    fn clear(&mut self) {}
+
+    fn exec(&mut self) -> Result<ExitStatus, Self::Errs> {
+        let artifact = self.build()?;
+        artifact.warns.write_all_stderr();
+        println!(
+            "built {} module(s) into {}",
+            artifact.object.len(),
+            self.package_dir().display()
+        );
+        Ok(ExitStatus::compile_passed(artifact.warns.len()))
+    }
+
+    fn eval(&mut self, _src: String) -> Result<String, Self::Errs> {
+        Ok("the project builder cannot be used in the REPL".to_string())
+    }
+}
+
+impl ProjectBuilder {
+    pub fn new_project(cfg: ErgConfig, root: PathBuf, name: &str, dist: PathBuf) -> Self {
+        Self {
+            shared: SharedCompilerResource::new(cfg.copy()),
+            cfg,
+            root,
+            name: python_package_name(name),
+            dist,
+            format: OutputFormat::default(),
+        }
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// `dist/{name}`
+    pub fn package_dir(&self) -> PathBuf {
+        self.dist.join(&self.name[..])
+    }
+
+    /// Type-checks and compiles all modules (in the topological order of the module graph).
+    /// Returns the generated files.
+    pub fn build(&mut self) -> Result<CompleteArtifact<Vec<PathBuf>>, CompileErrors> {
+        let dist = NormalizedPathBuf::new(self.dist.clone());
+        let sources = collect_files(
+            &self.root,
+            |dir| NormalizedPathBuf::new(dir.to_path_buf()) != dist,
+            is_source_file,
+        );
+        let mut errors = CompileErrors::empty();
+        let mut warns = CompileWarnings::empty();
+        // the modules checked as entry points (not imported by the modules checked before)
+        let mut entries = Dict::new();
+        for path in sources.iter() {
+            let normalized = NormalizedPathBuf::new(path.clone());
+            if self.shared.mod_cache.get(&normalized).is_some() {
+                continue;
+            }
+            let mut cfg = self.cfg.inherit(path.clone());
+            let src = cfg.input.read();
+            let shared = self.shared.inherit(path.clone());
+            let mut builder = HIRBuilder::new_with_cache(cfg.copy(), "<module>", shared);
+            match builder.build(src, "exec") {
+                Ok(artifact) => {
+                    warns.extend(artifact.warns);
+                    entries.insert(normalized, (artifact.object, cfg));
+                }
+                Err(artifact) => {
+                    warns.extend(artifact.warns);
+                    errors.extend(artifact.errors);
+                }
+            }
+        }
+        if !errors.is_empty() {
+            warns.write_all_stderr();
+            return Err(errors);
+        }
+        // if there are cyclic imports, the modules are compiled in the registered order
+        let _ = self.shared.graph.sort();
+        let mut modules = self
+            .shared
+            .graph
+            .ref_inner()
+            .iter()
+            .map(|node| node.id.clone())
+            .filter(|path| {
+                sources
+                    .iter()
+                    .any(|src| path == &NormalizedPathBuf::new(src.clone()))
+            })
+            .collect::<Vec<_>>();
+        for src in sources.iter() {
+            let src = NormalizedPathBuf::new(src.clone());
+            if !modules.contains(&src) {
+                modules.push(src);
+            }
+        }
+        let mut outputs = vec![];
+        for path in modules {
+            let hir_cfg = entries.remove(&path).or_else(|| {
+                let entry = self.shared.mod_cache.get(&path)?;
+                Some((entry.hir.clone()?, entry.cfg().clone()))
+            });
+            if let Some((hir, cfg)) = hir_cfg {
+                outputs.push(self.emit(&path, hir, &cfg)?);
+            }
+        }
+        self.write_package_inits()?;
+        if self.format == OutputFormat::Pyc {
+            self.copy_runtime()?;
+        }
+        Ok(CompleteArtifact::new(outputs, warns))
+    }
+
+    fn io_error(&self, errno: u32, path: &Path, err: io::Error) -> CompileErrors {
+        CompileErrors::from(CompileError::io_error(
+            self.cfg.input.clone(),
+            errno as usize,
+            path,
+            &err,
+        ))
+    }
+
+    fn emit(&self, path: &Path, hir: HIR, cfg: &ErgConfig) -> Result<PathBuf, CompileErrors> {
+        let linker = HIRLinker::new(cfg, &self.shared.mod_cache)
+            .with_package(self.root.clone(), self.name.clone());
+        let hir = HIRDesugarer::desugar(linker.link(hir));
+        let root = NormalizedPathBuf::new(self.root.clone()).to_path_buf();
+        let rel = path.strip_prefix(root).unwrap_or(path);
+        let out = self
+            .package_dir()
+            .join(rel)
+            .with_extension(self.format.extension());
+        if let Some(dir) = out.parent() {
+            fs::create_dir_all(dir).map_err(|err| self.io_error(line!(), dir, err))?;
+        }
+        let res = match self.format {
+            OutputFormat::Pyc => {
+                let hir = HIROptimizer::optimize_library(cfg.copy(), self.shared.clone(), hir);
+                let code = PyCodeGenerator::new(cfg.copy()).emit(hir);
+                code.dump_as_pyc(&out, cfg.py_magic_num)
+            }
+            OutputFormat::Py => {
+                let script = PyScriptGenerator::new().transpile(hir);
+                fs::write(&out, script.code)
+            }
+        };
+        res.map_err(|err| self.io_error(line!(), &out, err))?;
+        Ok(out)
+    }
+
+    /// Makes each directory of the package a regular Python package.
+    fn write_package_inits(&self) -> Result<(), CompileErrors> {
+        fn visit(dir: &Path) -> Result<(), (PathBuf, io::Error)> {
+            let has_init = ["__init__.pyc", "__init__.py"]
+                .iter()
+                .any(|init| dir.join(init).exists());
+            if !has_init {
+                let init = dir.join("__init__.py");
+                fs::write(&init, "").map_err(|err| (init, err))?;
+            }
+            let entries = fs::read_dir(dir).map_err(|err| (dir.to_path_buf(), err))?;
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() && entry.file_name() != "__pycache__" {
+                    visit(&path)?;
+                }
+            }
+            Ok(())
+        }
+        let package_dir = self.package_dir();
+        fs::create_dir_all(&package_dir)
+            .map_err(|err| self.io_error(line!(), &package_dir, err))?;
+        visit(&package_dir).map_err(|(path, err)| self.io_error(line!(), &path, err))
+    }
+
+    /// Copies the runtime modules (`_erg_*.py`) imported by the compiled code.
+    fn copy_runtime(&self) -> Result<(), CompileErrors> {
+        let entries = fs::read_dir(erg_std_path())
+            .map_err(|err| self.io_error(line!(), erg_std_path(), err))?;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("_erg_") && name.ends_with(".py") {
+                let dest = self.dist.join(&name);
+                fs::copy(entry.path(), &dest).map_err(|err| self.io_error(line!(), &dest, err))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,19 +1,35 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
 use erg_common::config::ErgConfig;
 use erg_common::error::MultiErrorDisplay;
 use erg_common::io::Output;
+use erg_common::pathutil::NormalizedPathBuf;
+use erg_common::python_util::opt_which_python;
 use erg_common::set;
 use erg_common::spawn::exec_new_thread;
-use erg_common::traits::Runnable;
+use erg_common::traits::{Runnable, Stream};
+use erg_common::Str;
 
+use erg_compiler::build_hir::HIRBuilder;
+use erg_compiler::compat::{check_api_compat, VersionBump};
 use erg_compiler::context::{Context, ModuleContext};
+use erg_compiler::docgen::{DocFormat, DocSite};
 use erg_compiler::error::CompileErrors;
 use erg_compiler::lower::ASTLowerer;
+use erg_compiler::package::{
+    DependencyResolver, Lockfile, PackageManifest, PackageSource, PackageType, SemVer, VersionReq,
+};
+use erg_compiler::project::ProjectBuilder;
 
 use erg_compiler::ty::constructors::{
     array_t, func0, func1, func2, kw, mono, nd_func, nd_proc, or, poly, proc1, subtype_q, ty_tp,
     type_q, unknown_len_array_mut, unknown_len_array_t, v_enum,
 };
 use erg_compiler::ty::Type::*;
+use erg_compiler::watch::Watcher;
 
 fn load_file(path: &'static str) -> Result<ModuleContext, CompileErrors> {
     let mut cfg = ErgConfig::with_main_path(path.into());
@@ -107,6 +123,277 @@ fn test_intersection() -> Result<(), ()> {
     Ok(())
 }
 
+#[test]
+fn test_docgen() -> Result<(), ()> {
+    exec_new_thread(_test_docgen, "test_docgen")
+}
+
+fn _test_docgen() -> Result<(), ()> {
+    let root = Path::new("tests/docgen");
+    let files = [root.join("geo.er"), root.join("shapes.er")];
+    let mut cfg = ErgConfig::default();
+    cfg.output = Output::Null;
+    let site = DocSite::build(&cfg, root, &files).map_err(|errs| errs.write_all_stderr())?;
+    let pages = site.render(DocFormat::Markdown);
+    let page = |name: &str| &pages.iter().find(|(page, _)| page == name).unwrap().1;
+    let geo = page("geo.md");
+    assert!(geo.starts_with("# module geo\n\nGeometric primitives.\n"));
+    assert!(geo.contains("### <a id=\"Show\"></a>Show\n\nThings that can be shown.\n"));
+    assert!(geo.contains("- [shapes.Circle](shapes.md#Circle)\n"));
+    assert!(geo.contains("> **norm**: (self: [Point](#Point)) -> Nat\n\nThe Manhattan norm.\n"));
+    assert!(geo.contains("#### Implementation of [Show](#Show)\n"));
+    assert!(geo.contains("### <a id=\"Point3D\"></a>Point3D\n\nBases: [Point](#Point)\n"));
+    assert!(geo.contains("> **add**: (x: Int, y: Int) -> Int\n\nAdds two numbers.\n"));
+    assert!(!geo.contains("private"));
+    let shapes = page("shapes.md");
+    assert!(shapes.contains("> **move!**: (p: [geo.Point](geo.md#Point)) => NoneType\n"));
+    assert!(page("index.md").contains("- [geo](geo.md): Geometric primitives.\n"));
+    let pages = site.render(DocFormat::Html);
+    let geo = &pages[0].1;
+    assert!(geo.contains("<pre class=\"signature\"><b>add</b>: (x: Int, y: Int) -&gt; Int</pre>"));
+    Ok(())
+}
+
+#[test]
+fn test_watch() -> Result<(), ()> {
+    exec_new_thread(_test_watch, "test_watch")
+}
+
+fn _test_watch() -> Result<(), ()> {
+    let dir = std::env::temp_dir().join(format!("erg_watch_test_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.er"), "b = import \"b\"\n.x = b.g 1\n").unwrap();
+    fs::write(dir.join("b.er"), "c = import \"c\"\n.g x = c.f x\n").unwrap();
+    fs::write(dir.join("c.er"), ".f x: Int = x + 1\n").unwrap();
+    let path = |name: &str| NormalizedPathBuf::new(dir.join(name));
+    let mut cfg = ErgConfig::with_main_path(dir.join("a.er"));
+    cfg.output = Output::Null;
+    let mut watcher = Watcher::new(cfg);
+    assert!(watcher.run_once::<HIRBuilder>().succeed());
+    assert_eq!(watcher.watched_files().len(), 3);
+    assert!(watcher.changed_files().is_empty());
+    assert_eq!(
+        watcher.affected_modules(&[dir.join("c.er")]),
+        set! {path("a.er"), path("b.er"), path("c.er")}
+    );
+    // modify b.er
+    fs::write(dir.join("b.er"), "c = import \"c\"\n.g x = c.f x + 1\n").unwrap();
+    let file = fs::File::options()
+        .write(true)
+        .open(dir.join("b.er"))
+        .unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(10))
+        .unwrap();
+    let changed = watcher.changed_files();
+    assert_eq!(changed, [path("b.er").to_path_buf()]);
+    let affected = watcher.invalidate(&changed);
+    assert_eq!(affected, set! {path("a.er"), path("b.er")});
+    // c.er is reused
+    assert!(watcher.shared().mod_cache.get(&path("c.er")).is_some());
+    assert!(watcher.shared().mod_cache.get(&path("b.er")).is_none());
+    let stat = watcher.run_once::<HIRBuilder>();
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(stat.succeed());
+    Ok(())
+}
+
+#[test]
+fn test_api_compat() -> Result<(), ()> {
+    exec_new_thread(_test_api_compat, "test_api_compat")
+}
+
+fn _test_api_compat() -> Result<(), ()> {
+    let old = load_file("tests/compat/old/lib.er").map_err(|errs| errs.write_all_stderr())?;
+    let new = load_file("tests/compat/new/lib.er").map_err(|errs| errs.write_all_stderr())?;
+    let report = check_api_compat(&old, &new);
+    let changes = report
+        .changes
+        .iter()
+        .map(|change| change.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        changes,
+        [
+            "added `Point.dist: (self: <module>.Point, other: <module>.Point) -> Nat`",
+            "`accept` is changed compatibly: (x: Nat) -> Nat -> (x: Int) -> Nat",
+            "added `added: (x: Int) -> Int`",
+            "the parameter `x` of `narrow` is narrowed: Int -> Nat",
+            "removed `removed: (x: Int) -> Int`",
+            "the parameter `x` of `rename` is renamed to `y`",
+            "the return type of `widen` is widened: Nat -> Int",
+        ]
+    );
+    assert_eq!(report.required_bump(), VersionBump::Major);
+    assert!(!report.is_compatible());
+    let v = |s: &str| s.parse::<SemVer>().unwrap();
+    assert_eq!(VersionBump::Major.apply(&v("1.2.3")), v("2.0.0"));
+    assert_eq!(VersionBump::Major.apply(&v("0.2.1")), v("0.3.0"));
+    assert_eq!(VersionBump::Minor.apply(&v("0.2.1")), v("0.2.2"));
+    assert!(!VersionBump::Major.is_satisfied_by(&v("0.2.1"), &v("0.2.2")));
+    assert!(VersionBump::Minor.is_satisfied_by(&v("0.2.1"), &v("0.2.2")));
+    assert!(!VersionBump::Major.is_satisfied_by(&v("1.2.3"), &v("1.3.0")));
+    assert!(VersionBump::Minor.is_satisfied_by(&v("1.2.3"), &v("1.3.0")));
+    assert!(!VersionBump::Patch.is_satisfied_by(&v("1.2.3"), &v("1.2.3")));
+    Ok(())
+}
+
+#[test]
+fn test_dependency_resolution() -> Result<(), ()> {
+    exec_new_thread(_test_dependency_resolution, "test_dependency_resolution")
+}
+
+fn _test_dependency_resolution() -> Result<(), ()> {
+    let cfg = ErgConfig::default();
+    let resolver = DependencyResolver::new(cfg.copy())
+        .with_cache_dir(Path::new("tests/packages/cache").to_path_buf());
+    let resolution = resolver
+        .resolve(Path::new("tests/packages/app"))
+        .map_err(|errs| errs.write_all_stderr())?;
+    let lockfile = resolution.to_lockfile();
+    let locked = lockfile
+        .packages
+        .iter()
+        .map(|pkg| {
+            (
+                &pkg.name[..],
+                pkg.version.to_string(),
+                pkg.source.to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        locked,
+        [
+            ("app", "0.1.0".to_string(), "root".to_string()),
+            ("bar", "1.0.0".to_string(), "cache".to_string()),
+            ("bar", "1.2.0".to_string(), "cache".to_string()),
+            ("bar", "2.0.0".to_string(), "cache".to_string()),
+            ("util", "0.3.0".to_string(), "path+../util".to_string()),
+        ]
+    );
+    assert_eq!(
+        lockfile.packages[0].dependencies,
+        [
+            Str::ever("bar = bar@1.2.0"),
+            Str::ever("new_bar = bar@2.0.0"),
+            Str::ever("util = util@0.3.0"),
+        ]
+    );
+    // the lockfile is also an Erg module
+    let dir = std::env::temp_dir().join("erg_test_dependency_resolution");
+    std::fs::create_dir_all(&dir).unwrap();
+    lockfile.write(&dir).unwrap();
+    let loaded = Lockfile::load(&cfg, &dir).map_err(|errs| errs.write_all_stderr())?;
+    std::fs::remove_dir_all(&dir).unwrap_or(());
+    assert_eq!(loaded.as_ref(), Some(&lockfile));
+    // locked versions take precedence
+    let mut old_lock = lockfile.clone();
+    old_lock
+        .packages
+        .retain(|pkg| pkg.version != "1.2.0".parse().unwrap());
+    let relocked = DependencyResolver::new(cfg.copy())
+        .with_cache_dir(Path::new("tests/packages/cache").to_path_buf())
+        .with_lockfile(old_lock)
+        .resolve(Path::new("tests/packages/app"))
+        .map_err(|errs| errs.write_all_stderr())?;
+    let bar = &relocked.packages[relocked.root().deps[0].1];
+    assert_eq!(bar.version, "1.0.0".parse().unwrap());
+    assert_eq!(bar.source, PackageSource::Cache);
+    // imports of the dependencies are resolved
+    let mut cfg = cfg.inherit("tests/packages/app/src/main.er".into());
+    cfg.output = Output::Null;
+    cfg.packages = resolution.to_config_packages();
+    let src = cfg.input.read();
+    let mut builder = HIRBuilder::new(cfg);
+    builder
+        .build(src, "exec")
+        .map_err(|arti| arti.errors.write_all_stderr())?;
+    Ok(())
+}
+
+#[test]
+fn test_package_manifest() -> Result<(), ()> {
+    exec_new_thread(_test_package_manifest, "test_package_manifest")
+}
+
+fn _test_package_manifest() -> Result<(), ()> {
+    let manifest = PackageManifest::load(&ErgConfig::default(), Path::new("tests/package"))
+        .map_err(|errs| {
+            errs.write_all_stderr();
+        })?;
+    assert_eq!(&manifest.name[..], "example");
+    assert_eq!(manifest.version, "0.1.0".parse().unwrap());
+    assert_eq!(manifest.package_type, PackageType::Hybrid);
+    assert_eq!(manifest.categories, [Str::ever("cli")]);
+    assert!(!manifest.deprecated);
+    let deps = manifest
+        .dependencies
+        .iter()
+        .map(|dep| (&dep.as_name[..], &dep.name[..], &dep.version[..]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        deps,
+        [
+            ("bar1", "bar", "1.*.*"),
+            ("bar2", "bar", "2.*.*"),
+            ("foo", "foo", "*")
+        ]
+    );
+    // unexpected arguments of `pack` are errors (they must not overwrite `path`)
+    let errs = PackageManifest::load(&ErgConfig::default(), Path::new("tests/package_err"))
+        .expect_err("unexpected arguments of `pack` should be rejected");
+    assert_eq!(errs.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_semver() {
+    let v = |s: &str| s.parse::<SemVer>().unwrap();
+    assert!(v("0.1.0") < v("0.1.1"));
+    assert!(v("1.0.0-alpha") < v("1.0.0"));
+    assert!(v("1.0.0-rc.2") < v("1.0.0-rc.10"));
+    assert!(v("1.0.0-alpha") < v("1.0.0-alpha.1"));
+    assert!(v("1.0.0-alpha.1") < v("1.0.0-alpha.beta"));
+    assert!(v("1.0.0-beta.11") < v("1.0.0-rc.1"));
+    assert!(v("0.10.0") > v("0.9.0"));
+    assert_eq!(v("1.2.3-rc1").to_string(), "1.2.3-rc1");
+    // the build metadata is ignored in comparisons
+    assert_eq!(v("1.0.0+build.1"), v("1.0.0+build.2"));
+    assert!(v("1.0.0-alpha+001") < v("1.0.0"));
+    assert_eq!(
+        v("1.0.0-beta+exp.sha.5114f85").to_string(),
+        "1.0.0-beta+exp.sha.5114f85"
+    );
+    assert!("1.0.0+".parse::<SemVer>().is_err());
+    assert!("1.0".parse::<SemVer>().is_err());
+    assert!("1.0.x".parse::<SemVer>().is_err());
+}
+
+#[test]
+fn test_version_req() {
+    let v = |s: &str| s.parse::<SemVer>().unwrap();
+    let req = |s: &str| s.parse::<VersionReq>().unwrap();
+    assert!(req("*").matches(&v("3.1.4")));
+    assert!(!req("*").matches(&v("1.0.0-alpha")));
+    assert!(req("1.*.*").matches(&v("1.9.0")));
+    assert!(!req("1.*.*").matches(&v("2.0.0")));
+    assert!(req("1.2.*").matches(&v("1.2.7")));
+    assert!(!req("1.2.*").matches(&v("1.3.0")));
+    assert!(req("1.1.0").matches(&v("1.1.0")));
+    assert!(!req("1.1.0").matches(&v("1.1.1")));
+    assert!(req(">=1.2.0, <2.0.0").matches(&v("1.5.0")));
+    assert!(!req(">=1.2.0, <2.0.0").matches(&v("2.0.0")));
+    assert!(req("^1.2.0").matches(&v("1.9.9")));
+    assert!(!req("^1.2.0").matches(&v("2.0.0-alpha")));
+    assert!(!req("^0.2.0").matches(&v("0.3.0")));
+    assert!(req("~1.2.0").matches(&v("1.2.5")));
+    assert!(!req("~1.2.0").matches(&v("1.3.0")));
+    assert!(req("=1.0.0-beta").matches(&v("1.0.0-beta")));
+    assert!(req("1.1.0").matches(&v("1.1.0+build")));
+    assert!("1.*.3".parse::<VersionReq>().is_err());
+    assert!(">=x".parse::<VersionReq>().is_err());
+}
+
 /*
 #[test]
 fn test_patch() -> Result<(), ()> {
@@ -139,3 +426,48 @@ fn _test_dir() -> Result<(), ()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_project_build() -> Result<(), ()> {
+    exec_new_thread(_test_project_build, "test_project_build")
+}
+
+fn _test_project_build() -> Result<(), ()> {
+    let dist = std::env::temp_dir().join(format!("erg_project_test_{}", std::process::id()));
+    let mut cfg = ErgConfig::default();
+    cfg.output = Output::Null;
+    let mut builder =
+        ProjectBuilder::new_project(cfg, PathBuf::from("tests/project"), "my-app", dist.clone());
+    let artifact = builder.build().map_err(|errs| errs.write_all_stderr())?;
+    let package = dist.join("my_app");
+    assert_eq!(builder.package_dir(), package);
+    assert_eq!(artifact.object.len(), 4);
+    // the imported modules are compiled before the importers
+    let pos = |path: PathBuf| artifact.object.iter().position(|out| out == &path);
+    assert!(pos(package.join("util.pyc")) < pos(package.join("geo/point.pyc")));
+    assert!(pos(package.join("geo/point.pyc")) < pos(package.join("main.pyc")));
+    assert!(package.join("geo/__init__.pyc").exists());
+    assert!(package.join("__init__.py").exists());
+    assert!(!package.join("geo/__init__.py").exists());
+    assert!(dist.join("_erg_std_prelude.py").exists());
+    // the built package can be imported from Python
+    let out = Command::new(opt_which_python().unwrap())
+        .current_dir(&dist)
+        .arg("-c")
+        .arg(
+            "from types import SimpleNamespace as Record; \
+            from my_app import main, util, geo; \
+            assert util.double(3) == 6; \
+            assert geo.name == 'geo'; \
+            assert geo.point.Point(Record(x=1, y=2)).twice() == 2",
+        )
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dist).unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    Ok(())
+}
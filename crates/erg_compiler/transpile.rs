@@ -640,7 +640,9 @@ impl PyScriptGenerator {
                 Self::transpile_ident(ident)
             }
             Accessor::Attr(attr) => {
-                if let Some(name) = debind(&attr.ident) {
+                if attr.ident.is_newtype_base() {
+                    self.transpile_expr(*attr.obj)
+                } else if let Some(name) = debind(&attr.ident) {
                     demangle(&name)
                 } else {
                     format!(
@@ -654,6 +656,9 @@ impl PyScriptGenerator {
     }
 
     fn transpile_call(&mut self, mut call: Call) -> String {
+        if call.is_newtype_ctor_call() {
+            return self.transpile_expr(call.args.remove(0));
+        }
         match call.obj.local_name() {
             Some("assert") => {
                 let mut code = format!("assert {}", self.transpile_expr(call.args.remove(0)));
@@ -974,6 +979,9 @@ impl PyScriptGenerator {
     }
 
     fn transpile_classdef(&mut self, classdef: ClassDef) -> String {
+        if classdef.obj.is_newtype() {
+            return self.transpile_newtypedef(classdef);
+        }
         let class_name = Self::transpile_ident(classdef.sig.into_ident());
         let mut code = format!("class {class_name}():\n");
         let mut init_method = format!(
@@ -1004,12 +1012,45 @@ impl PyScriptGenerator {
     }
 
     fn transpile_patchdef(&mut self, patch_def: PatchDef) -> String {
+        let methods = patch_def.methods.into_iter().map(|chunk| {
+            let Expr::Def(def) = chunk else { todo!() };
+            def
+        });
+        self.transpile_debound_methods(patch_def.sig.ident(), methods)
+    }
+
+    /// UserId = Newtype Nat => UserId = Nat
+    fn transpile_newtypedef(&mut self, classdef: ClassDef) -> String {
+        let ident = classdef.sig.ident().clone();
+        let Some(base) = classdef.require_or_sup else {
+            unreachable!()
+        };
+        let mut code = format!(
+            "{} = {}\n",
+            Self::transpile_ident(ident.clone()),
+            self.transpile_expr(*base)
+        );
+        let methods = classdef
+            .methods
+            .into_iter()
+            .filter_map(|chunk| match chunk {
+                Expr::Def(def) => Some(def),
+                _ => None,
+            });
+        code += &self.transpile_debound_methods(&ident, methods);
+        code
+    }
+
+    fn transpile_debound_methods(
+        &mut self,
+        type_ident: &Identifier,
+        methods: impl Iterator<Item = Def>,
+    ) -> String {
         let mut code = String::new();
-        for chunk in patch_def.methods.into_iter() {
-            let Expr::Def(mut def) = chunk else { todo!() };
+        for mut def in methods {
             let name = format!(
                 "{}{}",
-                demangle(&patch_def.sig.ident().to_string_notype()),
+                demangle(&type_ident.to_string_notype()),
                 demangle(&def.sig.ident().to_string_notype()),
             );
             def.sig.ident_mut().raw.name = VarName::from_str(Str::from(name));
@@ -200,6 +200,23 @@ impl PatchObj {
     }
 }
 
+/// `UserId = Newtype Nat`
+/// A distinct nominal type whose values are represented by the base type at runtime.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NewtypeObj {
+    pub t: Type,
+    pub base: Box<TypeObj>,
+}
+
+impl NewtypeObj {
+    pub fn new(t: Type, base: TypeObj) -> Self {
+        Self {
+            t,
+            base: Box::new(base),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum GenTypeObj {
     Class(ClassTypeObj),
@@ -210,6 +227,7 @@ pub enum GenTypeObj {
     Union(UnionTypeObj),
     Intersection(IntersectionTypeObj),
     Patch(PatchObj),
+    Newtype(NewtypeObj),
 }
 
 impl fmt::Display for GenTypeObj {
@@ -248,6 +266,10 @@ impl GenTypeObj {
         GenTypeObj::Patch(PatchObj::new(t, base, impls))
     }
 
+    pub fn newtype(t: Type, base: TypeObj) -> Self {
+        GenTypeObj::Newtype(NewtypeObj::new(t, base))
+    }
+
     pub fn subsumed(
         t: Type,
         sup: TypeObj,
@@ -269,6 +291,10 @@ impl GenTypeObj {
         GenTypeObj::Structural(StructuralTypeObj::new(t, type_))
     }
 
+    pub const fn is_newtype(&self) -> bool {
+        matches!(self, Self::Newtype(_))
+    }
+
     pub const fn is_inited(&self) -> bool {
         match self {
             Self::Class(class) => class.inited,
@@ -285,6 +311,7 @@ impl GenTypeObj {
             Self::Subtrait(subtrait) => Some(subtrait.sup.as_ref()),
             Self::Structural(type_) => Some(type_.base.as_ref()),
             Self::Patch(patch) => Some(patch.base.as_ref()),
+            Self::Newtype(newtype) => Some(newtype.base.as_ref()),
             _ => None,
         }
     }
@@ -319,7 +346,7 @@ impl GenTypeObj {
 
     pub fn meta_type(&self) -> Type {
         match self {
-            Self::Class(_) | Self::Subclass(_) => Type::ClassType,
+            Self::Class(_) | Self::Subclass(_) | Self::Newtype(_) => Type::ClassType,
             Self::Trait(_) | Self::Subtrait(_) => Type::TraitType,
             Self::Patch(_) => Type::Patch,
             Self::Structural(_) => Type::Type,
@@ -337,6 +364,7 @@ impl GenTypeObj {
             Self::Union(union_) => &union_.t,
             Self::Intersection(intersection) => &intersection.t,
             Self::Patch(patch) => &patch.t,
+            Self::Newtype(newtype) => &newtype.t,
         }
     }
 
@@ -350,6 +378,7 @@ impl GenTypeObj {
             Self::Union(union_) => &mut union_.t,
             Self::Intersection(intersection) => &mut intersection.t,
             Self::Patch(patch) => &mut patch.t,
+            Self::Newtype(newtype) => &mut newtype.t,
         }
     }
 
@@ -363,6 +392,7 @@ impl GenTypeObj {
             Self::Union(union_) => union_.t,
             Self::Intersection(intersection) => intersection.t,
             Self::Patch(patch) => patch.t,
+            Self::Newtype(newtype) => newtype.t,
         }
     }
 
@@ -294,6 +294,13 @@ impl VarInfo {
         }
     }
 
+    /// Returns `true` if the variable is a subroutine decorated with `@Test`.
+    pub fn is_test(&self) -> bool {
+        self.comptime_decos
+            .as_ref()
+            .is_some_and(|decos| decos.contains("Test"))
+    }
+
     pub fn nd_parameter(t: Type, def_loc: AbsLocation, namespace: Str) -> Self {
         let kind = VarKind::Parameter {
             def_id: DefId(0),
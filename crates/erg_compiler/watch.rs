@@ -0,0 +1,182 @@
+//! defines `Watcher`, which re-runs a runnable when the main file or its dependencies change (`--watch`).
+//!
+//! The compiler resource is shared between the runs,
+//! so only the changed modules and the modules depending on them are checked again.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+use erg_common::config::ErgConfig;
+use erg_common::dict::Dict;
+use erg_common::env::{erg_path, is_std_decl_path};
+use erg_common::error::MultiErrorDisplay;
+use erg_common::pathutil::NormalizedPathBuf;
+use erg_common::set::Set;
+use erg_common::traits::{ExitStatus, Runnable, Stream};
+
+use crate::build_hir::HIRBuilder;
+use crate::module::SharedCompilerResource;
+use crate::Compiler;
+
+const POLLING_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Runnables that can be created with a shared compiler resource.
+pub trait Watchable: Runnable {
+    /// Creates a runnable of the main module which uses `shared`.
+    fn with_shared(cfg: ErgConfig, shared: SharedCompilerResource) -> Self;
+}
+
+impl Watchable for HIRBuilder {
+    fn with_shared(cfg: ErgConfig, shared: SharedCompilerResource) -> Self {
+        HIRBuilder::new_with_cache(cfg, "<module>", shared)
+    }
+}
+
+impl Watchable for Compiler {
+    fn with_shared(cfg: ErgConfig, shared: SharedCompilerResource) -> Self {
+        Compiler::new_with_cache(cfg, shared)
+    }
+}
+
+#[derive(Debug)]
+pub struct Watcher {
+    cfg: ErgConfig,
+    shared: SharedCompilerResource,
+    /// the last modified times of the watched files (`None` if the file does not exist)
+    timestamps: Dict<PathBuf, Option<SystemTime>>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|md| md.modified()).ok()
+}
+
+impl Watcher {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self {
+            shared: SharedCompilerResource::new(cfg.copy()),
+            cfg,
+            timestamps: Dict::new(),
+        }
+    }
+
+    pub fn shared(&self) -> &SharedCompilerResource {
+        &self.shared
+    }
+
+    /// The main file and the modules it (transitively) imports, except for the standard library.
+    pub fn watched_files(&self) -> Vec<PathBuf> {
+        let main = NormalizedPathBuf::new(self.cfg.input.path().to_path_buf());
+        let mut files = vec![main.to_path_buf()];
+        for node in self.shared.graph.ref_inner().iter() {
+            let path = node.id.to_path_buf();
+            if !is_std_decl_path(&path) && !path.starts_with(erg_path()) && !files.contains(&path) {
+                files.push(path);
+            }
+        }
+        files
+    }
+
+    fn take_snapshot(&mut self) {
+        self.timestamps = self
+            .watched_files()
+            .into_iter()
+            .map(|path| {
+                let time = modified(&path);
+                (path, time)
+            })
+            .collect();
+    }
+
+    /// The watched files which have been modified (or removed) since the last run.
+    pub fn changed_files(&self) -> Vec<PathBuf> {
+        self.timestamps
+            .iter()
+            .filter(|(path, time)| modified(path) != **time)
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// The changed modules and the modules which (transitively) depend on them.
+    pub fn affected_modules(&self, changed: &[PathBuf]) -> Set<NormalizedPathBuf> {
+        let changed = changed
+            .iter()
+            .map(|path| NormalizedPathBuf::new(path.clone()))
+            .collect::<Set<_>>();
+        let graph = self.shared.graph.ref_inner();
+        let mut affected = changed.clone();
+        for node in graph.iter() {
+            if graph
+                .ancestors(&node.id)
+                .iter()
+                .any(|dep| changed.contains(dep))
+            {
+                affected.insert(node.id.clone());
+            }
+        }
+        affected
+    }
+
+    /// Discards the cached information of the affected modules.
+    /// The other modules (and the builtin modules) are reused in the next run.
+    pub fn invalidate(&mut self, changed: &[PathBuf]) -> Set<NormalizedPathBuf> {
+        let affected = self.affected_modules(changed);
+        for path in affected.iter() {
+            self.shared.clear(path);
+        }
+        self.shared.errors.clear();
+        self.shared.warns.clear();
+        affected
+    }
+
+    /// Runs `R` once and records the timestamps of the files used.
+    pub fn run_once<R: Watchable>(&mut self) -> ExitStatus {
+        let mut runnable = R::with_shared(self.cfg.copy(), self.shared.clone());
+        let stat = match runnable.exec() {
+            Ok(stat) => stat,
+            Err(errs) => {
+                errs.write_all_stderr();
+                ExitStatus::new(1, 0, errs.len())
+            }
+        };
+        self.take_snapshot();
+        stat
+    }
+
+    /// Runs `R` and re-runs it whenever the watched files change (never returns unless the input is not a file).
+    pub fn watch<R: Watchable>(mut self) -> ExitStatus {
+        if !self.cfg.input.is_file() {
+            eprintln!("--watch requires a file to be watched");
+            return ExitStatus::ERR1;
+        }
+        self.run_once::<R>();
+        println!(
+            "[watch] watching {} file(s). Press Ctrl+C to stop.",
+            self.timestamps.len()
+        );
+        loop {
+            sleep(POLLING_INTERVAL);
+            let changed = self.changed_files();
+            if changed.is_empty() {
+                continue;
+            }
+            let affected = self.invalidate(&changed);
+            let changed = changed
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>();
+            println!(
+                "[watch] {} changed, re-running ({} module(s) affected)",
+                changed.join(", "),
+                affected.len()
+            );
+            let stat = self.run_once::<R>();
+            let result = if stat.succeed() {
+                "succeeded"
+            } else {
+                "failed"
+            };
+            println!("[watch] {result}. Waiting for changes...");
+        }
+    }
+}
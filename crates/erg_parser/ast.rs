@@ -4337,7 +4337,7 @@ impl DefBody {
     pub fn def_kind(&self) -> DefKind {
         match self.block.first().unwrap() {
             Expr::Call(call) => match call.obj.get_name().map(|n| &n[..]) {
-                Some("Class") => DefKind::Class,
+                Some("Class" | "Newtype") => DefKind::Class,
                 Some("Inherit") => DefKind::Inherit,
                 Some("Trait") => DefKind::Trait,
                 Some("Subsume") => DefKind::Subsume,
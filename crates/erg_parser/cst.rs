@@ -0,0 +1,642 @@
+//! defines `ConcreteSyntaxTree` (CST).
+//!
+//! `Lexer` discards whitespaces and comments, so the source code cannot be restored from a `TokenStream`.
+//! CST keeps them as trivia attached to the tokens, and concatenating all tokens and trivia gives back the original source code.
+//! The tokens are grouped into logical lines (terminated by `Newline` tokens), and `ast::Module` can be derived from CST.
+use std::fmt;
+
+use erg_common::error::Location;
+use erg_common::traits::{DequeStream, Locational};
+use erg_common::{fn_name, Str};
+
+use crate::error::{CompleteArtifact, IncompleteArtifact, LexError, LexErrors};
+use crate::lex::Lexer;
+use crate::token::{Token, TokenKind, TokenStream};
+use crate::Parser;
+use TokenKind::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriviaKind {
+    /// spaces and tabs
+    Whitespace,
+    /// a line break which is not lexed as a `Newline` token
+    Newline,
+    /// `\` followed by a line break
+    LineContinuation,
+    /// `# ...`
+    Comment,
+    /// `#[ ... ]#`
+    MultiLineComment,
+}
+
+/// Source text which does not affect the syntax (whitespaces, comments, etc.).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub content: Str,
+    /// 1 origin
+    pub lineno: u32,
+    /// 0 origin
+    pub col_begin: u32,
+}
+
+impl fmt::Display for Trivia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.content)
+    }
+}
+
+impl Locational for Trivia {
+    fn loc(&self) -> Location {
+        text_loc(&self.content, self.lineno, self.col_begin)
+    }
+}
+
+impl Trivia {
+    pub const fn is_comment(&self) -> bool {
+        matches!(
+            self.kind,
+            TriviaKind::Comment | TriviaKind::MultiLineComment
+        )
+    }
+
+    /// `## ...` or `#[[ ... ]]#`
+    pub fn is_doc_comment(&self) -> bool {
+        match self.kind {
+            TriviaKind::Comment => self.content.starts_with("##"),
+            TriviaKind::MultiLineComment => {
+                self.content.starts_with("#[[") && self.content.ends_with("]]#")
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A doc comment (`'''...'''`, consecutive `##` lines, or `#[[...]]#`) without the markers and the common indentation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocComment {
+    /// the line of `lines[0]` (1 origin)
+    pub lineno: u32,
+    /// the first and the last line of the comment including the markers
+    pub range: (u32, u32),
+    pub lines: Vec<String>,
+}
+
+impl fmt::Display for DocComment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines.join("\n"))
+    }
+}
+
+impl DocComment {
+    /// `text` is the content between the markers, which starts at `lineno`.
+    fn new(lineno: u32, text: &str) -> Self {
+        let range = (lineno, lineno + text.matches('\n').count() as u32);
+        let mut lineno = lineno;
+        let mut lines = text.split('\n').collect::<Vec<_>>();
+        // `'''` and `#[[` are usually followed by a line break
+        if lines.len() > 1 && lines[0].trim().is_empty() {
+            lines.remove(0);
+            lineno += 1;
+        }
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        Self {
+            lineno,
+            range,
+            lines: dedent(&lines),
+        }
+    }
+
+    /// Returns the fenced code blocks (` ```lang `) in the doc comment as (the line of the first line of the code, code).
+    pub fn code_blocks(&self, lang: &str) -> Vec<(u32, String)> {
+        let mut blocks = vec![];
+        // (the line index of the fence, the code lines)
+        let mut block: Option<(usize, Vec<&str>)> = None;
+        for (i, line) in self.lines.iter().enumerate() {
+            let Some(info) = line.trim_start().strip_prefix("```") else {
+                if let Some((_, code)) = block.as_mut() {
+                    code.push(line);
+                }
+                continue;
+            };
+            match block.take() {
+                Some((fence, code)) => {
+                    let code = dedent(&code).join("\n");
+                    blocks.push((self.lineno + fence as u32 + 1, code));
+                }
+                None if info.trim() == lang => block = Some((i, vec![])),
+                // other languages: skip until the closing fence
+                None => {}
+            }
+        }
+        blocks
+    }
+}
+
+/// Removes the common indentation of the non-blank lines.
+fn dedent(lines: &[&str]) -> Vec<String> {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default().to_string())
+        .collect()
+}
+
+/// the location of `text` which starts at (`lineno`, `col_begin`)
+fn text_loc(text: &str, lineno: u32, col_begin: u32) -> Location {
+    if lineno == 0 {
+        return Location::Unknown;
+    }
+    let breaks = text.matches('\n').count() as u32;
+    let last_line = text.rsplit('\n').next().unwrap_or_default();
+    let col_end = if breaks == 0 {
+        col_begin + last_line.chars().count() as u32
+    } else {
+        last_line.chars().count() as u32
+    };
+    Location::range(lineno, col_begin, lineno + breaks, col_end)
+}
+
+#[derive(Debug, Clone)]
+pub struct CstToken {
+    /// `token.lineno` and `token.col_begin` indicate the actual position of `text`
+    pub token: Token,
+    /// the original text of the token (e.g. the content of a string literal token is unescaped)
+    pub text: Str,
+    pub leading_trivia: Vec<Trivia>,
+    /// trivia following the token on the same line
+    pub trailing_trivia: Vec<Trivia>,
+}
+
+impl fmt::Display for CstToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trivia in self.leading_trivia.iter() {
+            write!(f, "{trivia}")?;
+        }
+        write!(f, "{}", self.text)?;
+        for trivia in self.trailing_trivia.iter() {
+            write!(f, "{trivia}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Locational for CstToken {
+    fn loc(&self) -> Location {
+        text_loc(&self.text, self.token.lineno, self.token.col_begin)
+    }
+}
+
+impl CstToken {
+    pub fn is(&self, kind: TokenKind) -> bool {
+        self.token.is(kind)
+    }
+
+    /// `Newline`, `Indent`, `Dedent`, `BOF` and `EOF` do not appear in the code (except for the line break of `Newline`)
+    pub const fn is_layout(&self) -> bool {
+        matches!(self.token.kind, Newline | Indent | Dedent | BOF | EOF)
+    }
+
+    pub fn trivia(&self) -> impl Iterator<Item = &Trivia> {
+        self.leading_trivia
+            .iter()
+            .chain(self.trailing_trivia.iter())
+    }
+
+    /// Replaces the text of a symbol or an operator token. Trivia are kept as they are.
+    pub fn set_text<S: Into<Str>>(&mut self, text: S) {
+        let text = text.into();
+        self.token.content = text.clone();
+        self.text = text;
+    }
+}
+
+/// A logical line. All lines except the last one end with a `Newline` token.
+#[derive(Debug, Clone, Default)]
+pub struct CstLine {
+    /// the number of unclosed `Indent`s at the beginning of the line
+    pub depth: usize,
+    pub tokens: Vec<CstToken>,
+}
+
+impl fmt::Display for CstLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in self.tokens.iter() {
+            write!(f, "{token}")?;
+        }
+        Ok(())
+    }
+}
+
+impl CstLine {
+    /// tokens except for layout tokens (`Newline`, `Indent`, etc.)
+    pub fn code_tokens(&self) -> impl Iterator<Item = &CstToken> {
+        self.tokens.iter().filter(|token| !token.is_layout())
+    }
+
+    pub fn trivia(&self) -> impl Iterator<Item = &Trivia> {
+        self.tokens.iter().flat_map(|token| token.trivia())
+    }
+
+    pub fn comments(&self) -> impl Iterator<Item = &Trivia> {
+        self.trivia().filter(|trivia| trivia.is_comment())
+    }
+
+    /// Returns `true` if the line has no code (it may have comments).
+    pub fn is_empty(&self) -> bool {
+        self.code_tokens().next().is_none()
+    }
+
+    /// Returns `true` if the line has neither code nor comments.
+    pub fn is_blank(&self) -> bool {
+        self.is_empty() && self.comments().next().is_none()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConcreteSyntaxTree {
+    lines: Vec<CstLine>,
+}
+
+impl fmt::Display for ConcreteSyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.lines.iter() {
+            write!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+struct CstBuilder<'a> {
+    chars: &'a [char],
+    cursor: usize,
+    /// 1 origin
+    lineno: u32,
+    /// 0 origin
+    col: u32,
+    /// quotes of the string literals being interpolated (quote, is triple quoted)
+    quotes: Vec<(char, bool)>,
+}
+
+impl<'a> CstBuilder<'a> {
+    const fn new(chars: &'a [char]) -> Self {
+        Self {
+            chars,
+            cursor: 0,
+            lineno: 1,
+            col: 0,
+            quotes: vec![],
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.cursor + offset).copied()
+    }
+
+    fn starts_with(&self, offset: usize, pat: &str) -> bool {
+        let mut chars = self.chars.iter().skip(self.cursor + offset);
+        pat.chars().all(|c| chars.next() == Some(&c))
+    }
+
+    /// Consumes `len` chars and returns them.
+    fn consume(&mut self, len: usize) -> Str {
+        let end = (self.cursor + len).min(self.chars.len());
+        let text = self.chars[self.cursor..end].iter().collect::<String>();
+        let mut pos = (self.lineno, self.col);
+        advance(&mut pos, &text);
+        (self.lineno, self.col) = pos;
+        self.cursor = end;
+        Str::from(text)
+    }
+
+    /// the length of the comment starting at the cursor
+    fn comment_len(&self) -> usize {
+        let mut len = 0;
+        if self.starts_with(0, "#[") {
+            let mut nest_level = 0;
+            while self.peek(len).is_some() {
+                if self.starts_with(len, "#[") {
+                    nest_level += 1;
+                    len += 2;
+                } else if self.starts_with(len, "]#") {
+                    nest_level -= 1;
+                    len += 2;
+                    if nest_level == 0 {
+                        break;
+                    }
+                } else {
+                    len += 1;
+                }
+            }
+        } else {
+            while self.peek(len).is_some_and(|c| c != '\n') {
+                len += 1;
+            }
+        }
+        len
+    }
+
+    /// Scans trivia. If `line_break` is `false`, line breaks (`Newline` tokens) are not consumed.
+    fn scan_trivia(&mut self, line_break: bool) -> Vec<Trivia> {
+        let mut trivia = vec![];
+        loop {
+            let (kind, len) = match self.peek(0) {
+                Some(' ' | '\t') => {
+                    let len = (0..)
+                        .take_while(|i| matches!(self.peek(*i), Some(' ' | '\t')))
+                        .count();
+                    (TriviaKind::Whitespace, len)
+                }
+                Some('\n') if line_break => (TriviaKind::Newline, 1),
+                Some('\\') if self.peek(1) == Some('\n') => (TriviaKind::LineContinuation, 2),
+                Some('#') if self.peek(1) == Some('[') => {
+                    (TriviaKind::MultiLineComment, self.comment_len())
+                }
+                Some('#') => (TriviaKind::Comment, self.comment_len()),
+                _ => break,
+            };
+            let (lineno, col_begin) = (self.lineno, self.col);
+            let content = self.consume(len);
+            trivia.push(Trivia {
+                kind,
+                content,
+                lineno,
+                col_begin,
+            });
+        }
+        trivia
+    }
+
+    /// Scans a string literal body starting at `offset`.
+    /// Returns the length and whether it ended with an interpolation (`\{`).
+    fn scan_str(&self, mut offset: usize, quote: char, triple: bool) -> Option<(usize, bool)> {
+        let closer = if triple {
+            quote.to_string().repeat(3)
+        } else {
+            quote.to_string()
+        };
+        while self.peek(offset).is_some() {
+            if self.peek(offset) == Some('\\') {
+                if self.peek(offset + 1) == Some('{') {
+                    return Some((offset + 2, true));
+                }
+                offset += 2;
+            } else if self.starts_with(offset, &closer) {
+                return Some((offset + closer.len(), false));
+            } else {
+                offset += 1;
+            }
+        }
+        None
+    }
+
+    /// the length of the token text at the cursor
+    /// (the content of a token may differ from the source, so the text is scanned)
+    fn text_len(&mut self, token: &Token) -> Option<usize> {
+        match token.kind {
+            Newline => match self.peek(0) {
+                Some('\n') => Some(1),
+                None => Some(0),
+                _ => None,
+            },
+            Indent | Dedent | BOF | EOF => Some(0),
+            StrLit | TokenKind::DocComment | StrInterpLeft => {
+                let quote = self.peek(0).filter(|c| *c == '"' || *c == '\'')?;
+                let triple = self.starts_with(0, &quote.to_string().repeat(3));
+                let (len, interp) = self.scan_str(if triple { 3 } else { 1 }, quote, triple)?;
+                if interp != (token.kind == StrInterpLeft) {
+                    return None;
+                }
+                if interp {
+                    self.quotes.push((quote, triple));
+                }
+                Some(len)
+            }
+            StrInterpMid | StrInterpRight => {
+                if self.peek(0) != Some('}') {
+                    return None;
+                }
+                let (quote, triple) = *self.quotes.last()?;
+                let (len, interp) = self.scan_str(1, quote, triple)?;
+                if interp != (token.kind == StrInterpMid) {
+                    return None;
+                }
+                if !interp {
+                    self.quotes.pop();
+                }
+                Some(len)
+            }
+            // symbolized operators (e.g. `+`)
+            _ if self.peek(0) == Some('`') => {
+                let len = (1..).take_while(|i| self.peek(*i).is_some_and(|c| c != '`'));
+                Some(len.count() + 2)
+            }
+            _ => {
+                let len = token.content.chars().count();
+                self.starts_with(0, &token.content).then_some(len)
+            }
+        }
+    }
+
+    fn build(mut self, ts: &TokenStream) -> Result<ConcreteSyntaxTree, LexError> {
+        let mut lines = vec![];
+        let mut line = CstLine::default();
+        let mut depth = 0usize;
+        for token in ts.iter() {
+            let trivia = match token.kind {
+                Indent | Dedent | BOF => vec![],
+                Newline => self.scan_trivia(false),
+                _ => self.scan_trivia(true),
+            };
+            let Some(len) = self.text_len(token) else {
+                return Err(LexError::compiler_bug(0, token.loc(), fn_name!(), line!()));
+            };
+            let mut leading_trivia = trivia;
+            // trivia on the same line belong to the previous token
+            if let Some(prev) = line.tokens.last_mut().filter(|prev| !prev.is_layout()) {
+                let rest = leading_trivia
+                    .iter()
+                    .position(|trivia| trivia.kind == TriviaKind::Newline)
+                    .unwrap_or(leading_trivia.len());
+                let rest = leading_trivia.split_off(rest);
+                prev.trailing_trivia.extend(leading_trivia);
+                leading_trivia = rest;
+            }
+            let mut tok = token.clone();
+            tok.lineno = self.lineno;
+            tok.col_begin = self.col;
+            let text = self.consume(len);
+            match token.kind {
+                Indent => depth += 1,
+                Dedent => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            if line.is_empty() {
+                line.depth = depth;
+            }
+            line.tokens.push(CstToken {
+                token: tok,
+                text,
+                leading_trivia,
+                trailing_trivia: vec![],
+            });
+            if token.is(Newline) {
+                lines.push(std::mem::take(&mut line));
+            }
+        }
+        // trivia after the last token
+        let trivia = self.scan_trivia(true);
+        if let Some(last) = line.tokens.last_mut() {
+            last.trailing_trivia.extend(trivia);
+        } else if let Some(last) = lines.last_mut().and_then(|line| line.tokens.last_mut()) {
+            last.trailing_trivia.extend(trivia);
+        }
+        if !line.tokens.is_empty() {
+            lines.push(line);
+        }
+        let cst = ConcreteSyntaxTree { lines };
+        if self.cursor < self.chars.len() || cst.to_string().chars().ne(self.chars.iter().copied())
+        {
+            let loc = Location::range(self.lineno, self.col, self.lineno, self.col);
+            return Err(LexError::compiler_bug(0, loc, fn_name!(), line!()));
+        }
+        Ok(cst)
+    }
+}
+
+impl ConcreteSyntaxTree {
+    /// Builds CST from the source code and the token stream lexed from it.
+    pub fn new(src: &str, ts: &TokenStream) -> Result<Self, LexError> {
+        let chars = src.chars().collect::<Vec<_>>();
+        CstBuilder::new(&chars).build(ts)
+    }
+
+    pub fn parse(src: String) -> Result<Self, LexErrors> {
+        Lexer::from_str(src).lex_lossless()
+    }
+
+    pub fn lines(&self) -> &[CstLine] {
+        &self.lines
+    }
+
+    pub fn tokens(&self) -> impl Iterator<Item = &CstToken> {
+        self.lines.iter().flat_map(|line| line.tokens.iter())
+    }
+
+    pub fn tokens_mut(&mut self) -> impl Iterator<Item = &mut CstToken> {
+        self.lines
+            .iter_mut()
+            .flat_map(|line| line.tokens.iter_mut())
+    }
+
+    /// Returns the (non-layout) token which contains the position (1-origin line, 0-origin column).
+    pub fn get_token(&self, lineno: u32, col: u32) -> Option<&CstToken> {
+        self.tokens()
+            .find(|token| !token.is_layout() && loc_contains(token.loc(), lineno, col))
+    }
+
+    pub fn get_token_mut(&mut self, lineno: u32, col: u32) -> Option<&mut CstToken> {
+        self.tokens_mut()
+            .find(|token| !token.is_layout() && loc_contains(token.loc(), lineno, col))
+    }
+
+    pub fn comments(&self) -> impl Iterator<Item = &Trivia> {
+        self.lines.iter().flat_map(|line| line.comments())
+    }
+
+    /// Returns the doc comments in the order of appearance.
+    pub fn doc_comments(&self) -> Vec<DocComment> {
+        let mut docs = vec![];
+        // consecutive `##` lines (the line of the last line, the doc comment)
+        let mut sharps: Option<(u32, DocComment)> = None;
+        for token in self.tokens() {
+            if token.is(TokenKind::DocComment) {
+                let text = token.text.trim_matches(|c| c == '\'' || c == '"');
+                docs.push(DocComment::new(token.token.lineno, text));
+            }
+            for trivia in token.trivia().filter(|trivia| trivia.is_doc_comment()) {
+                if trivia.kind == TriviaKind::MultiLineComment {
+                    let text = &trivia.content[3..trivia.content.len() - 3];
+                    docs.push(DocComment::new(trivia.lineno, text));
+                    continue;
+                }
+                let line = trivia.content[2..]
+                    .strip_prefix(' ')
+                    .unwrap_or(&trivia.content[2..]);
+                match sharps.as_mut() {
+                    Some((last, doc)) if *last + 1 == trivia.lineno => {
+                        doc.lines.push(line.to_string());
+                        doc.range.1 = trivia.lineno;
+                        *last = trivia.lineno;
+                    }
+                    _ => {
+                        if let Some((_, doc)) = sharps.take() {
+                            docs.push(doc);
+                        }
+                        let doc = DocComment {
+                            lineno: trivia.lineno,
+                            range: (trivia.lineno, trivia.lineno),
+                            lines: vec![line.to_string()],
+                        };
+                        sharps = Some((trivia.lineno, doc));
+                    }
+                }
+            }
+        }
+        if let Some((_, doc)) = sharps {
+            docs.push(doc);
+        }
+        docs.sort_by_key(|doc| doc.lineno);
+        docs
+    }
+
+    /// Converts to `TokenStream` (the positions of the tokens are recalculated from the current text).
+    pub fn to_token_stream(&self) -> TokenStream {
+        let mut ts = TokenStream::empty();
+        let mut pos = (1, 0);
+        for token in self.tokens() {
+            for trivia in token.leading_trivia.iter() {
+                advance(&mut pos, &trivia.content);
+            }
+            let mut tok = token.token.clone();
+            (tok.lineno, tok.col_begin) = pos;
+            ts.push(tok);
+            advance(&mut pos, &token.text);
+            for trivia in token.trailing_trivia.iter() {
+                advance(&mut pos, &trivia.content);
+            }
+        }
+        ts
+    }
+
+    pub fn to_ast(&self) -> Result<CompleteArtifact, IncompleteArtifact> {
+        Parser::new(self.to_token_stream()).parse()
+    }
+}
+
+/// Advances the position (`lineno`, `col`) by `text`.
+fn advance(pos: &mut (u32, u32), text: &str) {
+    for c in text.chars() {
+        if c == '\n' {
+            *pos = (pos.0 + 1, 0);
+        } else {
+            pos.1 += 1;
+        }
+    }
+}
+
+fn loc_contains(loc: Location, lineno: u32, col: u32) -> bool {
+    let (Some(ln_begin), Some(col_begin), Some(ln_end), Some(col_end)) =
+        (loc.ln_begin(), loc.col_begin(), loc.ln_end(), loc.col_end())
+    else {
+        return false;
+    };
+    (ln_begin, col_begin) <= (lineno, col) && (lineno, col) < (ln_end, col_end)
+}
@@ -0,0 +1,549 @@
+//! defines and implements `Formatter`.
+//!
+//! The formatter works on the `ConcreteSyntaxTree`, which keeps the original text of each token and the comments,
+//! and uses the `ast::Module` to find the boundaries of top-level definitions.
+//! The formatted code is re-parsed and accepted only if it produces the same AST as the original.
+use std::fs;
+
+use erg_common::config::ErgConfig;
+use erg_common::error::Location;
+use erg_common::traits::{ExitStatus, Locational, Runnable, Stream};
+use erg_common::{fn_name, normalize_newline};
+
+use crate::ast::Module;
+use crate::cst::{ConcreteSyntaxTree, TriviaKind};
+use crate::error::{ParseError, ParseErrors, ParserRunnerError, ParserRunnerErrors};
+use crate::token::{TokenCategory, TokenKind};
+use TokenKind::*;
+
+/// Lines longer than this are broken at the outermost argument list.
+pub const MAX_WIDTH: usize = 100;
+pub const INDENT_WIDTH: usize = 4;
+
+/// A code token with its original text and position (0-origin lines and columns).
+#[derive(Debug, Clone)]
+struct SrcToken {
+    kind: TokenKind,
+    text: String,
+    begin: (usize, usize),
+    /// the line where the token ends
+    ln_end: usize,
+    /// whether the token is preceded by trivia (whitespaces, comments, etc.)
+    gap: bool,
+    /// whether the token is preceded by a line continuation (`\`)
+    continued: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Comment {
+    text: String,
+    begin: (usize, usize),
+    ln_end: usize,
+}
+
+#[derive(Debug)]
+enum LineKind {
+    Code {
+        tokens: Vec<SrcToken>,
+        trailing: Option<String>,
+    },
+    /// `text` is kept verbatim except for the indentation of the first line.
+    Comment { text: String },
+}
+
+#[derive(Debug)]
+struct Line {
+    kind: LineKind,
+    depth: usize,
+    /// 0-origin
+    ln_begin: usize,
+    ln_end: usize,
+    /// width of the indentation in the original source
+    src_indent: usize,
+    blank_before: usize,
+    /// rendered lines (text, depth)
+    physical: Vec<(String, usize)>,
+}
+
+impl Line {
+    const fn is_code(&self) -> bool {
+        matches!(self.kind, LineKind::Code { .. })
+    }
+
+    fn is_decorator(&self) -> bool {
+        matches!(&self.kind, LineKind::Code { tokens, .. } if tokens[0].kind == AtSign)
+    }
+}
+
+/// Rewrites Erg source code into the canonical style:
+///
+/// * indentation is `INDENT_WIDTH` spaces per block level
+/// * binary operators, `=`, `->`, `:` and `,` are surrounded by (or followed by) a single space
+/// * consecutive blank lines are collapsed into one, and multi-line top-level definitions are separated by a blank line
+/// * argument lists of lines longer than `MAX_WIDTH` are broken into one argument per line
+/// * comments are preserved
+#[derive(Debug)]
+struct Formatter {
+    break_long_lines: bool,
+}
+
+impl Formatter {
+    const fn new() -> Self {
+        Self {
+            break_long_lines: true,
+        }
+    }
+
+    /// 0-origin (line, column)
+    fn position(loc: Location) -> (usize, usize) {
+        let ln = loc.ln_begin().unwrap_or(1) as usize - 1;
+        (ln, loc.col_begin().unwrap_or(0) as usize)
+    }
+
+    fn ln_end(loc: Location) -> usize {
+        loc.ln_end().unwrap_or(1) as usize - 1
+    }
+
+    /// Splits the code tokens into logical lines (token list, block depth).
+    fn split_lines(cst: &ConcreteSyntaxTree) -> Vec<(Vec<SrcToken>, usize)> {
+        let mut lines = vec![];
+        // trivia kinds between the previous code token and the current one
+        let mut trivia = vec![];
+        for line in cst.lines() {
+            let mut tokens = vec![];
+            for token in line.tokens.iter() {
+                trivia.extend(token.leading_trivia.iter().map(|t| t.kind));
+                if !token.is_layout() {
+                    let loc = token.loc();
+                    tokens.push(SrcToken {
+                        kind: token.token.kind,
+                        text: token.text.to_string(),
+                        begin: Self::position(loc),
+                        ln_end: Self::ln_end(loc),
+                        gap: !trivia.is_empty(),
+                        continued: trivia.contains(&TriviaKind::LineContinuation),
+                    });
+                    trivia.clear();
+                }
+                trivia.extend(token.trailing_trivia.iter().map(|t| t.kind));
+            }
+            if !tokens.is_empty() {
+                lines.push((tokens, line.depth));
+            }
+        }
+        lines
+    }
+
+    fn collect_comments(cst: &ConcreteSyntaxTree) -> Vec<Comment> {
+        cst.comments()
+            .map(|comment| {
+                let loc = comment.loc();
+                Comment {
+                    text: comment.content.trim_end().to_string(),
+                    begin: Self::position(loc),
+                    ln_end: Self::ln_end(loc),
+                }
+            })
+            .collect()
+    }
+
+    fn build_lines(&self, cst: &ConcreteSyntaxTree) -> Result<Vec<Line>, Location> {
+        let mut lines = Self::split_lines(cst)
+            .into_iter()
+            .map(|(tokens, depth)| {
+                let first = tokens.first().unwrap();
+                let last = tokens.last().unwrap();
+                Line {
+                    ln_begin: first.begin.0,
+                    ln_end: last.ln_end,
+                    src_indent: first.begin.1,
+                    depth,
+                    kind: LineKind::Code {
+                        tokens,
+                        trailing: None,
+                    },
+                    blank_before: 0,
+                    physical: vec![],
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut standalone = vec![];
+        for comment in Self::collect_comments(cst) {
+            let (ln_begin, ln_end) = (comment.begin.0, comment.ln_end);
+            let unsupported = || Location::range(ln_begin as u32 + 1, 0, ln_end as u32 + 1, 0);
+            // a code line which has a token before the comment on the same line
+            let owner = lines.iter_mut().find(|line| {
+                let LineKind::Code { tokens, .. } = &line.kind else {
+                    return false;
+                };
+                tokens
+                    .iter()
+                    .any(|tok| tok.begin < comment.begin && tok.ln_end == ln_begin)
+            });
+            if let Some(owner) = owner {
+                let LineKind::Code { tokens, trailing } = &mut owner.kind else {
+                    unreachable!()
+                };
+                if ln_begin != ln_end || tokens.last().unwrap().begin > comment.begin {
+                    return Err(unsupported());
+                }
+                *trailing = Some(comment.text);
+                continue;
+            }
+            let followed = lines.iter().any(|line| {
+                let LineKind::Code { tokens, .. } = &line.kind else {
+                    return false;
+                };
+                tokens
+                    .iter()
+                    .any(|tok| tok.begin > comment.begin && tok.begin.0 == ln_end)
+            });
+            if followed {
+                return Err(unsupported());
+            }
+            standalone.push(Line {
+                ln_begin,
+                ln_end,
+                src_indent: comment.begin.1,
+                depth: 0,
+                kind: LineKind::Comment { text: comment.text },
+                blank_before: 0,
+                physical: vec![],
+            });
+        }
+        lines.extend(standalone);
+        lines.sort_by_key(|line| line.ln_begin);
+        self.set_comment_depths(&mut lines);
+        for i in 1..lines.len() {
+            lines[i].blank_before = lines[i]
+                .ln_begin
+                .saturating_sub(lines[i - 1].ln_end + 1)
+                .min(1);
+        }
+        Ok(lines)
+    }
+
+    /// A standalone comment is indented like the nearest code line with the same original indentation.
+    fn set_comment_depths(&self, lines: &mut [Line]) {
+        for i in 0..lines.len() {
+            if lines[i].is_code() {
+                continue;
+            }
+            let prev = lines[..i].iter().rev().find(|line| line.is_code());
+            let next = lines[i + 1..].iter().find(|line| line.is_code());
+            let indent = lines[i].src_indent;
+            let depth = match (prev, next) {
+                (_, Some(next)) if next.src_indent == indent => next.depth,
+                (Some(prev), _) if prev.src_indent == indent => prev.depth,
+                (Some(prev), Some(next)) if prev.src_indent < indent && next.depth > prev.depth => {
+                    next.depth
+                }
+                (_, Some(next)) => next.depth,
+                (Some(prev), None) => prev.depth.min(indent / INDENT_WIDTH),
+                (None, None) => 0,
+            };
+            lines[i].depth = depth;
+        }
+    }
+
+    /// Normalizes the number of blank lines before each line.
+    fn arrange_blank_lines(&self, lines: &mut [Line], module: &Module) {
+        if let Some(first) = lines.first_mut() {
+            first.blank_before = 0;
+        }
+        for i in 1..lines.len() {
+            if lines[i - 1].is_code() && lines[i].depth > lines[i - 1].depth {
+                lines[i].blank_before = 0;
+            }
+        }
+        // (ln_begin, ln_end) of each top-level chunk (0-origin)
+        let chunks = module
+            .iter()
+            .filter_map(|chunk| {
+                let ln_begin = (chunk.ln_begin()? as usize).checked_sub(1)?;
+                let ln_end = (chunk.ln_end()? as usize).checked_sub(1)?;
+                Some((ln_begin, ln_end))
+            })
+            .collect::<Vec<_>>();
+        for (i, (ln_begin, _)) in chunks.iter().enumerate().skip(1) {
+            let (prev_begin, prev_end) = chunks[i - 1];
+            let prev_height = lines
+                .iter()
+                .filter(|line| line.is_code() && (prev_begin..=prev_end).contains(&line.ln_begin))
+                .flat_map(|line| line.physical.iter())
+                .map(|(text, _)| text.lines().count())
+                .sum::<usize>();
+            // e.g. `C = Class {...}` and `C.` (methods) are kept together
+            if prev_height <= 1 {
+                continue;
+            }
+            let Some(mut idx) = lines
+                .iter()
+                .position(|line| line.is_code() && line.depth == 0 && line.ln_begin == *ln_begin)
+            else {
+                continue;
+            };
+            // comments and decorators directly above the definition belong to it
+            while idx > 0
+                && lines[idx].blank_before == 0
+                && (!lines[idx - 1].is_code() || lines[idx - 1].is_decorator())
+                && lines[idx - 1].depth == 0
+                && lines[idx - 1].ln_begin > prev_end
+            {
+                idx -= 1;
+            }
+            if idx > 0 {
+                lines[idx].blank_before = 1;
+            }
+        }
+    }
+
+    fn needs_space(prev: &SrcToken, next: &SrcToken) -> bool {
+        let gap = next.gap;
+        match (prev.kind, next.kind) {
+            (LParen | LSqBr, _) | (_, RParen | RSqBr | Comma | Semi | Try) => false,
+            (Comma | Semi, RBrace) | (LBrace, Assign) | (Assign, RBrace) => gap,
+            (Comma | Semi, _) => true,
+            (Dot | DblColon, _) => false,
+            (_, Dot | DblColon) => gap,
+            (RefOp | RefMutOp, LParen) => gap,
+            (RefOp | RefMutOp, _) => true,
+            (_, _) if prev.kind.category() == TokenCategory::UnaryOp => false,
+            (_, Colon) => false,
+            (Colon, _) => true,
+            (Closed | LeftOpen | RightOpen | Open, _)
+            | (_, Closed | LeftOpen | RightOpen | Open) => gap,
+            (VBar, _) | (_, VBar) => gap,
+            (_, _) if Self::is_spaced_op(prev.kind) || Self::is_spaced_op(next.kind) => true,
+            _ => gap,
+        }
+    }
+
+    fn is_spaced_op(kind: TokenKind) -> bool {
+        match kind {
+            Assign | Walrus | FuncArrow | ProcArrow | SubtypeOf | SupertypeOf | As | Inclusion
+            | Pipe => true,
+            _ => kind.category() == TokenCategory::BinOp,
+        }
+    }
+
+    fn render(&self, tokens: &[SrcToken], depth: usize) -> String {
+        let mut s = String::new();
+        for (i, tok) in tokens.iter().enumerate() {
+            if i > 0 {
+                if tok.continued {
+                    s += " \\\n";
+                    s += &" ".repeat((depth + 1) * INDENT_WIDTH);
+                } else if Self::needs_space(&tokens[i - 1], tok) {
+                    s.push(' ');
+                }
+            }
+            s += &tok.text;
+        }
+        s
+    }
+
+    /// Breaks the first outermost `(...)` containing commas into one element per line.
+    /// Array literals are not broken because they cannot span multiple lines.
+    fn break_line(&self, tokens: &[SrcToken], depth: usize) -> Option<Vec<(String, usize)>> {
+        let mut level = 0usize;
+        let mut open = None;
+        let mut commas = vec![];
+        for (i, tok) in tokens.iter().enumerate() {
+            match tok.kind {
+                LParen | LSqBr | LBrace => {
+                    if level == 0 && tok.kind == LParen {
+                        open = Some(i);
+                        commas.clear();
+                    }
+                    level += 1;
+                }
+                RParen | RSqBr | RBrace => {
+                    level = level.checked_sub(1)?;
+                    if level == 0 && open.is_some() {
+                        if commas.is_empty() {
+                            open = None;
+                            continue;
+                        }
+                        let open = open?;
+                        let mut lines = vec![(self.render(&tokens[..=open], depth), depth)];
+                        let mut elem_begin = open + 1;
+                        for comma in commas.iter().copied().chain(std::iter::once(i)) {
+                            if elem_begin < comma {
+                                let elem = self.render(&tokens[elem_begin..comma], depth + 1);
+                                lines.push((elem + ",", depth + 1));
+                            }
+                            elem_begin = comma + 1;
+                        }
+                        lines.push((self.render(&tokens[i..], depth), depth));
+                        return Some(lines);
+                    }
+                }
+                Comma if level == 1 && open.is_some() => {
+                    commas.push(i);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Renders each line into physical lines (text, depth).
+    fn render_lines(&self, lines: &mut [Line]) {
+        for line in lines.iter_mut() {
+            line.physical = match &line.kind {
+                LineKind::Code { tokens, trailing } => {
+                    let rendered = self.render(tokens, line.depth);
+                    let too_long = line.depth * INDENT_WIDTH + rendered.chars().count() > MAX_WIDTH;
+                    let mut physical =
+                        if self.break_long_lines && too_long && !rendered.contains('\n') {
+                            self.break_line(tokens, line.depth)
+                                .unwrap_or_else(|| vec![(rendered, line.depth)])
+                        } else {
+                            vec![(rendered, line.depth)]
+                        };
+                    if let Some(comment) = trailing {
+                        let last = &mut physical.last_mut().unwrap().0;
+                        last.push(' ');
+                        last.push_str(comment);
+                    }
+                    physical
+                }
+                LineKind::Comment { text } => vec![(text.clone(), line.depth)],
+            };
+        }
+    }
+
+    fn emit(&self, lines: &[Line]) -> String {
+        let mut code = String::new();
+        for line in lines {
+            for _ in 0..line.blank_before {
+                code.push('\n');
+            }
+            for (text, depth) in line.physical.iter() {
+                code += &" ".repeat(depth * INDENT_WIDTH);
+                code += text;
+                code.push('\n');
+            }
+        }
+        code
+    }
+
+    fn format_with(&self, cst: &ConcreteSyntaxTree, module: &Module) -> Result<String, Location> {
+        let mut lines = self.build_lines(cst)?;
+        self.render_lines(&mut lines);
+        self.arrange_blank_lines(&mut lines, module);
+        Ok(self.emit(&lines))
+    }
+}
+
+/// Formats the source code.
+/// Returns `Err` if the source code has syntax errors or cannot be formatted without changing the meaning.
+pub fn format_code(src: &str) -> Result<String, ParserRunnerErrors> {
+    FormatterRunner::new(ErgConfig::default()).format(src.to_string())
+}
+
+#[derive(Debug, Default)]
+pub struct FormatterRunner {
+    cfg: ErgConfig,
+}
+
+impl Runnable for FormatterRunner {
+    type Err = ParserRunnerError;
+    type Errs = ParserRunnerErrors;
+    const NAME: &'static str = "Erg formatter";
+
+    #[inline]
+    fn new(cfg: ErgConfig) -> Self {
+        Self { cfg }
+    }
+
+    #[inline]
+    fn cfg(&self) -> &ErgConfig {
+        &self.cfg
+    }
+    #[inline]
+    fn cfg_mut(&mut self) -> &mut ErgConfig {
+        &mut self.cfg
+    }
+
+    #[inline]
+    fn finish(&mut self) {}
+
+    #[inline]
+    fn initialize(&mut self) {}
+
+    #[inline]
+    fn clear(&mut self) {}
+
+    fn exec(&mut self) -> Result<ExitStatus, Self::Errs> {
+        let src = normalize_newline(&self.cfg_mut().input.read());
+        let formatted = self.format(src.clone())?;
+        let path = self.input().path().to_path_buf();
+        if self.cfg.check_only {
+            if formatted == src {
+                return Ok(ExitStatus::OK);
+            }
+            println!("{} is not formatted", path.display());
+            return Ok(ExitStatus::ERR1);
+        }
+        if !self.input().is_file() {
+            print!("{formatted}");
+        } else if formatted != src {
+            fs::write(&path, formatted).map_err(|err| {
+                let mut err_ = ParseError::feature_error(0, Location::Unknown, "writing the file");
+                err_.set_hint(err.to_string());
+                self.error(err_)
+            })?;
+        }
+        Ok(ExitStatus::OK)
+    }
+
+    fn eval(&mut self, src: String) -> Result<String, ParserRunnerErrors> {
+        self.format(src)
+    }
+}
+
+impl FormatterRunner {
+    fn parse(&self, src: String) -> Result<(ConcreteSyntaxTree, Module), ParserRunnerErrors> {
+        let cst = ConcreteSyntaxTree::parse(src)
+            .map_err(|errs| ParserRunnerErrors::convert(self.input(), errs))?;
+        let module = cst
+            .to_ast()
+            .map_err(|iart| ParserRunnerErrors::convert(self.input(), iart.errors))?
+            .ast;
+        Ok((cst, module))
+    }
+
+    fn error(&self, err: ParseError) -> ParserRunnerErrors {
+        ParserRunnerErrors::convert(self.input(), ParseErrors::new(vec![err]))
+    }
+
+    pub fn format(&mut self, src: String) -> Result<String, ParserRunnerErrors> {
+        let src = normalize_newline(&src);
+        let (cst, module) = self.parse(src)?;
+        let mut formatter = Formatter::new();
+        // if breaking long lines changes the meaning of the program, retry without it
+        for break_long_lines in [true, false] {
+            formatter.break_long_lines = break_long_lines;
+            let formatted = formatter.format_with(&cst, &module).map_err(|loc| {
+                self.error(ParseError::feature_error(
+                    0,
+                    loc,
+                    "formatting comments placed inside an expression",
+                ))
+            })?;
+            match self.parse(formatted.clone()) {
+                Ok((_, formatted_module)) if formatted_module.to_string() == module.to_string() => {
+                    return Ok(formatted);
+                }
+                _ => {}
+            }
+        }
+        let loc = module
+            .iter()
+            .next()
+            .map_or(Location::Unknown, |chunk| chunk.loc());
+        Err(self.error(ParseError::compiler_bug(0, loc, fn_name!(), line!())))
+    }
+}
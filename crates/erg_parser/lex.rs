@@ -11,6 +11,7 @@ use erg_common::traits::DequeStream;
 use erg_common::traits::{Locational, Runnable, Stream};
 use erg_common::{debug_power_assert, fn_name_full, normalize_newline, switch_lang};
 
+use crate::cst::ConcreteSyntaxTree;
 use crate::error::{LexError, LexErrors, LexResult, LexerRunnerError, LexerRunnerErrors};
 use crate::token::{Token, TokenCategory, TokenKind, TokenStream};
 use TokenKind::*;
@@ -226,6 +227,13 @@ impl Lexer /*<'a>*/ {
         }
     }
 
+    /// Lexes the source code while keeping whitespaces and comments as trivia.
+    pub fn lex_lossless(self) -> Result<ConcreteSyntaxTree, LexErrors> {
+        let src = self.chars.iter().collect::<String>();
+        let ts = self.lex()?;
+        ConcreteSyntaxTree::new(&src, &ts).map_err(|err| LexErrors::new(vec![err]))
+    }
+
     fn emit_multiline_token(&mut self, kind: TokenKind, col_begin: u32, cont: &str) -> Token {
         let cont = self.str_cache.get(cont);
         let lineno = (self.lineno_token_starts + 2).saturating_sub(cont.lines().count() as u32);
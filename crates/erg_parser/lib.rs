@@ -7,8 +7,10 @@ extern crate erg_common;
 pub mod ast;
 pub mod build_ast;
 pub mod convert;
+pub mod cst;
 pub mod desugar;
 pub mod error;
+pub mod format;
 pub mod lex;
 pub mod parse;
 pub mod token;
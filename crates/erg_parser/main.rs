@@ -8,6 +8,7 @@ use erg_common::spawn::exec_new_thread;
 use erg_common::traits::{ExitStatus, Runnable};
 
 use erg_parser::build_ast::ASTBuilder;
+use erg_parser::format::FormatterRunner;
 use erg_parser::lex::LexerRunner;
 use erg_parser::ParserRunner;
 
@@ -16,6 +17,7 @@ fn run() {
     let stat = match cfg.mode {
         Lex => LexerRunner::run(cfg),
         Parse => ParserRunner::run(cfg),
+        Format => FormatterRunner::run(cfg),
         Desugar | Execute => ASTBuilder::run(cfg),
         other => {
             eprintln!("invalid mode: {other}");
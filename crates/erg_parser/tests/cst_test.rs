@@ -0,0 +1,144 @@
+use std::fs;
+
+use erg_common::error::Location;
+use erg_common::traits::{DequeStream, Locational};
+
+use erg_parser::cst::{ConcreteSyntaxTree, TriviaKind};
+use erg_parser::lex::Lexer;
+use erg_parser::Parser;
+
+const FILES: [&str; 13] = [
+    "tests/args.er",
+    "tests/comment.er",
+    "tests/containers.er",
+    "tests/dependent.er",
+    "tests/fib.er",
+    "tests/hello_world.er",
+    "tests/multi_line_str_literal.er",
+    "tests/simple_if.er",
+    "tests/stream.er",
+    "tests/test1_basic_syntax.er",
+    "tests/test2_advanced_syntax.er",
+    "tests/test3_literal_syntax.er",
+    "tests/unformatted.er",
+];
+
+#[test]
+fn cst_round_trip() {
+    for file in FILES {
+        let src = fs::read_to_string(file).unwrap().replace("\r\n", "\n");
+        let cst = ConcreteSyntaxTree::parse(src.clone()).unwrap();
+        assert_eq!(cst.to_string(), src, "{file}");
+    }
+}
+
+#[test]
+fn cst_to_ast() {
+    for file in ["tests/fib.er", "tests/stream.er", "tests/unformatted.er"] {
+        let src = fs::read_to_string(file).unwrap();
+        let cst = ConcreteSyntaxTree::parse(src.clone()).unwrap();
+        let ts = Lexer::from_str(src).lex().unwrap();
+        let expected = Parser::new(ts).parse().unwrap().ast;
+        assert_eq!(cst.to_ast().unwrap().ast.to_string(), expected.to_string());
+    }
+}
+
+#[test]
+fn cst_comments() {
+    let src = "# a\nx = 1 # b\n#[\nc\n]#\nprint! x\n";
+    let cst = ConcreteSyntaxTree::parse(src.to_string()).unwrap();
+    let comments = cst
+        .comments()
+        .map(|c| c.content.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(comments, ["# a", "# b", "#[\nc\n]#"]);
+    // `# b` is attached to `1`
+    let one = cst.get_token(2, 4).unwrap();
+    assert_eq!(&one.text[..], "1");
+    assert!(one
+        .trailing_trivia
+        .iter()
+        .any(|t| t.kind == TriviaKind::Comment));
+}
+
+#[test]
+fn cst_positions() {
+    // the content of a string token is unescaped, but CST keeps the original text
+    let src = "s = \"a\\nb\"\nt = \"\"\"\nc\n\"\"\"\nu = 1\n";
+    let cst = ConcreteSyntaxTree::parse(src.to_string()).unwrap();
+    let s = cst.tokens().find(|t| t.text.starts_with("\"a")).unwrap();
+    assert_eq!(s.loc(), Location::range(1, 4, 1, 10));
+    let t = cst.tokens().find(|t| t.text.starts_with("\"\"\"")).unwrap();
+    assert_eq!(t.loc(), Location::range(2, 4, 4, 3));
+    let u = cst.get_token(5, 0).unwrap();
+    assert_eq!(&u.text[..], "u");
+}
+
+#[test]
+fn cst_rewrite() {
+    let src = "x = 1 # x\nprint! x\n";
+    let mut cst = ConcreteSyntaxTree::parse(src.to_string()).unwrap();
+    for token in cst.tokens_mut().filter(|t| &t.text[..] == "x") {
+        token.set_text("long_name");
+    }
+    assert_eq!(cst.to_string(), "long_name = 1 # x\nprint! long_name\n");
+    let ts = cst.to_token_stream();
+    let last = ts
+        .iter()
+        .rev()
+        .find(|t| &t.content[..] == "long_name")
+        .unwrap();
+    assert_eq!((last.lineno, last.col_begin), (2, 7));
+    assert!(cst.to_ast().is_ok());
+}
+
+#[test]
+fn cst_doc_comments() {
+    let src = r#"## adds two numbers
+## ```erg
+## assert add(1, 2) == 3
+## ```
+add x, y = x + y
+
+#[[
+```erg
+assert 1 == 1
+```
+```python
+print(1)
+```
+]]#
+C = Class()
+C.
+    '''
+    ```erg
+    c = C.new()
+    ```
+    '''
+    f self = 1
+"#;
+    let cst = ConcreteSyntaxTree::parse(src.to_string()).unwrap();
+    let docs = cst.doc_comments();
+    assert_eq!(docs.len(), 3);
+    assert_eq!(docs[0].lineno, 1);
+    assert_eq!(docs[0].lines[0], "adds two numbers");
+    assert_eq!(
+        docs[0].code_blocks("erg"),
+        [(3, "assert add(1, 2) == 3".to_string())]
+    );
+    assert_eq!(
+        docs[1].code_blocks("erg"),
+        [(9, "assert 1 == 1".to_string())]
+    );
+    assert_eq!(
+        docs[1].code_blocks("python"),
+        [(12, "print(1)".to_string())]
+    );
+    assert_eq!(
+        docs[2].code_blocks("erg"),
+        [(19, "c = C.new()".to_string())]
+    );
+    assert_eq!(docs[0].range, (1, 4));
+    assert_eq!(docs[1].range, (7, 14));
+    assert_eq!(docs[2].range, (17, 21));
+}
@@ -0,0 +1,39 @@
+use std::fs;
+
+use erg_common::traits::Stream;
+
+use erg_parser::format::format_code;
+
+const UNFORMATTED: &str = "tests/unformatted.er";
+const FORMATTED: &str = "tests/formatted.er";
+
+#[test]
+fn format_unformatted() {
+    let src = fs::read_to_string(UNFORMATTED).unwrap();
+    let expected = fs::read_to_string(FORMATTED).unwrap();
+    assert_eq!(format_code(&src).unwrap(), expected);
+}
+
+#[test]
+fn format_is_idempotent() {
+    let src = fs::read_to_string(FORMATTED).unwrap();
+    assert_eq!(format_code(&src).unwrap(), src);
+}
+
+#[test]
+fn format_preserves_comments() {
+    let src = "# a\nx = 1 # b\n#[\nc\n]#\nprint! x\n";
+    assert_eq!(format_code(src).unwrap(), src);
+}
+
+#[test]
+fn format_comment_inside_expr() {
+    // comments between tokens cannot be moved without changing their meaning
+    let errs = format_code("print! #[a]#1\n").unwrap_err();
+    assert_eq!(errs.len(), 1);
+}
+
+#[test]
+fn format_syntax_error() {
+    assert!(format_code("x = (1\n").is_err());
+}
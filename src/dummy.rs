@@ -14,6 +14,8 @@ use erg_compiler::hir::Expr;
 use erg_compiler::ty::HasType;
 
 use erg_compiler::error::{CompileError, CompileErrors};
+use erg_compiler::module::SharedCompilerResource;
+use erg_compiler::watch::Watchable;
 use erg_compiler::Compiler;
 
 pub type EvalError = CompileError;
@@ -195,6 +197,15 @@ impl Drop for DummyVM {
     }
 }
 
+impl Watchable for DummyVM {
+    fn with_shared(cfg: ErgConfig, shared: SharedCompilerResource) -> Self {
+        Self {
+            compiler: Compiler::new_with_cache(cfg, shared),
+            stream: None,
+        }
+    }
+}
+
 impl Runnable for DummyVM {
     type Err = EvalError;
     type Errs = EvalErrors;
@@ -1,4 +1,6 @@
 extern crate erg_common;
 extern crate erg_compiler;
 mod dummy;
+pub mod package_manager;
+pub mod tester;
 pub use dummy::DummyVM;
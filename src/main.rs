@@ -7,27 +7,56 @@ use erg_common::spawn::exec_new_thread;
 use erg_common::traits::{ExitStatus, Runnable};
 
 use erg_parser::build_ast::ASTBuilder;
+use erg_parser::format::FormatterRunner;
 use erg_parser::lex::LexerRunner;
 use erg_parser::ParserRunner;
 
 use erg_compiler::build_hir::HIRBuilder;
+use erg_compiler::docgen::DocGenerator;
 use erg_compiler::lower::ASTLowerer;
+use erg_compiler::project::ProjectBuilder;
 use erg_compiler::transpile::Transpiler;
 use erg_compiler::ty::deserialize::Deserializer;
+use erg_compiler::watch::Watcher;
 use erg_compiler::Compiler;
 
+use erg::package_manager::PackageManager;
+use erg::tester::TestRunner;
 use erg::DummyVM;
 
 fn run() {
     let cfg = ErgConfig::parse();
-    let stat = match cfg.mode {
+    let stat = if cfg.watch {
+        match cfg.mode {
+            FullCheck => Watcher::new(cfg).watch::<HIRBuilder>(),
+            Compile => Watcher::new(cfg).watch::<Compiler>(),
+            Execute => Watcher::new(cfg).watch::<DummyVM>(),
+            _ => {
+                eprintln!("--watch is available only in check, compile and run modes");
+                ExitStatus::ERR1
+            }
+        }
+    } else {
+        run_mode(cfg)
+    };
+    std::process::exit(stat.code);
+}
+
+fn run_mode(cfg: ErgConfig) -> ExitStatus {
+    match cfg.mode {
         Lex => LexerRunner::run(cfg),
         Parse => ParserRunner::run(cfg),
         Desugar => ASTBuilder::run(cfg),
         TypeCheck => ASTLowerer::run(cfg),
         FullCheck => HIRBuilder::run(cfg),
+        // `erg compile <DIR>`: build all modules in the directory
+        Compile | Transpile if cfg.input.path().is_dir() => ProjectBuilder::run(cfg),
         Compile => Compiler::run(cfg),
         Transpile => Transpiler::run(cfg),
+        Format => FormatterRunner::run(cfg),
+        Test => TestRunner::run(cfg),
+        Pack => PackageManager::run(cfg),
+        Doc => DocGenerator::run(cfg),
         Execute => DummyVM::run(cfg),
         Read => Deserializer::run(cfg),
         LanguageServer => {
@@ -44,8 +73,7 @@ fn run() {
                 ExitStatus::ERR1
             }
         }
-    };
-    std::process::exit(stat.code);
+    }
 }
 
 fn main() {
@@ -0,0 +1,354 @@
+//! defines `PackageManager`, which handles `erg pack <command>`.
+use std::env;
+use std::fs::{self, remove_file};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use erg_common::config::ErgConfig;
+use erg_common::error::MultiErrorDisplay;
+use erg_common::io::Input;
+use erg_common::python_util::exec_pyc;
+use erg_common::traits::{ExitStatus, Runnable};
+
+use erg_compiler::build_hir::HIRBuilder;
+use erg_compiler::compat::check_api_compat;
+use erg_compiler::context::ModuleContext;
+use erg_compiler::error::{CompileError, CompileErrors};
+use erg_compiler::package::{DependencyResolver, Lockfile, PackageManifest, PackageType};
+use erg_compiler::project::ProjectBuilder;
+use erg_compiler::Compiler;
+
+use crate::tester::TestRunner;
+
+const USAGE: &str = "\
+USAGE:
+    erg pack init [app|lib|hybrid]
+    erg pack build [--release]
+    erg pack update
+    erg pack compat <OLD_PACKAGE_ROOT>";
+
+/// `erg pack <command> [ARGS]...`
+///
+/// The package root is the nearest ancestor directory (including the input directory) containing `package.er`.
+#[derive(Debug, Default)]
+pub struct PackageManager {
+    cfg: ErgConfig,
+}
+
+impl Runnable for PackageManager {
+    type Err = CompileError;
+    type Errs = CompileErrors;
+    const NAME: &'static str = "Erg package manager";
+
+    fn new(mut cfg: ErgConfig) -> Self {
+        if cfg.input.is_repl() {
+            cfg.input = Input::file(PathBuf::from("."));
+        }
+        Self { cfg }
+    }
+
+    #[inline]
+    fn cfg(&self) -> &ErgConfig {
+        &self.cfg
+    }
+    #[inline]
+    fn cfg_mut(&mut self) -> &mut ErgConfig {
+        &mut self.cfg
+    }
+
+    #[inline]
+    fn finish(&mut self) {}
+
+    #[inline]
+    fn initialize(&mut self) {}
+
+    #[inline]
+    fn clear(&mut self) {}
+
+    fn exec(&mut self) -> Result<ExitStatus, Self::Errs> {
+        let args = self.cfg.runtime_args.clone();
+        match args.first().copied() {
+            Some("init") => self.init(&args[1..]),
+            Some("build") => self.build(&args[1..]),
+            Some("update") => self.update(),
+            Some("compat") => self.compat(&args[1..]),
+            Some(other) => {
+                eprintln!("unknown command: {other}\n\n{USAGE}");
+                Ok(ExitStatus::ERR1)
+            }
+            None => {
+                println!("{USAGE}");
+                Ok(ExitStatus::OK)
+            }
+        }
+    }
+
+    fn eval(&mut self, _src: String) -> Result<String, Self::Errs> {
+        Ok("`erg pack` cannot be used in the REPL".to_string())
+    }
+}
+
+impl PackageManager {
+    fn input_dir(&self) -> PathBuf {
+        let path = self.input().path();
+        if path.is_dir() {
+            path.to_path_buf()
+        } else {
+            self.input().dir()
+        }
+    }
+
+    fn project_root(&self) -> Option<PathBuf> {
+        let dir = self.input_dir();
+        if dir.join(PackageManifest::FILENAME).exists() {
+            Some(dir)
+        } else {
+            self.input().project_root()
+        }
+    }
+
+    fn require_project_root(&self) -> Option<PathBuf> {
+        let root = self.project_root();
+        if root.is_none() {
+            eprintln!(
+                "{} not found in {} or any parent directory",
+                PackageManifest::FILENAME,
+                self.input_dir().display()
+            );
+        }
+        root
+    }
+
+    fn io_error(&self, errno: u32, path: &Path, err: io::Error) -> CompileErrors {
+        CompileErrors::from(CompileError::io_error(
+            self.cfg.input.clone(),
+            errno as usize,
+            path,
+            &err,
+        ))
+    }
+
+    /// Resolves the dependencies and updates `package.lock.er`.
+    /// If `locked`, the versions in the existing lockfile are preferred.
+    fn resolve(&self, root: &Path, locked: bool) -> Result<ErgConfig, CompileErrors> {
+        let mut resolver = DependencyResolver::new(self.cfg.copy());
+        let old_lock = Lockfile::load(&self.cfg, root)?;
+        if locked {
+            if let Some(lockfile) = old_lock.clone() {
+                resolver = resolver.with_lockfile(lockfile);
+            }
+        }
+        let resolution = resolver.resolve(root)?;
+        let lockfile = resolution.to_lockfile();
+        if old_lock.as_ref() != Some(&lockfile) {
+            lockfile
+                .write(root)
+                .map_err(|err| self.io_error(line!(), &root.join(Lockfile::FILENAME), err))?;
+        }
+        let mut cfg = self.cfg.copy();
+        cfg.packages = resolution.to_config_packages();
+        Ok(cfg)
+    }
+
+    /// `erg pack init [app|lib|hybrid]`: generates `package.er` and `src/` in the input directory.
+    fn init(&self, args: &[&str]) -> Result<ExitStatus, CompileErrors> {
+        let package_type = match args.first() {
+            Some(ty) => match ty.parse::<PackageType>() {
+                Ok(ty) => ty,
+                Err(_) => {
+                    eprintln!("invalid package type: {ty} (expected app, lib or hybrid)");
+                    return Ok(ExitStatus::ERR1);
+                }
+            },
+            None => PackageType::App,
+        };
+        let root = self.input_dir();
+        let manifest = root.join(PackageManifest::FILENAME);
+        if manifest.exists() {
+            eprintln!("{} already exists", manifest.display());
+            return Ok(ExitStatus::ERR1);
+        }
+        let name = root
+            .canonicalize()
+            .ok()
+            .and_then(|root| {
+                root.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .unwrap_or_else(|| "package".to_string());
+        let src_dir = root.join("src");
+        fs::create_dir_all(&src_dir).map_err(|err| self.io_error(line!(), &src_dir, err))?;
+        fs::write(&manifest, manifest_template(&name, package_type))
+            .map_err(|err| self.io_error(line!(), &manifest, err))?;
+        let mut sources = vec![];
+        if package_type.is_app() {
+            sources.push(("main.er", "print! \"Hello, world!\"\n"));
+        }
+        if package_type.is_lib() {
+            sources.push((
+                "lib.er",
+                "## Returns `x + y`.\n.add x: Int, y: Int = x + y\n",
+            ));
+        }
+        for (file, src) in sources {
+            let path = src_dir.join(file);
+            if !path.exists() {
+                fs::write(&path, src).map_err(|err| self.io_error(line!(), &path, err))?;
+            }
+        }
+        println!("created {package_type} package `{name}`");
+        Ok(ExitStatus::OK)
+    }
+
+    /// `erg pack build [--release]`: compiles `src/**/*.er` into `build/debug/<name>` (or `build/release/<name>`).
+    fn build(&self, args: &[&str]) -> Result<ExitStatus, CompileErrors> {
+        let mut release = false;
+        for arg in args {
+            match *arg {
+                "--release" => release = true,
+                other => {
+                    eprintln!("invalid argument: {other}\n\n{USAGE}");
+                    return Ok(ExitStatus::ERR1);
+                }
+            }
+        }
+        let Some(root) = self.require_project_root() else {
+            return Ok(ExitStatus::ERR1);
+        };
+        let manifest = PackageManifest::load(&self.cfg, &root)?;
+        let pkg_cfg = self.resolve(&root, true)?;
+        if release {
+            let mut runner = TestRunner::new(self.cfg.inherit(root.clone()));
+            if runner.exec()? != ExitStatus::OK {
+                eprintln!("the tests failed, the release build was aborted");
+                return Ok(ExitStatus::ERR1);
+            }
+        }
+        if let Some(script) = &manifest.pre_build {
+            if self.run_script(&root.join(script))? != ExitStatus::OK {
+                eprintln!("the pre_build script failed, the build was aborted");
+                return Ok(ExitStatus::ERR1);
+            }
+        }
+        let profile = if release { "release" } else { "debug" };
+        let build_dir = root.join("build").join(profile);
+        let mut builder =
+            ProjectBuilder::new_project(pkg_cfg, root.join("src"), &manifest.name, build_dir);
+        let artifact = builder.build()?;
+        artifact.warns.write_all_stderr();
+        if let Some(script) = &manifest.post_build {
+            if self.run_script(&root.join(script))? != ExitStatus::OK {
+                eprintln!("the post_build script failed, the build was aborted");
+                return Ok(ExitStatus::ERR1);
+            }
+        }
+        println!(
+            "built {} v{} ({profile}, {} module(s)) into {}",
+            manifest.name,
+            manifest.version,
+            artifact.object.len(),
+            builder.package_dir().display()
+        );
+        Ok(ExitStatus::OK)
+    }
+
+    /// `erg pack update`: resolves the dependencies ignoring `package.lock.er` and rewrites it.
+    fn update(&self) -> Result<ExitStatus, CompileErrors> {
+        let Some(root) = self.require_project_root() else {
+            return Ok(ExitStatus::ERR1);
+        };
+        let cfg = self.resolve(&root, false)?;
+        for pkg in cfg.packages.iter().skip(1) {
+            println!("locked {} v{}", pkg.name, pkg.version);
+        }
+        Ok(ExitStatus::OK)
+    }
+
+    /// `erg pack compat <OLD_PACKAGE_ROOT>`: checks that the version bump from the old package
+    /// is large enough for the API changes of `src/lib.er`.
+    fn compat(&self, args: &[&str]) -> Result<ExitStatus, CompileErrors> {
+        let Some(old_root) = args.first().map(PathBuf::from) else {
+            eprintln!("the root of the old package is not specified\n\n{USAGE}");
+            return Ok(ExitStatus::ERR1);
+        };
+        let Some(root) = self.require_project_root() else {
+            return Ok(ExitStatus::ERR1);
+        };
+        let old_manifest = PackageManifest::load(&self.cfg, &old_root)?;
+        let manifest = PackageManifest::load(&self.cfg, &root)?;
+        if !manifest.package_type.is_lib() || !old_manifest.package_type.is_lib() {
+            eprintln!("`erg pack compat` can only be used for lib packages");
+            return Ok(ExitStatus::ERR1);
+        }
+        let old = self.load_api(&old_root)?;
+        let new = self.load_api(&root)?;
+        let report = check_api_compat(&old, &new);
+        println!("{report}");
+        let bump = report.required_bump();
+        if bump.is_satisfied_by(&old_manifest.version, &manifest.version) {
+            println!("{} -> {}: ok", old_manifest.version, manifest.version);
+            Ok(ExitStatus::OK)
+        } else {
+            eprintln!(
+                "{} -> {}: the version must be {} or higher",
+                old_manifest.version,
+                manifest.version,
+                bump.apply(&old_manifest.version)
+            );
+            Ok(ExitStatus::ERR1)
+        }
+    }
+
+    /// Type-checks `<root>/src/lib.er` (without updating the lockfile).
+    fn load_api(&self, root: &Path) -> Result<ModuleContext, CompileErrors> {
+        let mut resolver = DependencyResolver::new(self.cfg.copy());
+        if let Some(lockfile) = Lockfile::load(&self.cfg, root)? {
+            resolver = resolver.with_lockfile(lockfile);
+        }
+        let mut cfg = self.cfg.inherit(root.join("src").join("lib.er"));
+        cfg.packages = resolver.resolve(root)?.to_config_packages();
+        let src = cfg.input.read();
+        let mut builder = HIRBuilder::new(cfg);
+        builder.build(src, "exec").map_err(|arti| arti.errors)?;
+        Ok(builder.pop_mod_ctx().unwrap())
+    }
+
+    /// Executes `pre_build` or `post_build` script.
+    fn run_script(&self, script: &Path) -> Result<ExitStatus, CompileErrors> {
+        let mut cfg = self.cfg.inherit(script.to_path_buf());
+        let src = cfg.input.read();
+        let pyc = env::temp_dir().join(format!("erg_pack_{}.pyc", cfg.input.id()));
+        let mut compiler = Compiler::new(cfg);
+        compiler
+            .compile_and_dump_as_pyc(&pyc, src, "exec")
+            .map_err(|eart| eart.errors)?;
+        let code = exec_pyc(
+            pyc.to_string_lossy(),
+            self.cfg.py_command,
+            &[],
+            Stdio::inherit(),
+        );
+        remove_file(&pyc).unwrap_or(());
+        if code == Some(0) {
+            Ok(ExitStatus::OK)
+        } else {
+            Ok(ExitStatus::ERR1)
+        }
+    }
+}
+
+fn manifest_template(name: &str, package_type: PackageType) -> String {
+    format!(
+        "\
+name = \"{name}\"
+author = \"\"
+version = \"0.1.0\"
+description = \"\"
+categories = []
+type = \"{package_type}\"
+license = \"\"
+dependencies = {{=}}
+"
+    )
+}
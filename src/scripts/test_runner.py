@@ -0,0 +1,52 @@
+import io
+import marshal
+import sys
+import traceback
+from contextlib import redirect_stderr, redirect_stdout
+
+def load_code(path):
+    with open(path, 'rb') as f:
+        # skip the header (magic number, flags, timestamp, size)
+        f.read(16)
+        return marshal.load(f)
+
+# returns the line of the innermost frame in the tested module (0 if unknown)
+def erg_lineno(code, exc):
+    lineno = 0
+    tb = exc.__traceback__
+    while tb is not None:
+        if tb.tb_frame.f_code.co_filename == code.co_filename and (tb.tb_lineno or 0) > 0:
+            lineno = tb.tb_lineno
+        tb = tb.tb_next
+    return lineno
+
+def run_test(code, name):
+    # each test is run in a freshly executed module so that tests do not affect each other
+    module = {'__name__': '__erg_test__'}
+    out = io.StringIO()
+    try:
+        with redirect_stdout(out), redirect_stderr(out):
+            exec(code, module)
+            module[name]()
+        return ('ok', 0, '')
+    except KeyboardInterrupt:
+        raise
+    except BaseException as exc:
+        status = 'failed' if isinstance(exc, AssertionError) else 'error'
+        msg = ''.join(traceback.format_exception_only(type(exc), exc)).strip()
+        if out.getvalue():
+            msg += '\n' + out.getvalue().rstrip()
+        return (status, erg_lineno(code, exc), msg)
+
+def main():
+    pyc, src_dir, names = sys.argv[1], sys.argv[2], sys.argv[3:]
+    sys.path.insert(0, src_dir)
+    code = load_code(pyc)
+    for (i, name) in enumerate(names):
+        (status, lineno, msg) = run_test(code, name)
+        print(status, i, lineno)
+        for line in msg.splitlines():
+            print('|', line)
+        sys.stdout.flush()
+
+main()
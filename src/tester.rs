@@ -0,0 +1,444 @@
+//! defines `TestRunner`, which collects and runs `@Test` subroutines and doctests.
+use std::env;
+use std::fs::{self, remove_file};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use erg_common::config::ErgConfig;
+use erg_common::error::MultiErrorDisplay;
+use erg_common::io::Input;
+use erg_common::pathutil::collect_files;
+use erg_common::python_util::opt_which_python;
+use erg_common::style::remove_style;
+use erg_common::traits::{ExitStatus, Runnable, Stream};
+
+use erg_parser::ast::{Expr, Module};
+use erg_parser::cst::ConcreteSyntaxTree;
+
+use erg_compiler::error::{CompileError, CompileErrors};
+use erg_compiler::ty::codeobj::CodeObj;
+use erg_compiler::Compiler;
+use erg_compiler::TestCase;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TestStatus {
+    Ok,
+    /// an assertion failed
+    Failed,
+    /// other exceptions were raised
+    Error,
+    Ignored,
+}
+
+#[derive(Debug, Default)]
+struct TestSummary {
+    passed: usize,
+    failed: usize,
+    ignored: usize,
+    /// modules that could not be compiled
+    broken_modules: usize,
+}
+
+impl TestSummary {
+    fn is_ok(&self) -> bool {
+        self.failed == 0 && self.broken_modules == 0
+    }
+}
+
+/// Runs the subroutines decorated with `@Test` in `<dir>/tests/**/*.er` and `<dir>/**/*.test.er`,
+/// and the ` ```erg ` code blocks in the doc comments of `<dir>/**/*.er` (doctests).
+///
+/// Each test module is compiled, and each test is run in a freshly executed module.
+#[derive(Debug, Default)]
+pub struct TestRunner {
+    cfg: ErgConfig,
+}
+
+impl Runnable for TestRunner {
+    type Err = CompileError;
+    type Errs = CompileErrors;
+    const NAME: &'static str = "Erg test runner";
+
+    fn new(mut cfg: ErgConfig) -> Self {
+        // `erg test` (without arguments) runs the tests in the current directory
+        if cfg.input.is_repl() {
+            cfg.input = Input::file(PathBuf::from("."));
+        }
+        Self { cfg }
+    }
+
+    #[inline]
+    fn cfg(&self) -> &ErgConfig {
+        &self.cfg
+    }
+    #[inline]
+    fn cfg_mut(&mut self) -> &mut ErgConfig {
+        &mut self.cfg
+    }
+
+    #[inline]
+    fn finish(&mut self) {}
+
+    #[inline]
+    fn initialize(&mut self) {}
+
+    #[inline]
+    fn clear(&mut self) {}
+
+    fn exec(&mut self) -> Result<ExitStatus, Self::Errs> {
+        let mut summary = TestSummary::default();
+        if self.input().is_file() {
+            let test_files = collect_test_files(self.input().path());
+            let doc_files = collect_doc_files(self.input().path());
+            if test_files.is_empty() && doc_files.is_empty() {
+                println!("no tests found in {}", self.input().path().display());
+            }
+            let mut files = test_files.iter().chain(&doc_files).collect::<Vec<_>>();
+            files.sort();
+            files.dedup();
+            for file in files {
+                let src = fs::read_to_string(file).unwrap_or_default();
+                let cfg = self.cfg.inherit(file.clone());
+                if test_files.contains(file) {
+                    self.run_module(cfg.copy(), src.clone(), &mut summary);
+                }
+                self.run_doctests(cfg, src, &mut summary);
+            }
+        } else {
+            let src = self.cfg_mut().input.read();
+            self.run_module(self.cfg.copy(), src.clone(), &mut summary);
+            self.run_doctests(self.cfg.copy(), src, &mut summary);
+        }
+        println!(
+            "\ntest result: {}. {} passed; {} failed; {} ignored",
+            if summary.is_ok() { "ok" } else { "FAILED" },
+            summary.passed,
+            summary.failed,
+            summary.ignored,
+        );
+        if summary.broken_modules > 0 {
+            println!("{} module(s) could not be compiled", summary.broken_modules);
+        }
+        if summary.is_ok() {
+            Ok(ExitStatus::OK)
+        } else {
+            Ok(ExitStatus::ERR1)
+        }
+    }
+
+    fn eval(&mut self, _src: String) -> Result<String, Self::Errs> {
+        Ok("`erg test` cannot be used in the REPL".to_string())
+    }
+}
+
+impl TestRunner {
+    fn run_module(&self, cfg: ErgConfig, src: String, summary: &mut TestSummary) {
+        let path = cfg.input.path().to_path_buf();
+        let mut compiler = Compiler::new(cfg);
+        let arti = match compiler.compile_tests(src) {
+            Ok(arti) => arti,
+            Err(eart) => {
+                eart.errors.write_all_stderr();
+                summary.broken_modules += 1;
+                return;
+            }
+        };
+        let (code, tests) = arti.object;
+        if tests.is_empty() {
+            return;
+        }
+        println!("running {} test(s) in {}", tests.len(), path.display());
+        let (runnable, ignored) = tests
+            .into_iter()
+            .partition::<Vec<_>, _>(|test| test.params == 0);
+        for test in ignored.iter() {
+            report(&path, test, TestStatus::Ignored, 0, &[]);
+            summary.ignored += 1;
+        }
+        if runnable.is_empty() {
+            return;
+        }
+        let results = run_pyc(&compiler, code, &runnable);
+        for (test, (status, lineno, msg)) in runnable.iter().zip(results) {
+            report(&path, test, status, lineno, &msg);
+            match status {
+                TestStatus::Ok => summary.passed += 1,
+                TestStatus::Failed | TestStatus::Error => summary.failed += 1,
+                TestStatus::Ignored => summary.ignored += 1,
+            }
+        }
+    }
+
+    /// Runs each ` ```erg ` code block in the doc comments of the module as a test.
+    /// The public names of the module are imported into the code block.
+    ///
+    /// The code blocks are taken from the CST, since `##` and `#[[...]]#` doc comments are trivia and not in the AST.
+    /// Each block is compiled by `Compiler` (`HIRBuilder` and the code generator), because it has to be executed.
+    fn run_doctests(&self, cfg: ErgConfig, src: String, summary: &mut TestSummary) {
+        let Ok(cst) = ConcreteSyntaxTree::parse(src) else {
+            return;
+        };
+        let doctests = cst
+            .doc_comments()
+            .iter()
+            .flat_map(|doc| doc.code_blocks("erg"))
+            .filter(|(_, code)| !code.trim().is_empty())
+            .collect::<Vec<_>>();
+        if doctests.is_empty() {
+            return;
+        }
+        let path = cfg.input.path().to_path_buf();
+        let stem = cfg.input.file_stem();
+        // a module given by `-c` or a pipe cannot be imported
+        let publics = if cfg.input.is_file() {
+            cst.to_ast()
+                .map(|arti| public_names(&arti.ast))
+                .unwrap_or_default()
+        } else {
+            vec![]
+        };
+        println!(
+            "running {} doctest(s) in {}",
+            doctests.len(),
+            path.display()
+        );
+        for (lineno, code) in doctests {
+            let (header, doctest_src) = doctest_module(&stem, &publics, lineno, &code);
+            // the doctest module is placed next to the module so that the module can be imported
+            let doctest_path = cfg.input.dir().join(format!("{stem}.doctest.er"));
+            let mut compiler = Compiler::new(cfg.inherit(doctest_path));
+            // maps a line of the doctest module to the line of the doc comment
+            let map_line = |ln: u32| (ln + lineno).saturating_sub(header + 1);
+            let (status, err_line, msg) = match compiler.compile_tests(doctest_src) {
+                Ok(arti) => {
+                    let (code, tests) = arti.object;
+                    let (status, ln, msg) = run_pyc(&compiler, code, &tests).pop().unwrap_or((
+                        TestStatus::Error,
+                        0,
+                        vec![],
+                    ));
+                    (status, if ln > header { map_line(ln) } else { 0 }, msg)
+                }
+                Err(eart) => {
+                    let err_line = eart
+                        .errors
+                        .iter()
+                        .filter_map(|err| err.core.loc.ln_begin())
+                        .find(|ln| *ln > header)
+                        .map_or(0, map_line);
+                    let msg = eart
+                        .errors
+                        .into_iter()
+                        .map(|err| {
+                            let ln = err.core.loc.ln_begin().unwrap_or(0);
+                            let msg = remove_style(&err.core.main_message);
+                            if ln > header {
+                                format!("line {}: {msg}", map_line(ln))
+                            } else {
+                                msg
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    (TestStatus::Failed, err_line, msg)
+                }
+            };
+            report_doctest(&path, lineno, status, err_line, &msg);
+            if status == TestStatus::Ok {
+                summary.passed += 1;
+            } else {
+                summary.failed += 1;
+            }
+        }
+    }
+}
+
+/// Returns (the number of the lines before the code block, a module which runs `code` as a test).
+fn doctest_module(stem: &str, publics: &[String], lineno: u32, code: &str) -> (u32, String) {
+    let mut src = String::new();
+    if !publics.is_empty() {
+        src += &format!("{{{};}} = import \"{stem}\"\n", publics.join("; "));
+    }
+    src += &format!("@Test\ndoctest_L{lineno}!() =\n");
+    let header = src.lines().count() as u32;
+    for line in code.lines() {
+        src += &format!("    {line}\n");
+    }
+    (header, src)
+}
+
+fn public_names(module: &Module) -> Vec<String> {
+    let mut names = vec![];
+    for expr in module.iter() {
+        let ident = match expr {
+            Expr::Def(def) => def.sig.ident(),
+            Expr::ClassDef(class_def) => class_def.def.sig.ident(),
+            _ => None,
+        };
+        if let Some(ident) = ident.filter(|ident| ident.vis.is_public()) {
+            names.push(ident.inspect().to_string());
+        }
+    }
+    names
+}
+
+/// Runs the tests in `code` and returns the results in the order of `tests`.
+/// If the tests cannot be run at all, all of them are reported as errors.
+fn run_pyc(
+    compiler: &Compiler,
+    code: CodeObj,
+    tests: &[TestCase],
+) -> Vec<(TestStatus, u32, Vec<String>)> {
+    let pyc = env::temp_dir().join(format!("erg_test_{}.pyc", compiler.input().id()));
+    let out = code
+        .dump_as_pyc(&pyc, compiler.cfg.py_magic_num)
+        .map_err(|err| format!("failed to dump a .pyc file: {err}"))
+        .and_then(|_| exec_test_runner(compiler.cfg.py_command, &pyc, compiler.input(), tests));
+    remove_file(&pyc).unwrap_or(());
+    let stdout = match out {
+        Ok(out) => String::from_utf8_lossy(&out.stdout).to_string(),
+        Err(msg) => return vec![(TestStatus::Error, 0, vec![msg]); tests.len()],
+    };
+    let mut results = parse_results(&stdout);
+    (0..tests.len())
+        .map(|i| {
+            results
+                .iter_mut()
+                .find(|(idx, ..)| *idx == i)
+                .map(|(_, status, lineno, msg)| (*status, *lineno, std::mem::take(msg)))
+                // the test process has crashed
+                .unwrap_or((TestStatus::Error, 0, vec![]))
+        })
+        .collect()
+}
+
+/// Executes `scripts/test_runner.py` with the configured Python (the one the `.pyc` is dumped for).
+/// The arguments are passed without a shell, so paths may contain spaces.
+fn exec_test_runner(
+    py_command: Option<&str>,
+    pyc: &Path,
+    input: &Input,
+    tests: &[TestCase],
+) -> Result<Output, String> {
+    let py_command = match py_command {
+        Some(cmd) => cmd.to_string(),
+        None => opt_which_python()?,
+    };
+    Command::new(&py_command)
+        .arg("-c")
+        .arg(include_str!("scripts/test_runner.py"))
+        .arg(pyc)
+        .arg(input.dir())
+        .args(tests.iter().map(|test| &test.py_name[..]))
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|err| format!("cannot execute {py_command}: {err}"))
+}
+
+fn report_doctest(path: &Path, lineno: u32, status: TestStatus, err_line: u32, msg: &[String]) {
+    let status_str = match status {
+        TestStatus::Ok => "ok",
+        TestStatus::Failed => "FAILED",
+        TestStatus::Error => "ERROR",
+        TestStatus::Ignored => "ignored",
+    };
+    println!("doctest {}:{lineno} ... {status_str}", path.display());
+    if matches!(status, TestStatus::Failed | TestStatus::Error) {
+        let lineno = if err_line > 0 { err_line } else { lineno };
+        println!("    --> {}:{lineno}", path.display());
+        for line in msg {
+            println!("    {line}");
+        }
+    }
+}
+
+fn report(path: &Path, test: &TestCase, status: TestStatus, lineno: u32, msg: &[String]) {
+    let status_str = match status {
+        TestStatus::Ok => "ok",
+        TestStatus::Failed => "FAILED",
+        TestStatus::Error => "ERROR",
+        TestStatus::Ignored => "ignored (tests with parameters are not supported)",
+    };
+    println!("test {} ... {status_str}", test.name);
+    if matches!(status, TestStatus::Failed | TestStatus::Error) {
+        // failures are reported at the location in the Erg source (or the test definition)
+        let lineno = if lineno > 0 {
+            lineno
+        } else {
+            test.loc.ln_begin().unwrap_or(0)
+        };
+        println!("    --> {}:{lineno}", path.display());
+        for line in msg {
+            println!("    {line}");
+        }
+    }
+}
+
+/// Parses the output of `scripts/test_runner.py`:
+///
+/// ```text
+/// <status> <index> <lineno>
+/// | <message>
+/// ```
+fn parse_results(stdout: &str) -> Vec<(usize, TestStatus, u32, Vec<String>)> {
+    let mut results: Vec<(usize, TestStatus, u32, Vec<String>)> = vec![];
+    for line in stdout.lines() {
+        if let Some(msg) = line.strip_prefix("| ") {
+            if let Some(last) = results.last_mut() {
+                last.3.push(msg.to_string());
+            }
+            continue;
+        }
+        let mut elems = line.split(' ');
+        let status = match elems.next() {
+            Some("ok") => TestStatus::Ok,
+            Some("failed") => TestStatus::Failed,
+            Some("error") => TestStatus::Error,
+            _ => continue,
+        };
+        let (Some(Ok(idx)), Some(Ok(lineno))) =
+            (elems.next().map(str::parse), elems.next().map(str::parse))
+        else {
+            continue;
+        };
+        results.push((idx, status, lineno, vec![]));
+    }
+    results
+}
+
+fn is_erg_source(name: &str) -> bool {
+    name.ends_with(".er") && !name.ends_with(".d.er")
+}
+
+/// Collects `<root>/tests/**/*.er` and `<root>/**/*.test.er` which contain `@Test`.
+/// If `root` is a file, it is returned as is.
+pub fn collect_test_files(root: &Path) -> Vec<PathBuf> {
+    if root.is_file() {
+        return vec![root.to_path_buf()];
+    }
+    let tests = root.join("tests");
+    let mut files = collect_files(
+        root,
+        |_| true,
+        |file| {
+            let name = file.file_name().unwrap_or_default().to_string_lossy();
+            name.ends_with(".test.er") || (file.starts_with(&tests) && is_erg_source(&name))
+        },
+    );
+    files.retain(|file| fs::read_to_string(file).is_ok_and(|src| src.contains("@Test")));
+    files
+}
+
+/// Collects `<root>/**/*.er` which contain ` ```erg ` (candidates of doctests).
+/// If `root` is a file, it is returned as is.
+pub fn collect_doc_files(root: &Path) -> Vec<PathBuf> {
+    if root.is_file() {
+        return vec![root.to_path_buf()];
+    }
+    let mut files = collect_files(
+        root,
+        |_| true,
+        |file| is_erg_source(&file.file_name().unwrap_or_default().to_string_lossy()),
+    );
+    files.retain(|file| fs::read_to_string(file).is_ok_and(|src| src.contains("```erg")));
+    files
+}
@@ -13,6 +13,7 @@ use erg_common::traits::{ExitStatus, Runnable, Stream};
 use erg_compiler::error::CompileErrors;
 use erg_compiler::Compiler;
 
+use erg::tester::TestRunner;
 use erg::DummyVM;
 
 pub(crate) fn expect_repl_success(name: &'static str, lines: Vec<String>) -> Result<(), ()> {
@@ -154,6 +155,27 @@ pub(crate) fn expect_end_with(
     }
 }
 
+/// Runs the `@Test` subroutines in the file
+pub(crate) fn expect_tests_end_with(file_path: &'static str, code: i32) -> Result<(), ()> {
+    match exec_tests(file_path) {
+        Ok(stat) if stat.code == code => Ok(()),
+        Ok(stat) => {
+            println!(
+                "err[{file_path}]: should end with {code}, but end with {}",
+                stat.code
+            );
+            Err(())
+        }
+        Err(errs) => {
+            if DEBUG_MODE {
+                errs.write_all_stderr();
+            }
+            println!("err[{file_path}]: should end with {code}, but got errors");
+            Err(())
+        }
+    }
+}
+
 pub(crate) fn expect_failure(
     file_path: &'static str,
     num_warns: usize,
@@ -243,6 +265,13 @@ pub fn _exec_compiler(file_path: &'static str) -> Result<ExitStatus, CompileErro
     compiler.exec()
 }
 
+pub fn _exec_tests(file_path: &'static str) -> Result<ExitStatus, CompileErrors> {
+    println!("{DEBUG_MAIN}[test] exec test runner: {file_path}{RESET}");
+    let cfg = ErgConfig::with_main_path(PathBuf::from(file_path));
+    let mut runner = TestRunner::new(set_cfg(cfg));
+    runner.exec()
+}
+
 pub(crate) fn exec_file(file_path: &'static str) -> Result<ExitStatus, CompileErrors> {
     exec_new_thread(move || _exec_file(file_path), file_path)
 }
@@ -257,3 +286,7 @@ pub(crate) fn exec_repl(
 pub(crate) fn exec_compiler(file_path: &'static str) -> Result<ExitStatus, CompileErrors> {
     exec_new_thread(move || _exec_compiler(file_path), file_path)
 }
+
+pub(crate) fn exec_tests(file_path: &'static str) -> Result<ExitStatus, CompileErrors> {
+    exec_new_thread(move || _exec_tests(file_path), file_path)
+}
@@ -1,5 +1,7 @@
 mod common;
-use common::{expect_compile_success, expect_end_with, expect_failure, expect_success};
+use common::{
+    expect_compile_success, expect_end_with, expect_failure, expect_success, expect_tests_end_with,
+};
 use erg_common::python_util::{module_exists, opt_which_python};
 
 #[test]
@@ -27,6 +29,11 @@ fn exec_assert_cast_ok() -> Result<(), ()> {
     expect_success("tests/should_ok/assert_cast.er", 0)
 }
 
+#[test]
+fn exec_assert_test_ok() -> Result<(), ()> {
+    expect_tests_end_with("tests/should_ok/assert.test.er", 0)
+}
+
 #[test]
 fn exec_class() -> Result<(), ()> {
     expect_success("examples/class.er", 0)
@@ -52,6 +59,11 @@ fn exec_comment() -> Result<(), ()> {
     expect_success("tests/should_ok/comment.er", 0)
 }
 
+#[test]
+fn exec_const_func() -> Result<(), ()> {
+    expect_success("tests/should_ok/const_func.er", 0)
+}
+
 #[test]
 fn exec_control() -> Result<(), ()> {
     expect_success("examples/control.er", 2)
@@ -82,6 +94,11 @@ fn exec_dict() -> Result<(), ()> {
     expect_success("examples/dict.er", 0)
 }
 
+#[test]
+fn exec_doctest_ok() -> Result<(), ()> {
+    expect_tests_end_with("tests/should_ok/doctest.er", 0)
+}
+
 #[test]
 fn exec_external() -> Result<(), ()> {
     let py_command = opt_which_python().unwrap();
@@ -97,6 +114,16 @@ fn exec_fib() -> Result<(), ()> {
     expect_success("examples/fib.er", 0)
 }
 
+#[test]
+fn exec_gadt() -> Result<(), ()> {
+    expect_success("tests/should_ok/gadt.er", 0)
+}
+
+#[test]
+fn exec_gadt_import() -> Result<(), ()> {
+    expect_success("tests/should_ok/gadt/import.er", 0)
+}
+
 #[test]
 fn exec_helloworld() -> Result<(), ()> {
     // HACK: When running the test with Windows, the exit code is 1 (the cause is unknown)
@@ -128,6 +155,16 @@ fn exec_import_cyclic() -> Result<(), ()> {
     expect_success("tests/should_ok/cyclic/import.er", 0)
 }
 
+#[test]
+fn exec_import_cyclic_destructure() -> Result<(), ()> {
+    expect_success("tests/should_ok/cyclic/destructure.er", 0)
+}
+
+#[test]
+fn exec_import_cyclic_entry() -> Result<(), ()> {
+    expect_success("tests/should_ok/cyclic/entry.er", 0)
+}
+
 #[test]
 fn exec_index() -> Result<(), ()> {
     expect_success("tests/should_ok/index.er", 0)
@@ -208,6 +245,11 @@ fn exec_never() -> Result<(), ()> {
     expect_success("tests/should_ok/never.er", 0)
 }
 
+#[test]
+fn exec_newtype() -> Result<(), ()> {
+    expect_success("tests/should_ok/newtype.er", 0)
+}
+
 #[test]
 fn exec_operators() -> Result<(), ()> {
     expect_success("tests/should_ok/operators.er", 0)
@@ -358,6 +400,11 @@ fn exec_assert_cast() -> Result<(), ()> {
     expect_failure("examples/assert_cast.er", 0, 3)
 }
 
+#[test]
+fn exec_assert_test() -> Result<(), ()> {
+    expect_tests_end_with("tests/should_err/assert.test.er", 1)
+}
+
 #[test]
 fn exec_class_attr_err() -> Result<(), ()> {
     expect_failure("tests/should_err/class_attr.er", 1, 1)
@@ -368,16 +415,36 @@ fn exec_collection_err() -> Result<(), ()> {
     expect_failure("tests/should_err/collection.er", 0, 4)
 }
 
+#[test]
+fn exec_const_cycle_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/const_cycle.er", 0, 2)
+}
+
+#[test]
+fn exec_const_func_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/const_func.er", 0, 2)
+}
+
 #[test]
 fn exec_dependent_err() -> Result<(), ()> {
     expect_failure("tests/should_err/dependent.er", 0, 5)
 }
 
+#[test]
+fn exec_doctest() -> Result<(), ()> {
+    expect_tests_end_with("tests/should_err/doctest.er", 1)
+}
+
 #[test]
 fn exec_err_import() -> Result<(), ()> {
     expect_failure("tests/should_err/err_import.er", 0, 9)
 }
 
+#[test]
+fn exec_gadt_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/gadt.er", 0, 4)
+}
+
 /// This file compiles successfully, but causes a run-time error due to incomplete method dispatching
 #[test]
 fn exec_tests_impl() -> Result<(), ()> {
@@ -399,6 +466,11 @@ fn exec_import_cyclic_err() -> Result<(), ()> {
     expect_failure("tests/should_err/cyclic/import.er", 0, 1)
 }
 
+#[test]
+fn exec_import_cyclic_type_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/cyclic/type.er", 0, 2)
+}
+
 #[test]
 fn exec_infer_union_array() -> Result<(), ()> {
     expect_failure("tests/should_err/infer_union_array.er", 2, 1)
@@ -488,6 +560,11 @@ fn exec_mut_dict_err() -> Result<(), ()> {
     expect_failure("tests/should_err/mut_dict.er", 0, 3)
 }
 
+#[test]
+fn exec_newtype_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/newtype.er", 0, 6)
+}
+
 #[test]
 fn exec_quantified_err() -> Result<(), ()> {
     expect_failure("tests/should_err/quantified.er", 0, 3)